@@ -0,0 +1,287 @@
+use crate::access_control::events::{
+    OwnerProposed, OwnershipTransferred, RoleGranted, RoleRevoked,
+};
+use crate::access_control::{AccessControlCore, Role};
+use near_sdk::store::{IterableSet, LookupMap};
+use near_sdk::{assert_one_yocto, env, near, require, AccountId, IntoStorageKey};
+
+const ERR_NOT_OWNER: &str = "Method is only available to the owner";
+
+/// Implementation of an owner with a two-step ownership transfer and of roles granted by the
+/// owner. See [`AccessControlCore`] for the methods a contract can expose.
+#[near]
+pub struct AccessControl {
+    /// Current owner of the contract.
+    pub owner_id: AccountId,
+
+    /// Account which can accept the ownership, if a transfer is in progress.
+    pub proposed_owner_id: Option<AccountId>,
+
+    /// Role -> accounts which have it.
+    pub roles: LookupMap<Role, IterableSet<AccountId>>,
+
+    /// Prefix used to derive the storage prefix of each role's members.
+    prefix: Vec<u8>,
+}
+
+impl AccessControl {
+    pub fn new<S>(prefix: S, owner_id: AccountId) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        let prefix = prefix.into_storage_key();
+        Self {
+            owner_id,
+            proposed_owner_id: None,
+            roles: LookupMap::new([prefix.as_slice(), b"r"].concat()),
+            prefix,
+        }
+    }
+
+    /// Returns whether `account_id` is the owner.
+    pub fn is_owner(&self, account_id: &AccountId) -> bool {
+        &self.owner_id == account_id
+    }
+
+    /// Returns whether `account_id` has `role`.
+    pub fn has_role(&self, role: &str, account_id: &AccountId) -> bool {
+        self.roles.get(role).is_some_and(|members| members.contains(account_id))
+    }
+
+    /// Panics if the predecessor is not the owner.
+    pub fn assert_owner(&self) {
+        require!(self.is_owner(&env::predecessor_account_id()), ERR_NOT_OWNER);
+    }
+
+    /// Panics if the predecessor doesn't have `role`.
+    pub fn assert_role(&self, role: &str) {
+        if !self.has_role(role, &env::predecessor_account_id()) {
+            env::panic_str(&format!("Method requires the {} role", role));
+        }
+    }
+
+    /// Panics if the predecessor neither is the owner nor has any of `roles`.
+    pub fn assert_owner_or_any_role(&self, roles: &[&str]) {
+        let predecessor = env::predecessor_account_id();
+        if !self.is_owner(&predecessor)
+            && !roles.iter().any(|role| self.has_role(role, &predecessor))
+        {
+            env::panic_str(&format!("Method requires the owner or one of the roles {:?}", roles));
+        }
+    }
+
+    /// Grants `role` to `account_id` without checking the caller, which is useful in the
+    /// contract initializer. Returns whether the account didn't already have the role.
+    pub fn internal_grant_role(&mut self, role: Role, account_id: AccountId) -> bool {
+        let members_prefix = [self.prefix.as_slice(), b"m", &env::sha256(role.as_bytes())].concat();
+        let granted = self
+            .roles
+            .entry(role.clone())
+            .or_insert_with(|| IterableSet::new(members_prefix))
+            .insert(account_id.clone());
+        if granted {
+            RoleGranted {
+                role: &role,
+                account_id: &account_id,
+                by: &env::predecessor_account_id(),
+            }
+            .emit();
+        }
+        granted
+    }
+
+    /// Revokes `role` from `account_id` without checking the caller. Returns whether the account
+    /// had the role.
+    pub fn internal_revoke_role(&mut self, role: &str, account_id: &AccountId) -> bool {
+        let members = match self.roles.get_mut(role) {
+            Some(members) => members,
+            None => return false,
+        };
+        let revoked = members.remove(account_id);
+        // Remove the whole set once the last member is gone to free the storage.
+        if members.is_empty() {
+            self.roles.remove(role);
+        }
+        if revoked {
+            RoleRevoked { role, account_id, by: &env::predecessor_account_id() }.emit();
+        }
+        revoked
+    }
+
+    /// Transfers the ownership to `new_owner_id` without checking the caller.
+    pub fn internal_set_owner(&mut self, new_owner_id: AccountId) {
+        let old_owner_id = std::mem::replace(&mut self.owner_id, new_owner_id);
+        self.proposed_owner_id = None;
+        OwnershipTransferred { old_owner_id: &old_owner_id, new_owner_id: &self.owner_id }.emit();
+    }
+}
+
+impl AccessControlCore for AccessControl {
+    fn acl_owner(&self) -> AccountId {
+        self.owner_id.clone()
+    }
+
+    fn acl_proposed_owner(&self) -> Option<AccountId> {
+        self.proposed_owner_id.clone()
+    }
+
+    fn acl_propose_owner(&mut self, new_owner_id: Option<AccountId>) {
+        assert_one_yocto();
+        self.assert_owner();
+        self.proposed_owner_id = new_owner_id;
+        OwnerProposed {
+            owner_id: &self.owner_id,
+            proposed_owner_id: self.proposed_owner_id.as_deref(),
+        }
+        .emit();
+    }
+
+    fn acl_accept_ownership(&mut self) {
+        assert_one_yocto();
+        let predecessor = env::predecessor_account_id();
+        require!(
+            self.proposed_owner_id.as_ref() == Some(&predecessor),
+            "Method is only available to the proposed owner"
+        );
+        self.internal_set_owner(predecessor);
+    }
+
+    fn acl_grant_role(&mut self, role: Role, account_id: AccountId) -> bool {
+        assert_one_yocto();
+        self.assert_owner();
+        self.internal_grant_role(role, account_id)
+    }
+
+    fn acl_revoke_role(&mut self, role: Role, account_id: AccountId) -> bool {
+        assert_one_yocto();
+        self.assert_owner();
+        self.internal_revoke_role(&role, &account_id)
+    }
+
+    fn acl_renounce_role(&mut self, role: Role) -> bool {
+        assert_one_yocto();
+        self.internal_revoke_role(&role, &env::predecessor_account_id())
+    }
+
+    fn acl_has_role(&self, role: Role, account_id: AccountId) -> bool {
+        self.has_role(&role, &account_id)
+    }
+
+    fn acl_role_members(
+        &self,
+        role: Role,
+        from_index: Option<u32>,
+        limit: Option<u32>,
+    ) -> Vec<AccountId> {
+        let members = match self.roles.get(&role) {
+            Some(members) => members,
+            None => return vec![],
+        };
+        let limit = limit.map(|v| v as usize).unwrap_or(usize::MAX);
+        require!(limit != 0, "Cannot provide limit of 0.");
+        members.iter().skip(from_index.unwrap_or(0) as usize).take(limit).cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+    use near_sdk::{testing_env, NearToken};
+
+    fn setup() -> (VMContextBuilder, AccessControl) {
+        let mut context = VMContextBuilder::new();
+        context.predecessor_account_id(accounts(0)).attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        (context, AccessControl::new(b"acl".to_vec(), accounts(0)))
+    }
+
+    fn call_from(context: &mut VMContextBuilder, account_id: AccountId) {
+        testing_env!(context.predecessor_account_id(account_id).build());
+    }
+
+    #[test]
+    fn two_step_ownership_transfer() {
+        let (mut context, mut acl) = setup();
+        acl.acl_propose_owner(Some(accounts(1)));
+        assert_eq!(acl.acl_owner(), accounts(0));
+        assert_eq!(acl.acl_proposed_owner(), Some(accounts(1)));
+
+        call_from(&mut context, accounts(1));
+        acl.acl_accept_ownership();
+        assert_eq!(acl.acl_owner(), accounts(1));
+        assert_eq!(acl.acl_proposed_owner(), None);
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"access_control","version":"1.0.0","event":"ownership_transferred","data":[{"old_owner_id":"alice","new_owner_id":"bob"}]}"#
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Method is only available to the proposed owner")]
+    fn accept_ownership_without_proposal() {
+        let (mut context, mut acl) = setup();
+        acl.acl_propose_owner(Some(accounts(1)));
+        acl.acl_propose_owner(None);
+        call_from(&mut context, accounts(1));
+        acl.acl_accept_ownership();
+    }
+
+    #[test]
+    #[should_panic(expected = "Method is only available to the owner")]
+    fn propose_owner_by_non_owner() {
+        let (mut context, mut acl) = setup();
+        call_from(&mut context, accounts(1));
+        acl.acl_propose_owner(Some(accounts(1)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Requires attached deposit of exactly 1 yoctoNEAR")]
+    fn grant_role_without_deposit() {
+        let (mut context, mut acl) = setup();
+        testing_env!(context.attached_deposit(NearToken::from_near(0)).build());
+        acl.acl_grant_role("minter".to_string(), accounts(1));
+    }
+
+    #[test]
+    fn grant_and_revoke_roles() {
+        let (mut context, mut acl) = setup();
+        assert!(acl.acl_grant_role("minter".to_string(), accounts(1)));
+        assert!(!acl.acl_grant_role("minter".to_string(), accounts(1)));
+        assert!(acl.acl_grant_role("minter".to_string(), accounts(2)));
+        assert!(acl.acl_has_role("minter".to_string(), accounts(1)));
+        assert!(!acl.acl_has_role("pauser".to_string(), accounts(1)));
+        assert_eq!(
+            acl.acl_role_members("minter".to_string(), None, None),
+            vec![accounts(1), accounts(2)]
+        );
+        assert_eq!(acl.acl_role_members("minter".to_string(), Some(1), Some(1)), vec![accounts(2)]);
+
+        assert!(acl.acl_revoke_role("minter".to_string(), accounts(1)));
+        assert!(!acl.acl_revoke_role("minter".to_string(), accounts(1)));
+
+        call_from(&mut context, accounts(2));
+        acl.assert_role("minter");
+        assert!(acl.acl_renounce_role("minter".to_string()));
+        assert!(acl.acl_role_members("minter".to_string(), None, None).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Method requires the minter role")]
+    fn assert_role_without_role() {
+        let (mut context, acl) = setup();
+        call_from(&mut context, accounts(1));
+        acl.assert_role("minter");
+    }
+
+    #[test]
+    fn assert_owner_or_any_role() {
+        let (mut context, mut acl) = setup();
+        acl.assert_owner_or_any_role(&["pauser"]);
+        acl.internal_grant_role("pauser".to_string(), accounts(1));
+        call_from(&mut context, accounts(1));
+        acl.assert_owner_or_any_role(&["admin", "pauser"]);
+    }
+}
@@ -0,0 +1,136 @@
+//! Events emitted by the [`AccessControl`](super::AccessControl) component.
+//!
+//! These follow the events format (nep-297), using `access_control` as the standard name:
+//! <https://github.com/near/NEPs/blob/master/specs/Standards/EventsFormat.md>
+//!
+//! The events are [`OwnerProposed`], [`OwnershipTransferred`], [`RoleGranted`] and
+//! [`RoleRevoked`]. They can be logged by calling `.emit()` on them.
+
+use crate::event::NearEvent;
+use near_sdk::serde::Serialize;
+use near_sdk::AccountIdRef;
+
+/// Data to log when the owner proposes a new owner. To log this event,
+/// call [`.emit()`](OwnerProposed::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct OwnerProposed<'a> {
+    pub owner_id: &'a AccountIdRef,
+    pub proposed_owner_id: Option<&'a AccountIdRef>,
+}
+
+impl OwnerProposed<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        new_access_control_v1(AccessControlEventKind::OwnerProposed(&[self])).emit()
+    }
+}
+
+/// Data to log when the proposed owner accepts the ownership. To log this event,
+/// call [`.emit()`](OwnershipTransferred::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct OwnershipTransferred<'a> {
+    pub old_owner_id: &'a AccountIdRef,
+    pub new_owner_id: &'a AccountIdRef,
+}
+
+impl OwnershipTransferred<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        new_access_control_v1(AccessControlEventKind::OwnershipTransferred(&[self])).emit()
+    }
+}
+
+/// Data to log when a role is granted to an account. To log this event,
+/// call [`.emit()`](RoleGranted::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RoleGranted<'a> {
+    pub role: &'a str,
+    pub account_id: &'a AccountIdRef,
+    pub by: &'a AccountIdRef,
+}
+
+impl RoleGranted<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        new_access_control_v1(AccessControlEventKind::RoleGranted(&[self])).emit()
+    }
+}
+
+/// Data to log when a role is revoked from, or renounced by, an account. To log this event,
+/// call [`.emit()`](RoleRevoked::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RoleRevoked<'a> {
+    pub role: &'a str,
+    pub account_id: &'a AccountIdRef,
+    pub by: &'a AccountIdRef,
+}
+
+impl RoleRevoked<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        new_access_control_v1(AccessControlEventKind::RoleRevoked(&[self])).emit()
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub(crate) struct AccessControlEvent<'a> {
+    version: &'static str,
+    #[serde(flatten)]
+    event_kind: AccessControlEventKind<'a>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(tag = "event", content = "data")]
+#[serde(rename_all = "snake_case")]
+enum AccessControlEventKind<'a> {
+    OwnerProposed(&'a [OwnerProposed<'a>]),
+    OwnershipTransferred(&'a [OwnershipTransferred<'a>]),
+    RoleGranted(&'a [RoleGranted<'a>]),
+    RoleRevoked(&'a [RoleRevoked<'a>]),
+}
+
+fn new_access_control_v1(event_kind: AccessControlEventKind) -> NearEvent {
+    NearEvent::AccessControl(AccessControlEvent { version: "1.0.0", event_kind })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils;
+
+    #[test]
+    fn owner_proposed() {
+        let owner_id = AccountIdRef::new_or_panic("bob");
+        OwnerProposed { owner_id, proposed_owner_id: Some(AccountIdRef::new_or_panic("alice")) }
+            .emit();
+        assert_eq!(
+            test_utils::get_logs()[0],
+            r#"EVENT_JSON:{"standard":"access_control","version":"1.0.0","event":"owner_proposed","data":[{"owner_id":"bob","proposed_owner_id":"alice"}]}"#
+        );
+    }
+
+    #[test]
+    fn role_granted() {
+        let account_id = AccountIdRef::new_or_panic("alice");
+        let by = AccountIdRef::new_or_panic("bob");
+        RoleGranted { role: "minter", account_id, by }.emit();
+        assert_eq!(
+            test_utils::get_logs()[0],
+            r#"EVENT_JSON:{"standard":"access_control","version":"1.0.0","event":"role_granted","data":[{"role":"minter","account_id":"alice","by":"bob"}]}"#
+        );
+    }
+}
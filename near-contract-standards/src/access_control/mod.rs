@@ -0,0 +1,149 @@
+//! Ownership and role based access control for contracts.
+//!
+//! [`AccessControl`] keeps track of a single owner, which can be handed over with a two-step
+//! propose/accept transfer, and of any number of named roles granted by the owner. Contracts
+//! embed it in their state, gate their own methods with [`AccessControl::assert_owner`] or
+//! [`AccessControl::assert_role`], and expose the management methods by implementing
+//! [`AccessControlCore`].
+//!
+//! # Examples
+//! See [`AccessControlCore`] for example usage.
+
+mod core_impl;
+pub mod events;
+
+pub use core_impl::*;
+
+use near_sdk::{ext_contract, AccountId};
+
+/// Name of a role, such as `"minter"` or `"pauser"`.
+pub type Role = String;
+
+/// Methods to inspect and manage the owner and roles of a contract.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::{near, PanicOnDefault, AccountId};
+/// use near_sdk::json_types::U128;
+/// use near_contract_standards::access_control::{AccessControl, AccessControlCore, Role};
+/// use near_contract_standards::fungible_token::FungibleToken;
+///
+/// #[near(contract_state)]
+/// #[derive(PanicOnDefault)]
+/// pub struct Contract {
+///     token: FungibleToken,
+///     acl: AccessControl,
+/// }
+///
+/// #[near]
+/// impl Contract {
+///     pub fn mint(&mut self, account_id: AccountId, amount: U128) {
+///         self.acl.assert_role("minter");
+///         self.token.internal_deposit(&account_id, amount.into());
+///     }
+/// }
+///
+/// #[near]
+/// impl AccessControlCore for Contract {
+///     fn acl_owner(&self) -> AccountId {
+///         self.acl.acl_owner()
+///     }
+///
+///     fn acl_proposed_owner(&self) -> Option<AccountId> {
+///         self.acl.acl_proposed_owner()
+///     }
+///
+///     #[payable]
+///     fn acl_propose_owner(&mut self, new_owner_id: Option<AccountId>) {
+///         self.acl.acl_propose_owner(new_owner_id)
+///     }
+///
+///     #[payable]
+///     fn acl_accept_ownership(&mut self) {
+///         self.acl.acl_accept_ownership()
+///     }
+///
+///     #[payable]
+///     fn acl_grant_role(&mut self, role: Role, account_id: AccountId) -> bool {
+///         self.acl.acl_grant_role(role, account_id)
+///     }
+///
+///     #[payable]
+///     fn acl_revoke_role(&mut self, role: Role, account_id: AccountId) -> bool {
+///         self.acl.acl_revoke_role(role, account_id)
+///     }
+///
+///     #[payable]
+///     fn acl_renounce_role(&mut self, role: Role) -> bool {
+///         self.acl.acl_renounce_role(role)
+///     }
+///
+///     fn acl_has_role(&self, role: Role, account_id: AccountId) -> bool {
+///         self.acl.acl_has_role(role, account_id)
+///     }
+///
+///     fn acl_role_members(&self, role: Role, from_index: Option<u32>, limit: Option<u32>) -> Vec<AccountId> {
+///         self.acl.acl_role_members(role, from_index, limit)
+///     }
+/// }
+/// ```
+///
+#[ext_contract(ext_access_control)]
+pub trait AccessControlCore {
+    /// Returns the current owner of the contract.
+    fn acl_owner(&self) -> AccountId;
+
+    /// Returns the account proposed as the next owner, if any.
+    fn acl_proposed_owner(&self) -> Option<AccountId>;
+
+    /// Proposes `new_owner_id` as the next owner, who then has to call `acl_accept_ownership`.
+    /// Passing `None` cancels a pending proposal.
+    ///
+    /// Requirements:
+    /// * Caller of the method must be the owner
+    /// * Caller of the method must attach a deposit of 1 yoctoⓃ for security purposes
+    fn acl_propose_owner(&mut self, new_owner_id: Option<AccountId>);
+
+    /// Accepts the ownership proposed by the current owner.
+    ///
+    /// Requirements:
+    /// * Caller of the method must be the proposed owner
+    /// * Caller of the method must attach a deposit of 1 yoctoⓃ for security purposes
+    fn acl_accept_ownership(&mut self);
+
+    /// Grants `role` to `account_id`. Returns whether the account didn't already have the role.
+    ///
+    /// Requirements:
+    /// * Caller of the method must be the owner
+    /// * Caller of the method must attach a deposit of 1 yoctoⓃ for security purposes
+    fn acl_grant_role(&mut self, role: Role, account_id: AccountId) -> bool;
+
+    /// Revokes `role` from `account_id`. Returns whether the account had the role.
+    ///
+    /// Requirements:
+    /// * Caller of the method must be the owner
+    /// * Caller of the method must attach a deposit of 1 yoctoⓃ for security purposes
+    fn acl_revoke_role(&mut self, role: Role, account_id: AccountId) -> bool;
+
+    /// Removes `role` from the caller. Returns whether the caller had the role.
+    ///
+    /// Requirements:
+    /// * Caller of the method must attach a deposit of 1 yoctoⓃ for security purposes
+    fn acl_renounce_role(&mut self, role: Role) -> bool;
+
+    /// Returns whether `account_id` has `role`.
+    fn acl_has_role(&self, role: Role, account_id: AccountId) -> bool;
+
+    /// Get a paginated list of the accounts which have `role`.
+    ///
+    /// Arguments:
+    /// * `from_index`: the starting index of accounts to return
+    /// * `limit`: the maximum number of accounts to return
+    fn acl_role_members(
+        &self,
+        role: Role,
+        from_index: Option<u32>, // default: 0
+        limit: Option<u32>,      // default: unlimited (could fail due to gas limit)
+    ) -> Vec<AccountId>;
+}
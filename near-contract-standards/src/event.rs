@@ -10,6 +10,10 @@ use near_sdk::serde_json;
 pub(crate) enum NearEvent<'a> {
     Nep171(crate::non_fungible_token::events::Nep171Event<'a>),
     Nep141(crate::fungible_token::events::Nep141Event<'a>),
+    Nep245(crate::multi_token::events::Nep245Event<'a>),
+    AccessControl(crate::access_control::events::AccessControlEvent<'a>),
+    Pausable(crate::pausable::events::PausableEvent<'a>),
+    StagedUpgrade(crate::staged_upgrade::events::StagedUpgradeEvent<'a>),
 }
 
 impl<'a> NearEvent<'a> {
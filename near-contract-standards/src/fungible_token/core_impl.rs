@@ -245,3 +245,33 @@ impl FungibleTokenResolver for FungibleToken {
         self.internal_ft_resolve_transfer(&sender_id, receiver_id, amount).0.into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::accounts;
+    use near_sdk::test_utils::fuzz::Fuzzer;
+    use near_sdk::NearToken;
+
+    #[test]
+    fn fuzz_transfers() {
+        Fuzzer::new(|| {
+            let mut token = FungibleToken::new(b"t".to_vec());
+            for i in 0..4 {
+                token.internal_register_account(&accounts(i));
+            }
+            token.internal_deposit(&accounts(0), 1_000);
+            token
+        })
+        .deposits(vec![NearToken::from_yoctonear(1)])
+        .payable_method("ft_transfer", |token, input| {
+            token.ft_transfer(input.account()?, U128(input.int_in_range(0..=1_500)?), None);
+            Ok(())
+        })
+        .invariant("total supply is the sum of the balances", |token| {
+            token.total_supply
+                == (0..4).map(|i| token.internal_unwrap_balance_of(&accounts(i))).sum::<u128>()
+        })
+        .run();
+    }
+}
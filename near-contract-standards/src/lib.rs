@@ -1,17 +1,25 @@
 // We want to enable all clippy lints, but some of them generate false positives.
 #![allow(clippy::missing_const_for_fn, clippy::redundant_pub_crate)]
 
+/// Ownership with two-step transfer and role based access control.
+pub mod access_control;
 /// Fungible tokens as described in [by the spec](https://nomicon.io/Standards/FungibleToken/README.html).
 pub mod fungible_token;
+/// Multi tokens as described in [by the spec](https://github.com/near/NEPs/blob/master/neps/nep-0245.md).
+pub mod multi_token;
 /// Non-fungible tokens as described in [by the spec](https://nomicon.io/Standards/NonFungibleToken/README.html).
 pub mod non_fungible_token;
+/// Pausing of individual contract features.
+pub mod pausable;
+/// Upgrades of the contract code through a staging area, with a versioned state migration.
+pub mod staged_upgrade;
 /// Storage management deals with handling [state storage](https://docs.near.org/docs/concepts/storage-staking) on NEAR. This follows the [storage management standard](https://nomicon.io/Standards/StorageManagement.html).
 pub mod storage_management;
 /// This upgrade standard is a use case where a staging area exists for a WASM
 /// blob, allowing it to be stored for a period of time before deployed.
 #[deprecated(
     since = "4.1.0",
-    note = "This was removed because there is no standard (NEP) for upgradable contracts."
+    note = "This was removed because there is no standard (NEP) for upgradable contracts. Use `staged_upgrade` instead."
 )]
 pub mod upgrade;
 
@@ -0,0 +1,311 @@
+use crate::multi_token::approval::ext_mt_approval_receiver;
+/// Common implementation of the approval management extension for multi tokens.
+use crate::multi_token::approval::MultiTokenApproval;
+use crate::multi_token::token::{Approval, TokenId};
+use crate::multi_token::utils::{bytes_for_approved_account_id, refund_approved_account_ids};
+use crate::multi_token::MultiToken;
+use crate::non_fungible_token::refund_deposit;
+use near_sdk::json_types::U128;
+use near_sdk::{assert_one_yocto, env, require, AccountId, Gas, NearToken, Promise};
+
+const GAS_FOR_MT_APPROVE: Gas = Gas::from_tgas(10);
+
+fn expect_approval<T>(option: Option<T>) -> T {
+    option.unwrap_or_else(|| env::panic_str("next_approval_by_id must be set for approval ext"))
+}
+
+impl MultiToken {
+    /// Removes approvals of `account_id` (or all approvals if `None`) for the caller's tokens and
+    /// refunds the storage they used.
+    fn internal_revoke(&mut self, token_ids: Vec<TokenId>, account_id: Option<&AccountId>) {
+        assert_one_yocto();
+        let approvals_by_id = self
+            .approvals_by_id
+            .as_mut()
+            .unwrap_or_else(|| env::panic_str("MT does not support Approval Management"));
+        let owner_id = env::predecessor_account_id();
+
+        let mut revoked = Vec::new();
+        for token_id in token_ids {
+            let key = (token_id, owner_id.clone());
+            // if tokens have no approvals, do nothing
+            let approvals = match approvals_by_id.get_mut(&key) {
+                Some(approvals) => approvals,
+                None => continue,
+            };
+            match account_id {
+                Some(account_id) => {
+                    // if account_id was already not approved, do nothing
+                    if approvals.remove(account_id).is_some() {
+                        revoked.push(account_id.clone());
+                    }
+                    // if this was the last approval, remove the whole HashMap to save space.
+                    if approvals.is_empty() {
+                        approvals_by_id.remove(&key);
+                    }
+                }
+                None => {
+                    revoked.extend(approvals.keys().cloned());
+                    approvals_by_id.remove(&key);
+                }
+            }
+        }
+
+        if !revoked.is_empty() {
+            refund_approved_account_ids(owner_id, revoked.iter());
+        }
+    }
+}
+
+impl MultiTokenApproval for MultiToken {
+    fn mt_approve(
+        &mut self,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<U128>,
+        account_id: AccountId,
+        msg: Option<String>,
+    ) -> Option<Promise> {
+        require!(
+            env::attached_deposit() >= NearToken::from_yoctonear(1),
+            "Requires attached deposit of at least 1 yoctoNEAR"
+        );
+        require!(token_ids.len() == amounts.len(), "Invalid number of amounts");
+        let owner_id = env::predecessor_account_id();
+        require!(owner_id != account_id, "Approved account must differ from the owner");
+
+        let mut approval_ids = Vec::with_capacity(token_ids.len());
+        let mut storage_used = 0;
+        for (token_id, amount) in token_ids.iter().zip(&amounts) {
+            require!(
+                self.internal_balance_of(&owner_id, token_id) >= amount.0,
+                "Not enough balance to approve"
+            );
+
+            let next_approval_id_by_id = expect_approval(self.next_approval_id_by_id.as_mut());
+            let approval_id: u64 = next_approval_id_by_id.get(token_id).copied().unwrap_or(1u64);
+            // increment next_approval_id for this token
+            next_approval_id_by_id.insert(token_id.clone(), approval_id + 1);
+
+            // update HashMap of approvals for this token and owner
+            let approvals_by_id = self
+                .approvals_by_id
+                .as_mut()
+                .unwrap_or_else(|| env::panic_str("MT does not support Approval Management"));
+            let old_approval = approvals_by_id
+                .entry((token_id.clone(), owner_id.clone()))
+                .or_default()
+                .insert(account_id.clone(), Approval { amount: amount.0, approval_id });
+
+            // If this approval replaced existing for same account, no storage was used.
+            if old_approval.is_none() {
+                storage_used += bytes_for_approved_account_id(&account_id);
+            }
+            approval_ids.push(approval_id);
+        }
+
+        // Require that enough deposit was attached to pay for storage, and refund excess.
+        refund_deposit(storage_used);
+
+        // if given `msg`, schedule call to `mt_on_approve` and return it. Else, return None.
+        msg.map(|msg| {
+            ext_mt_approval_receiver::ext(account_id)
+                .with_static_gas(env::prepaid_gas().saturating_sub(GAS_FOR_MT_APPROVE))
+                .mt_on_approve(token_ids, amounts, owner_id, approval_ids, msg)
+        })
+    }
+
+    fn mt_revoke(&mut self, token_ids: Vec<TokenId>, account_id: AccountId) {
+        self.internal_revoke(token_ids, Some(&account_id));
+    }
+
+    fn mt_revoke_all(&mut self, token_ids: Vec<TokenId>) {
+        self.internal_revoke(token_ids, None);
+    }
+
+    fn mt_is_approved(
+        &self,
+        token_ids: Vec<TokenId>,
+        owner_id: AccountId,
+        approved_account_id: AccountId,
+        amounts: Vec<U128>,
+        approval_ids: Option<Vec<u64>>,
+    ) -> bool {
+        require!(token_ids.len() == amounts.len(), "Invalid number of amounts");
+        if let Some(approval_ids) = &approval_ids {
+            require!(token_ids.len() == approval_ids.len(), "Invalid number of approval ids");
+        }
+
+        let approvals_by_id = if let Some(a) = self.approvals_by_id.as_ref() {
+            a
+        } else {
+            // contract does not support approval management
+            return false;
+        };
+
+        token_ids.into_iter().zip(amounts).enumerate().all(|(i, (token_id, amount))| {
+            // the approved amount can only be transferred while the owner still holds it
+            if self.internal_balance_of(&owner_id, &token_id) < amount.0 {
+                return false;
+            }
+            let approval = match approvals_by_id
+                .get(&(token_id, owner_id.clone()))
+                .and_then(|approvals| approvals.get(&approved_account_id))
+            {
+                Some(approval) => approval,
+                // account not in approvals HashMap
+                None => return false,
+            };
+            approval.amount >= amount.0
+                && approval_ids.as_ref().is_none_or(|ids| ids[i] == approval.approval_id)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::multi_token::core::MultiTokenCore;
+    use crate::multi_token::{MultiToken, MultiTokenApproval};
+    use near_sdk::json_types::U128;
+    use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+    use near_sdk::{testing_env, AccountId, NearToken};
+
+    fn setup() -> (VMContextBuilder, MultiToken) {
+        let mut context = VMContextBuilder::new();
+        context
+            .current_account_id(accounts(0))
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(NearToken::from_near(1));
+        testing_env!(context.build());
+        let mut token = MultiToken::new(
+            b"t".to_vec(),
+            accounts(0),
+            None::<Vec<u8>>,
+            None::<Vec<u8>>,
+            Some(b"a".to_vec()),
+        );
+        token.internal_mint("gold".to_string(), accounts(1), 100, None);
+        (context, token)
+    }
+
+    fn call(context: &mut VMContextBuilder, predecessor: AccountId, deposit: NearToken) {
+        testing_env!(context.predecessor_account_id(predecessor).attached_deposit(deposit).build());
+    }
+
+    #[test]
+    fn approve_and_transfer() {
+        let (mut context, mut token) = setup();
+        call(&mut context, accounts(1), NearToken::from_millinear(10));
+        assert!(token.mt_approve(vec!["gold".into()], vec![U128(50)], accounts(2), None).is_none());
+        assert!(token.mt_is_approved(
+            vec!["gold".into()],
+            accounts(1),
+            accounts(2),
+            vec![U128(50)],
+            None
+        ));
+        assert!(!token.mt_is_approved(
+            vec!["gold".into()],
+            accounts(1),
+            accounts(2),
+            vec![U128(51)],
+            None
+        ));
+        assert!(!token.mt_is_approved(
+            vec!["gold".into()],
+            accounts(1),
+            accounts(2),
+            vec![U128(1)],
+            Some(vec![2])
+        ));
+
+        call(&mut context, accounts(2), NearToken::from_yoctonear(1));
+        token.mt_transfer(accounts(3), "gold".into(), U128(20), Some((accounts(1), 1)), None);
+        assert_eq!(token.mt_balance_of(accounts(3), "gold".into()), U128(20));
+        assert!(token.mt_is_approved(
+            vec!["gold".into()],
+            accounts(1),
+            accounts(2),
+            vec![U128(30)],
+            Some(vec![1])
+        ));
+        assert!(!token.mt_is_approved(
+            vec!["gold".into()],
+            accounts(1),
+            accounts(2),
+            vec![U128(31)],
+            None
+        ));
+    }
+
+    #[test]
+    fn approvals_of_holder_after_transfer() {
+        let (mut context, mut token) = setup();
+        let gold = || vec!["gold".to_string()];
+        call(&mut context, accounts(1), NearToken::from_millinear(10));
+        token.mt_approve(gold(), vec![U128(50)], accounts(3), None);
+
+        // the minter transfers most of its tokens to bob, who approves carol
+        call(&mut context, accounts(1), NearToken::from_yoctonear(1));
+        token.mt_transfer(accounts(2), "gold".into(), U128(80), None, None);
+        call(&mut context, accounts(2), NearToken::from_millinear(10));
+        token.mt_approve(gold(), vec![U128(60)], accounts(3), None);
+
+        assert!(token.mt_is_approved(gold(), accounts(2), accounts(3), vec![U128(60)], None));
+        assert!(token.mt_is_approved(
+            gold(),
+            accounts(2),
+            accounts(3),
+            vec![U128(1)],
+            Some(vec![2])
+        ));
+        // the approval of the minter only covers the balance it still holds
+        assert!(token.mt_is_approved(gold(), accounts(1), accounts(3), vec![U128(20)], None));
+        assert!(!token.mt_is_approved(gold(), accounts(1), accounts(3), vec![U128(50)], None));
+
+        // carol uses the whole approval of bob, which is removed and refunded to bob
+        call(&mut context, accounts(3), NearToken::from_yoctonear(1));
+        token.mt_transfer(accounts(4), "gold".into(), U128(60), Some((accounts(2), 2)), None);
+        assert!(!token.mt_is_approved(gold(), accounts(2), accounts(3), vec![U128(1)], None));
+        let refund = get_created_receipts().pop().unwrap();
+        assert_eq!(refund.receiver_id, accounts(2));
+    }
+
+    #[test]
+    #[should_panic(expected = "Not enough approved amount")]
+    fn transfer_above_approved_amount() {
+        let (mut context, mut token) = setup();
+        call(&mut context, accounts(1), NearToken::from_millinear(10));
+        token.mt_approve(vec!["gold".into()], vec![U128(50)], accounts(2), None);
+
+        call(&mut context, accounts(2), NearToken::from_yoctonear(1));
+        token.mt_transfer(accounts(3), "gold".into(), U128(51), Some((accounts(1), 1)), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Sender not approved")]
+    fn transfer_after_revoke() {
+        let (mut context, mut token) = setup();
+        call(&mut context, accounts(1), NearToken::from_millinear(10));
+        token.mt_approve(vec!["gold".into()], vec![U128(50)], accounts(2), None);
+        call(&mut context, accounts(1), NearToken::from_yoctonear(1));
+        token.mt_revoke(vec!["gold".into()], accounts(2));
+        assert!(!token.mt_is_approved(
+            vec!["gold".into()],
+            accounts(1),
+            accounts(2),
+            vec![U128(1)],
+            None
+        ));
+
+        call(&mut context, accounts(2), NearToken::from_yoctonear(1));
+        token.mt_transfer(accounts(3), "gold".into(), U128(1), Some((accounts(1), 1)), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Not enough balance to approve")]
+    fn approve_more_than_balance() {
+        let (mut context, mut token) = setup();
+        call(&mut context, accounts(1), NearToken::from_millinear(10));
+        token.mt_approve(vec!["gold".into()], vec![U128(101)], accounts(2), None);
+    }
+}
@@ -0,0 +1,136 @@
+mod approval_impl;
+mod approval_receiver;
+
+pub use approval_receiver::*;
+use near_sdk::ext_contract;
+
+use crate::multi_token::token::TokenId;
+use near_sdk::json_types::U128;
+use near_sdk::AccountId;
+use near_sdk::Promise;
+
+/// Trait used when it's desired to have a multi token that has a
+/// traditional escrow or approval system. This allows Alice to allow Bob
+/// to take up to a given amount of the tokens with the unique identifier "19".
+/// Approvals are granted by each holder separately for the tokens they hold.
+///
+/// [approval management standard]: https://github.com/near/NEPs/blob/master/neps/nep-0245.md
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::{PanicOnDefault, AccountId, near, Promise};
+/// use near_sdk::json_types::U128;
+/// use near_contract_standards::multi_token::{TokenId, MultiToken, MultiTokenApproval};
+///
+/// #[near(contract_state)]
+/// #[derive(PanicOnDefault)]
+/// pub struct Contract {
+///    tokens: MultiToken,
+///}
+///
+/// #[near]
+/// impl MultiTokenApproval for Contract {
+///     #[payable]
+///     fn mt_approve(&mut self, token_ids: Vec<TokenId>, amounts: Vec<U128>, account_id: AccountId, msg: Option<String>) -> Option<Promise> {
+///         self.tokens.mt_approve(token_ids, amounts, account_id, msg)
+///     }
+///
+///     #[payable]
+///     fn mt_revoke(&mut self, token_ids: Vec<TokenId>, account_id: AccountId) {
+///         self.tokens.mt_revoke(token_ids, account_id);
+///     }
+///
+///     #[payable]
+///     fn mt_revoke_all(&mut self, token_ids: Vec<TokenId>) {
+///         self.tokens.mt_revoke_all(token_ids);
+///     }
+///
+///     fn mt_is_approved(&self, token_ids: Vec<TokenId>, owner_id: AccountId, approved_account_id: AccountId, amounts: Vec<U128>, approval_ids: Option<Vec<u64>>) -> bool {
+///         self.tokens.mt_is_approved(token_ids, owner_id, approved_account_id, amounts, approval_ids)
+///     }
+/// }
+/// ```
+///
+#[ext_contract(ext_mt_approval)]
+pub trait MultiTokenApproval {
+    /// Add an approved account for the caller's tokens.
+    ///
+    /// Requirements
+    /// * Caller of the method must attach a deposit of at least 1 yoctoⓃ for
+    ///   security purposes
+    /// * Contract MAY require caller to attach larger deposit, to cover cost of
+    ///   storing approver data
+    /// * Contract MUST panic if the caller doesn't hold `amounts[i]` of `token_ids[i]`
+    /// * Contract MUST increment approval ID even if re-approving an account
+    /// * If successfully approved or if had already been approved, and if `msg` is
+    ///   present, contract MUST call `mt_on_approve` on `account_id`. See
+    ///   `mt_on_approve` description below for details.
+    ///
+    /// Arguments:
+    /// * `token_ids`: the tokens for which to add an approval
+    /// * `amounts`: the amount of each token to approve
+    /// * `account_id`: the account to add to `approvals`
+    /// * `msg`: optional string to be passed to `mt_on_approve`
+    ///
+    /// Returns void, if no `msg` given. Otherwise, returns promise call to
+    /// `mt_on_approve`, which can resolve with whatever it wants.
+    fn mt_approve(
+        &mut self,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<U128>,
+        account_id: AccountId,
+        msg: Option<String>,
+    ) -> Option<Promise>;
+
+    /// Revoke an approved account for the caller's tokens.
+    ///
+    /// Requirements
+    /// * Caller of the method must attach a deposit of 1 yoctoⓃ for security
+    ///   purposes
+    /// * If contract requires >1yN deposit on `mt_approve`, contract
+    ///   MUST refund associated storage deposit when owner revokes approval
+    ///
+    /// Arguments:
+    /// * `token_ids`: the tokens for which to revoke an approval
+    /// * `account_id`: the account to remove from `approvals`
+    fn mt_revoke(&mut self, token_ids: Vec<TokenId>, account_id: AccountId);
+
+    /// Revoke all approved accounts for the caller's tokens.
+    ///
+    /// Requirements
+    /// * Caller of the method must attach a deposit of 1 yoctoⓃ for security
+    ///   purposes
+    /// * If contract requires >1yN deposit on `mt_approve`, contract
+    ///   MUST refund all associated storage deposit when owner revokes approvals
+    ///
+    /// Arguments:
+    /// * `token_ids`: the tokens with approvals to revoke
+    fn mt_revoke_all(&mut self, token_ids: Vec<TokenId>);
+
+    /// Check if tokens held by `owner_id` are approved for transfer by a given account,
+    /// optionally checking an approval_id. Approvals are granted by each holder, so the
+    /// holder whose approvals are checked must be given.
+    ///
+    /// Arguments:
+    /// * `token_ids`: the tokens for which to check an approval
+    /// * `owner_id`: the holder of the tokens which granted the approvals
+    /// * `approved_account_id`: the account to check the existence of in `approvals`
+    /// * `amounts`: the minimum amount of each token which must be approved, and held by
+    ///   `owner_id`
+    /// * `approval_ids`: optional approval IDs to check against current approval IDs
+    ///   for given account
+    ///
+    /// Returns:
+    /// if `approval_ids` given, `true` if `approved_account_id` is approved with given
+    /// `approval_ids` for all tokens, otherwise, `true` if `approved_account_id` is in
+    /// list of approved accounts for all tokens
+    fn mt_is_approved(
+        &self,
+        token_ids: Vec<TokenId>,
+        owner_id: AccountId,
+        approved_account_id: AccountId,
+        amounts: Vec<U128>,
+        approval_ids: Option<Vec<u64>>,
+    ) -> bool;
+}
@@ -0,0 +1,1126 @@
+use super::resolver::MultiTokenResolver;
+use crate::multi_token::core::receiver::ext_mt_receiver;
+use crate::multi_token::core::resolver::ext_mt_resolver;
+use crate::multi_token::core::MultiTokenCore;
+use crate::multi_token::events::{MtBurn, MtMint, MtTransfer};
+use crate::multi_token::metadata::TokenMetadata;
+use crate::multi_token::token::{Approval, ClearedApproval, Token, TokenId};
+use crate::multi_token::utils::refund_approved_account_ids;
+use crate::non_fungible_token::refund_deposit_to_account;
+use near_sdk::json_types::U128;
+use near_sdk::store::{IterableMap, IterableSet, LookupMap};
+use near_sdk::{
+    assert_one_yocto, env, near, require, AccountId, Gas, IntoStorageKey, PromiseOrValue,
+    PromiseResult,
+};
+use std::collections::HashMap;
+use std::ops::Deref;
+
+const GAS_FOR_RESOLVE_TRANSFER: Gas = Gas::from_tgas(10);
+const GAS_FOR_MT_TRANSFER_CALL: Gas = Gas::from_tgas(35);
+
+const ERR_TOTAL_SUPPLY_OVERFLOW: &str = "Total supply overflow";
+
+pub type Balance = u128;
+
+/// Implementation of the multi token standard.
+/// Allows to include NEP-245 compatible token to any contract.
+/// There are next traits that any contract may implement:
+///     - MultiTokenCore -- interface with mt_transfer methods. MultiToken provides methods for it.
+///     - MultiTokenApproval -- interface with mt_approve methods. MultiToken provides methods for it.
+///     - MultiTokenEnumeration -- interface for getting lists of tokens. MultiToken provides methods for it.
+///     - MultiTokenMetadataProvider -- return metadata for the contract, up to contract to implement.
+#[near]
+pub struct MultiToken {
+    // owner of contract
+    pub owner_id: AccountId,
+
+    // always required, account which minted the token
+    pub owner_by_id: IterableMap<TokenId, AccountId>,
+
+    // always required
+    pub total_supply: LookupMap<TokenId, Balance>,
+
+    // always required
+    pub balances: LookupMap<(TokenId, AccountId), Balance>,
+
+    // required by metadata extension
+    pub token_metadata_by_id: Option<LookupMap<TokenId, TokenMetadata>>,
+
+    // required by enumeration extension
+    pub tokens_per_owner: Option<LookupMap<AccountId, IterableSet<TokenId>>>,
+
+    // required by approval extension, (token, owner) -> approved account -> approval
+    pub approvals_by_id: Option<LookupMap<(TokenId, AccountId), HashMap<AccountId, Approval>>>,
+    pub next_approval_id_by_id: Option<LookupMap<TokenId, u64>>,
+}
+
+/// Storage prefix of the set of tokens held by `owner_id`, used by the enumeration extension.
+fn tokens_per_owner_prefix(owner_id: &AccountId) -> Vec<u8> {
+    [b"mt".as_slice(), &env::sha256(owner_id.as_bytes())].concat()
+}
+
+impl MultiToken {
+    pub fn new<Q, R, S, T>(
+        prefix: Q,
+        owner_id: AccountId,
+        token_metadata_prefix: Option<R>,
+        enumeration_prefix: Option<S>,
+        approval_prefix: Option<T>,
+    ) -> Self
+    where
+        Q: IntoStorageKey,
+        R: IntoStorageKey,
+        S: IntoStorageKey,
+        T: IntoStorageKey,
+    {
+        let prefix: Vec<u8> = prefix.into_storage_key();
+        let (approvals_by_id, next_approval_id_by_id) = if let Some(prefix) = approval_prefix {
+            let prefix: Vec<u8> = prefix.into_storage_key();
+            (
+                Some(LookupMap::new(prefix.clone())),
+                Some(LookupMap::new([prefix, "n".into()].concat())),
+            )
+        } else {
+            (None, None)
+        };
+
+        Self {
+            owner_id,
+            owner_by_id: IterableMap::new([prefix.clone(), "o".into()].concat()),
+            total_supply: LookupMap::new([prefix.clone(), "s".into()].concat()),
+            balances: LookupMap::new([prefix, "b".into()].concat()),
+            token_metadata_by_id: token_metadata_prefix.map(LookupMap::new),
+            tokens_per_owner: enumeration_prefix.map(LookupMap::new),
+            approvals_by_id,
+            next_approval_id_by_id,
+        }
+    }
+
+    /// Writes all cached changes of the underlying collections to storage. This is done
+    /// automatically when the contract state is written, but is useful to measure storage usage.
+    pub fn flush(&mut self) {
+        self.owner_by_id.flush();
+        self.total_supply.flush();
+        self.balances.flush();
+        if let Some(token_metadata_by_id) = &mut self.token_metadata_by_id {
+            token_metadata_by_id.flush();
+        }
+        if let Some(tokens_per_owner) = &mut self.tokens_per_owner {
+            tokens_per_owner.flush();
+        }
+        if let Some(approvals_by_id) = &mut self.approvals_by_id {
+            approvals_by_id.flush();
+        }
+        if let Some(next_approval_id_by_id) = &mut self.next_approval_id_by_id {
+            next_approval_id_by_id.flush();
+        }
+    }
+
+    /// Returns the balance of `account_id` for `token_id`, which is zero for unknown accounts.
+    pub fn internal_balance_of(&self, account_id: &AccountId, token_id: &TokenId) -> Balance {
+        self.balances.get(&(token_id.clone(), account_id.clone())).copied().unwrap_or(0)
+    }
+
+    fn internal_add_balance(
+        &mut self,
+        token_id: &TokenId,
+        account_id: &AccountId,
+        amount: Balance,
+    ) {
+        let balance = self.internal_balance_of(account_id, token_id);
+        let new_balance =
+            balance.checked_add(amount).unwrap_or_else(|| env::panic_str("Balance overflow"));
+        self.balances.insert((token_id.clone(), account_id.clone()), new_balance);
+
+        // if using Enumeration standard, the account now holds this token
+        if balance == 0 {
+            if let Some(tokens_per_owner) = &mut self.tokens_per_owner {
+                tokens_per_owner
+                    .entry(account_id.clone())
+                    .or_insert_with(|| IterableSet::new(tokens_per_owner_prefix(account_id)))
+                    .insert(token_id.clone());
+            }
+        }
+    }
+
+    fn internal_sub_balance(
+        &mut self,
+        token_id: &TokenId,
+        account_id: &AccountId,
+        amount: Balance,
+    ) {
+        let balance = self.internal_balance_of(account_id, token_id);
+        let new_balance = balance
+            .checked_sub(amount)
+            .unwrap_or_else(|| env::panic_str("The account doesn't have enough balance"));
+        if new_balance > 0 {
+            self.balances.insert((token_id.clone(), account_id.clone()), new_balance);
+            return;
+        }
+        self.balances.remove(&(token_id.clone(), account_id.clone()));
+
+        // if using Enumeration standard, the account doesn't hold this token anymore
+        if let Some(tokens_per_owner) = &mut self.tokens_per_owner {
+            let is_empty = tokens_per_owner.get_mut(account_id).is_some_and(|tokens| {
+                tokens.remove(token_id);
+                tokens.is_empty()
+            });
+            if is_empty {
+                tokens_per_owner.remove(account_id);
+            }
+        }
+    }
+
+    /// Increases the balance of `account_id` and the total supply of `token_id` by `amount`.
+    pub fn internal_deposit(
+        &mut self,
+        token_id: &TokenId,
+        account_id: &AccountId,
+        amount: Balance,
+    ) {
+        let supply = self
+            .total_supply
+            .get_mut(token_id)
+            .unwrap_or_else(|| env::panic_str("Token not found"));
+        *supply =
+            supply.checked_add(amount).unwrap_or_else(|| env::panic_str(ERR_TOTAL_SUPPLY_OVERFLOW));
+        self.internal_add_balance(token_id, account_id, amount);
+    }
+
+    /// Decreases the balance of `account_id` and the total supply of `token_id` by `amount`.
+    pub fn internal_withdraw(
+        &mut self,
+        token_id: &TokenId,
+        account_id: &AccountId,
+        amount: Balance,
+    ) {
+        self.internal_sub_balance(token_id, account_id, amount);
+        let supply = self
+            .total_supply
+            .get_mut(token_id)
+            .unwrap_or_else(|| env::panic_str("Token not found"));
+        *supply =
+            supply.checked_sub(amount).unwrap_or_else(|| env::panic_str(ERR_TOTAL_SUPPLY_OVERFLOW));
+    }
+
+    /// Transfer `amount` of `token_id` from `from` to `to`
+    ///
+    /// Do not perform any safety checks or do any logging
+    pub fn internal_transfer_unguarded(
+        &mut self,
+        #[allow(clippy::ptr_arg)] token_id: &TokenId,
+        from: &AccountId,
+        to: &AccountId,
+        amount: Balance,
+    ) {
+        self.internal_sub_balance(token_id, from, amount);
+        self.internal_add_balance(token_id, to, amount);
+    }
+
+    /// Checks that `sender_id` is allowed to transfer `amount` of `token_id`, consuming the
+    /// approval if the sender is not the owner.
+    ///
+    /// An exhausted approval is removed and its storage refunded to the owner if
+    /// `remove_exhausted` is set. Otherwise it is kept with a zero amount, so that it can be
+    /// restored by `mt_resolve_transfer` without using more storage.
+    /// Returns the owner of the transferred tokens and the consumed approval.
+    fn internal_check_transfer(
+        &mut self,
+        sender_id: &AccountId,
+        #[allow(clippy::ptr_arg)] token_id: &TokenId,
+        amount: Balance,
+        approval: Option<(AccountId, u64)>,
+        remove_exhausted: bool,
+    ) -> (AccountId, Option<ClearedApproval>) {
+        let (owner_id, approval_id) = match approval {
+            Some((owner_id, approval_id)) if &owner_id != sender_id => (owner_id, approval_id),
+            _ => return (sender_id.clone(), None),
+        };
+
+        // Panic if approval extension is NOT being used
+        let approvals_by_id = self
+            .approvals_by_id
+            .as_mut()
+            .unwrap_or_else(|| env::panic_str("Approval extension is disabled"));
+
+        let key = (token_id.clone(), owner_id);
+        let approvals =
+            approvals_by_id.get_mut(&key).unwrap_or_else(|| env::panic_str("Sender not approved"));
+        let actual = approvals
+            .get(sender_id)
+            .copied()
+            .unwrap_or_else(|| env::panic_str("Sender not approved"));
+
+        require!(
+            actual.approval_id == approval_id,
+            format!(
+                "The actual approval_id {} is different from the given approval_id {}",
+                actual.approval_id, approval_id
+            )
+        );
+        require!(actual.amount >= amount, "Not enough approved amount");
+
+        // Consume the approved amount, removing the approval once it is exhausted and refunding
+        // the storage it used to the owner
+        if actual.amount == amount && remove_exhausted {
+            approvals.remove(sender_id);
+            if approvals.is_empty() {
+                approvals_by_id.remove(&key);
+            }
+            refund_approved_account_ids(key.1.clone(), std::iter::once(sender_id));
+        } else {
+            approvals.insert(
+                sender_id.clone(),
+                Approval { amount: actual.amount - amount, approval_id },
+            );
+        }
+
+        (key.1, Some((sender_id.clone(), approval_id, U128(amount))))
+    }
+
+    /// Transfer `amount` of `token_id` from its owner to `receiver_id`, checking that sender is
+    /// allowed to transfer.
+    /// Return previous owner and the consumed approval.
+    pub fn internal_transfer(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        #[allow(clippy::ptr_arg)] token_id: &TokenId,
+        amount: Balance,
+        approval: Option<(AccountId, u64)>,
+        memo: Option<String>,
+    ) -> (AccountId, Option<ClearedApproval>) {
+        let (mut owner_ids, mut approvals) = self.internal_batch_transfer(
+            sender_id,
+            receiver_id,
+            std::slice::from_ref(token_id),
+            &[amount],
+            approval.map(|approval| vec![Some(approval)]),
+            memo,
+        );
+        (owner_ids.remove(0), approvals.remove(0))
+    }
+
+    /// Transfer `amounts[i]` of `token_ids[i]` from their owners to `receiver_id`, checking that
+    /// sender is allowed to transfer each of them.
+    /// Return previous owners and the consumed approvals.
+    pub fn internal_batch_transfer(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        token_ids: &[TokenId],
+        amounts: &[Balance],
+        approvals: Option<Vec<Option<(AccountId, u64)>>>,
+        memo: Option<String>,
+    ) -> (Vec<AccountId>, Vec<Option<ClearedApproval>>) {
+        self.internal_batch_transfer_with(
+            sender_id,
+            receiver_id,
+            token_ids,
+            amounts,
+            approvals,
+            memo,
+            true,
+        )
+    }
+
+    /// Same as `internal_batch_transfer`, but exhausted approvals are only removed if
+    /// `remove_exhausted` is set. See `internal_check_transfer`.
+    #[allow(clippy::too_many_arguments)]
+    fn internal_batch_transfer_with(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        token_ids: &[TokenId],
+        amounts: &[Balance],
+        approvals: Option<Vec<Option<(AccountId, u64)>>>,
+        memo: Option<String>,
+        remove_exhausted: bool,
+    ) -> (Vec<AccountId>, Vec<Option<ClearedApproval>>) {
+        require!(token_ids.len() == amounts.len(), "Invalid number of amounts");
+        if let Some(approvals) = &approvals {
+            require!(token_ids.len() == approvals.len(), "Invalid number of approvals");
+        }
+        require!(!token_ids.is_empty(), "At least one token should be transferred");
+
+        let mut approvals = approvals.map(Vec::into_iter);
+        let mut owner_ids = Vec::with_capacity(token_ids.len());
+        let mut cleared_approvals = Vec::with_capacity(token_ids.len());
+        for (token_id, &amount) in token_ids.iter().zip(amounts) {
+            require!(amount > 0, "The amount should be a positive number");
+            require!(self.total_supply.contains_key(token_id), "Token not found");
+
+            let approval = approvals.as_mut().and_then(|approvals| approvals.next().flatten());
+            let (owner_id, cleared_approval) = self.internal_check_transfer(
+                sender_id,
+                token_id,
+                amount,
+                approval,
+                remove_exhausted,
+            );
+            require!(&owner_id != receiver_id, "Sender and receiver should be different");
+
+            self.internal_transfer_unguarded(token_id, &owner_id, receiver_id, amount);
+
+            owner_ids.push(owner_id);
+            cleared_approvals.push(cleared_approval);
+        }
+
+        Self::emit_transfers(
+            &owner_ids,
+            receiver_id,
+            token_ids,
+            amounts,
+            Some(sender_id),
+            memo.as_deref(),
+        );
+
+        (owner_ids, cleared_approvals)
+    }
+
+    fn emit_transfers(
+        owner_ids: &[AccountId],
+        receiver_id: &AccountId,
+        token_ids: &[TokenId],
+        amounts: &[Balance],
+        sender_id: Option<&AccountId>,
+        memo: Option<&str>,
+    ) {
+        let token_ids: Vec<[&str; 1]> = token_ids.iter().map(|id| [id.as_str()]).collect();
+        let amounts: Vec<[U128; 1]> = amounts.iter().map(|amount| [U128(*amount)]).collect();
+        let events: Vec<MtTransfer> = owner_ids
+            .iter()
+            .zip(token_ids.iter().zip(&amounts))
+            .map(|(owner_id, (token_ids, amounts))| MtTransfer {
+                old_owner_id: owner_id,
+                new_owner_id: receiver_id,
+                token_ids,
+                amounts,
+                authorized_id: sender_id
+                    .filter(|sender_id| *sender_id != owner_id)
+                    .map(|f| f.deref()),
+                memo,
+            })
+            .collect();
+        MtTransfer::emit_many(&events);
+    }
+
+    /// Mint a new token without checking:
+    /// * Whether the caller id is equal to the `owner_id`
+    /// * Assumes there will be a refund to the predecessor after covering the storage costs
+    ///
+    /// Returns the newly minted token and emits the mint event
+    pub fn internal_mint(
+        &mut self,
+        token_id: TokenId,
+        token_owner_id: AccountId,
+        amount: Balance,
+        token_metadata: Option<TokenMetadata>,
+    ) -> Token {
+        let token = self.internal_mint_with_refund(
+            token_id,
+            token_owner_id,
+            amount,
+            token_metadata,
+            Some(env::predecessor_account_id()),
+        );
+        MtMint {
+            owner_id: token.owner_id.as_ref().unwrap(),
+            token_ids: &[&token.token_id],
+            amounts: &[U128(amount)],
+            memo: None,
+        }
+        .emit();
+        token
+    }
+
+    /// Mint a new token without checking:
+    /// * Whether the caller id is equal to the `owner_id`
+    /// * `refund_id` will transfer the left over balance after storage costs are calculated to the provided account.
+    ///   Typically the account will be the owner. If `None`, will not refund. This is useful for delaying refunding
+    ///   until multiple tokens have been minted.
+    ///
+    /// Returns the newly minted token and does not emit the mint event. This allows minting multiple before emitting.
+    pub fn internal_mint_with_refund(
+        &mut self,
+        token_id: TokenId,
+        token_owner_id: AccountId,
+        amount: Balance,
+        token_metadata: Option<TokenMetadata>,
+        refund_id: Option<AccountId>,
+    ) -> Token {
+        // Remember current storage usage if refund_id is Some
+        let initial_storage_usage = refund_id.map(|account_id| (account_id, env::storage_usage()));
+
+        if self.token_metadata_by_id.is_some() && token_metadata.is_none() {
+            env::panic_str("Must provide metadata");
+        }
+        if self.owner_by_id.contains_key(&token_id) {
+            env::panic_str("token_id must be unique");
+        }
+        require!(amount > 0, "The amount should be a positive number");
+
+        let owner_id: AccountId = token_owner_id;
+
+        // Core behavior: every token has a minter and a total supply
+        self.owner_by_id.insert(token_id.clone(), owner_id.clone());
+        self.total_supply.insert(token_id.clone(), 0);
+        self.internal_deposit(&token_id, &owner_id, amount);
+
+        // Metadata extension: Save metadata, keep variable around to return later.
+        // Note that check above already panicked if metadata extension in use but no metadata
+        // provided to call.
+        if let Some(token_metadata_by_id) = &mut self.token_metadata_by_id {
+            token_metadata_by_id.insert(token_id.clone(), token_metadata.clone().unwrap());
+        }
+
+        // Storage usage only changes once the collections are written
+        if let Some((id, storage_usage)) = initial_storage_usage {
+            self.flush();
+            refund_deposit_to_account(env::storage_usage() - storage_usage, id)
+        }
+
+        Token { token_id, owner_id: Some(owner_id), metadata: token_metadata }
+    }
+
+    /// Burn `amount` of `token_id` held by `owner_id` without checking:
+    /// * Whether the caller id is equal to `owner_id`
+    ///
+    /// Emits the burn event.
+    pub fn internal_burn(
+        &mut self,
+        #[allow(clippy::ptr_arg)] token_id: &TokenId,
+        owner_id: &AccountId,
+        amount: Balance,
+        memo: Option<String>,
+    ) {
+        require!(amount > 0, "The amount should be a positive number");
+        self.internal_withdraw(token_id, owner_id, amount);
+        MtBurn {
+            owner_id,
+            token_ids: &[token_id],
+            amounts: &[U128(amount)],
+            authorized_id: None,
+            memo: memo.as_deref(),
+        }
+        .emit();
+    }
+
+    /// Restores approvals consumed by a transfer call which was (partially) reverted, up to
+    /// `refunded_amount` tokens. The approvals which were exhausted by the transfer and are not
+    /// restored are removed, and the storage they used is refunded to the owner.
+    fn internal_restore_approvals(
+        &mut self,
+        #[allow(clippy::ptr_arg)] token_id: &TokenId,
+        owner_id: &AccountId,
+        cleared_approvals: Vec<ClearedApproval>,
+        mut refunded_amount: Balance,
+    ) {
+        let approvals_by_id = match &mut self.approvals_by_id {
+            Some(approvals_by_id) => approvals_by_id,
+            None => return,
+        };
+        let key = (token_id.clone(), owner_id.clone());
+        // The owner may have revoked all approvals in the meantime
+        let approvals = match approvals_by_id.get_mut(&key) {
+            Some(approvals) => approvals,
+            None => return,
+        };
+        let mut removed = Vec::new();
+        for (account_id, approval_id, amount) in cleared_approvals {
+            let restored = std::cmp::min(amount.0, refunded_amount);
+            refunded_amount -= restored;
+            // The owner may have revoked the approval or approved the account again in the
+            // meantime, in which case the storage of the consumed approval was already released
+            let approval = match approvals.get_mut(&account_id) {
+                Some(approval) if approval.approval_id == approval_id => approval,
+                _ => continue,
+            };
+            approval.amount += restored;
+            if approval.amount == 0 {
+                approvals.remove(&account_id);
+                removed.push(account_id);
+            }
+        }
+        if approvals.is_empty() {
+            approvals_by_id.remove(&key);
+        }
+        if !removed.is_empty() {
+            refund_approved_account_ids(owner_id.clone(), removed.iter());
+        }
+    }
+}
+
+impl MultiTokenCore for MultiToken {
+    fn mt_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        amount: U128,
+        approval: Option<(AccountId, u64)>,
+        memo: Option<String>,
+    ) {
+        assert_one_yocto();
+        let sender_id = env::predecessor_account_id();
+        self.internal_transfer(&sender_id, &receiver_id, &token_id, amount.0, approval, memo);
+    }
+
+    fn mt_batch_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<U128>,
+        approvals: Option<Vec<Option<(AccountId, u64)>>>,
+        memo: Option<String>,
+    ) {
+        assert_one_yocto();
+        let sender_id = env::predecessor_account_id();
+        let amounts: Vec<Balance> = amounts.into_iter().map(|amount| amount.0).collect();
+        self.internal_batch_transfer(
+            &sender_id,
+            &receiver_id,
+            &token_ids,
+            &amounts,
+            approvals,
+            memo,
+        );
+    }
+
+    fn mt_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        amount: U128,
+        approval: Option<(AccountId, u64)>,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<Vec<U128>> {
+        self.mt_batch_transfer_call(
+            receiver_id,
+            vec![token_id],
+            vec![amount],
+            approval.map(|approval| vec![Some(approval)]),
+            memo,
+            msg,
+        )
+    }
+
+    fn mt_batch_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<U128>,
+        approvals: Option<Vec<Option<(AccountId, u64)>>>,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<Vec<U128>> {
+        assert_one_yocto();
+        require!(env::prepaid_gas() > GAS_FOR_MT_TRANSFER_CALL, "More gas is required");
+        let sender_id = env::predecessor_account_id();
+        let balances: Vec<Balance> = amounts.iter().map(|amount| amount.0).collect();
+        // Exhausted approvals are removed by `mt_resolve_transfer`, once it is known whether
+        // they are restored
+        let (old_owners, cleared_approvals) = self.internal_batch_transfer_with(
+            &sender_id,
+            &receiver_id,
+            &token_ids,
+            &balances,
+            approvals,
+            memo,
+            false,
+        );
+        // Only pass approvals to the resolver if any were consumed
+        let cleared_approvals = cleared_approvals.iter().any(Option::is_some).then(|| {
+            cleared_approvals.into_iter().map(|approval| approval.map(|a| vec![a])).collect()
+        });
+        // Initiating receiver's call and the callback
+        ext_mt_receiver::ext(receiver_id.clone())
+            .with_static_gas(env::prepaid_gas().saturating_sub(GAS_FOR_MT_TRANSFER_CALL))
+            .mt_on_transfer(sender_id, old_owners.clone(), token_ids.clone(), amounts.clone(), msg)
+            .then(
+                ext_mt_resolver::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_RESOLVE_TRANSFER)
+                    .mt_resolve_transfer(
+                        old_owners,
+                        receiver_id,
+                        token_ids,
+                        amounts,
+                        cleared_approvals,
+                    ),
+            )
+            .into()
+    }
+
+    fn mt_token(&self, token_ids: Vec<TokenId>) -> Vec<Option<Token>> {
+        token_ids
+            .into_iter()
+            .map(|token_id| {
+                let owner_id = self.owner_by_id.get(&token_id)?.clone();
+                let metadata =
+                    self.token_metadata_by_id.as_ref().and_then(|by_id| by_id.get(&token_id));
+                Some(Token { metadata: metadata.cloned(), token_id, owner_id: Some(owner_id) })
+            })
+            .collect()
+    }
+
+    fn mt_balance_of(&self, account_id: AccountId, token_id: TokenId) -> U128 {
+        self.internal_balance_of(&account_id, &token_id).into()
+    }
+
+    fn mt_batch_balance_of(&self, account_id: AccountId, token_ids: Vec<TokenId>) -> Vec<U128> {
+        token_ids
+            .iter()
+            .map(|token_id| self.internal_balance_of(&account_id, token_id).into())
+            .collect()
+    }
+
+    fn mt_supply(&self, token_id: TokenId) -> Option<U128> {
+        self.total_supply.get(&token_id).map(|supply| U128(*supply))
+    }
+
+    fn mt_batch_supply(&self, token_ids: Vec<TokenId>) -> Vec<Option<U128>> {
+        token_ids.into_iter().map(|token_id| self.mt_supply(token_id)).collect()
+    }
+}
+
+impl MultiTokenResolver for MultiToken {
+    /// Returns the amount of each token that was used by `receiver_id`.
+    fn mt_resolve_transfer(
+        &mut self,
+        previous_owner_ids: Vec<AccountId>,
+        receiver_id: AccountId,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<U128>,
+        approvals: Option<Vec<Option<Vec<ClearedApproval>>>>,
+    ) -> Vec<U128> {
+        require!(
+            previous_owner_ids.len() == token_ids.len() && amounts.len() == token_ids.len(),
+            "Invalid number of arguments"
+        );
+
+        // Get the unused amounts from the `mt_on_transfer` call result.
+        let unused_amounts: Vec<Balance> = match env::promise_result(0) {
+            PromiseResult::Successful(value) => {
+                match near_sdk::serde_json::from_slice::<Vec<U128>>(&value) {
+                    Ok(unused) if unused.len() == amounts.len() => unused
+                        .into_iter()
+                        .zip(&amounts)
+                        .map(|(unused, amount)| std::cmp::min(amount.0, unused.0))
+                        .collect(),
+                    _ => amounts.iter().map(|amount| amount.0).collect(),
+                }
+            }
+            PromiseResult::Failed => amounts.iter().map(|amount| amount.0).collect(),
+        };
+
+        let mut approvals = approvals.map(Vec::into_iter);
+        let mut refunds = Vec::new();
+        let mut used_amounts = Vec::with_capacity(amounts.len());
+        for (i, (amount, unused_amount)) in amounts.iter().zip(unused_amounts).enumerate() {
+            let cleared_approvals =
+                approvals.as_mut().and_then(|approvals| approvals.next().flatten());
+            let token_id = &token_ids[i];
+            let previous_owner_id = &previous_owner_ids[i];
+
+            // The receiver might have already transferred or burned some of the tokens.
+            let receiver_balance = self.internal_balance_of(&receiver_id, token_id);
+            let refund_amount = std::cmp::min(receiver_balance, unused_amount);
+            if refund_amount > 0 {
+                self.internal_transfer_unguarded(
+                    token_id,
+                    &receiver_id,
+                    previous_owner_id,
+                    refund_amount,
+                );
+                refunds.push(i);
+            }
+            if let Some(cleared_approvals) = cleared_approvals {
+                self.internal_restore_approvals(
+                    token_id,
+                    previous_owner_id,
+                    cleared_approvals,
+                    refund_amount,
+                );
+            }
+            used_amounts.push(U128(amount.0 - refund_amount));
+        }
+
+        if !refunds.is_empty() {
+            // Each refund moves tokens back from the receiver to the previous owner
+            let refund_token_ids: Vec<[&str; 1]> =
+                refunds.iter().map(|&i| [token_ids[i].as_str()]).collect();
+            let refund_amounts: Vec<[U128; 1]> =
+                refunds.iter().map(|&i| [U128(amounts[i].0 - used_amounts[i].0)]).collect();
+            let events: Vec<MtTransfer> = refunds
+                .iter()
+                .zip(refund_token_ids.iter().zip(&refund_amounts))
+                .map(|(&i, (token_ids, amounts))| MtTransfer {
+                    old_owner_id: &receiver_id,
+                    new_owner_id: &previous_owner_ids[i],
+                    token_ids,
+                    amounts,
+                    authorized_id: None,
+                    memo: Some("refund"),
+                })
+                .collect();
+            MtTransfer::emit_many(&events);
+        }
+
+        used_amounts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::multi_token::enumeration::MultiTokenEnumeration;
+    use crate::multi_token::utils::bytes_for_approved_account_id;
+    use crate::multi_token::MultiTokenApproval;
+    use near_sdk::mock::MockAction;
+    use near_sdk::test_utils::{accounts, get_created_receipts, get_logs, VMContextBuilder};
+    use near_sdk::{test_vm_config, testing_env, NearToken, RuntimeFeesConfig};
+
+    fn setup() -> (VMContextBuilder, MultiToken) {
+        let mut context = VMContextBuilder::new();
+        context
+            .current_account_id(accounts(0))
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(NearToken::from_near(1));
+        testing_env!(context.build());
+        let mut token = MultiToken::new(
+            b"t".to_vec(),
+            accounts(0),
+            None::<Vec<u8>>,
+            Some(b"e".to_vec()),
+            Some(b"a".to_vec()),
+        );
+        token.internal_mint("sword".to_string(), accounts(1), 100, None);
+        token.internal_mint("shield".to_string(), accounts(1), 1, None);
+        (context, token)
+    }
+
+    fn transfer_context(context: &mut VMContextBuilder, predecessor: AccountId) {
+        testing_env!(context
+            .predecessor_account_id(predecessor)
+            .attached_deposit(NearToken::from_yoctonear(1))
+            .build());
+    }
+
+    #[test]
+    fn mint() {
+        let (_, token) = setup();
+        assert_eq!(token.mt_balance_of(accounts(1), "sword".to_string()), U128(100));
+        assert_eq!(
+            token.mt_batch_supply(vec!["sword".into(), "shield".into(), "bow".into()]),
+            vec![Some(U128(100)), Some(U128(1)), None]
+        );
+        assert_eq!(
+            get_logs()[0],
+            r#"EVENT_JSON:{"standard":"nep245","version":"1.0.0","event":"mt_mint","data":[{"owner_id":"bob","token_ids":["sword"],"amounts":["100"]}]}"#
+        );
+        let tokens = token.mt_token(vec!["sword".into(), "bow".into()]);
+        assert_eq!(tokens[0].as_ref().unwrap().owner_id, Some(accounts(1)));
+        assert!(tokens[1].is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "token_id must be unique")]
+    fn mint_duplicate() {
+        let (_, mut token) = setup();
+        token.internal_mint("sword".to_string(), accounts(2), 1, None);
+    }
+
+    #[test]
+    fn transfer() {
+        let (mut context, mut token) = setup();
+        transfer_context(&mut context, accounts(1));
+        token.mt_transfer(accounts(2), "sword".to_string(), U128(30), None, Some("gift".into()));
+
+        assert_eq!(
+            token.mt_batch_balance_of(accounts(1), vec!["sword".into(), "shield".into()]),
+            vec![U128(70), U128(1)]
+        );
+        assert_eq!(token.mt_balance_of(accounts(2), "sword".to_string()), U128(30));
+        assert_eq!(token.mt_supply("sword".to_string()), Some(U128(100)));
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"nep245","version":"1.0.0","event":"mt_transfer","data":[{"old_owner_id":"bob","new_owner_id":"charlie","token_ids":["sword"],"amounts":["30"],"memo":"gift"}]}"#
+            ]
+        );
+    }
+
+    #[test]
+    fn batch_transfer() {
+        let (mut context, mut token) = setup();
+        transfer_context(&mut context, accounts(1));
+        token.mt_batch_transfer(
+            accounts(2),
+            vec!["sword".into(), "shield".into()],
+            vec![U128(100), U128(1)],
+            None,
+            None,
+        );
+
+        assert_eq!(token.mt_balance_of(accounts(1), "sword".to_string()), U128(0));
+        assert_eq!(
+            token.mt_batch_balance_of(accounts(2), vec!["sword".into(), "shield".into()]),
+            vec![U128(100), U128(1)]
+        );
+        assert!(token.mt_tokens_for_owner(accounts(1), None, None).is_empty());
+        assert_eq!(token.mt_tokens_for_owner(accounts(2), None, None).len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "The account doesn't have enough balance")]
+    fn transfer_more_than_balance() {
+        let (mut context, mut token) = setup();
+        transfer_context(&mut context, accounts(1));
+        token.mt_transfer(accounts(2), "sword".to_string(), U128(101), None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid number of amounts")]
+    fn batch_transfer_length_mismatch() {
+        let (mut context, mut token) = setup();
+        transfer_context(&mut context, accounts(1));
+        token.mt_batch_transfer(accounts(2), vec!["sword".into()], vec![], None, None);
+    }
+
+    #[test]
+    fn burn() {
+        let (_, mut token) = setup();
+        token.internal_burn(&"sword".to_string(), &accounts(1), 40, None);
+        assert_eq!(token.mt_balance_of(accounts(1), "sword".to_string()), U128(60));
+        assert_eq!(token.mt_supply("sword".to_string()), Some(U128(60)));
+    }
+
+    #[test]
+    fn resolve_transfer_refunds_unused_amounts() {
+        let (mut context, mut token) = setup();
+        transfer_context(&mut context, accounts(1));
+        let _ = token.mt_batch_transfer_call(
+            accounts(2),
+            vec!["sword".into(), "shield".into()],
+            vec![U128(50), U128(1)],
+            None,
+            None,
+            "".to_string(),
+        );
+
+        // The receiver only used 20 swords, and returned the shield.
+        testing_env!(
+            context.predecessor_account_id(accounts(0)).build(),
+            test_vm_config(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Successful(
+                near_sdk::serde_json::to_vec(&[U128(30), U128(1)]).unwrap()
+            )],
+        );
+        let used = token.mt_resolve_transfer(
+            vec![accounts(1), accounts(1)],
+            accounts(2),
+            vec!["sword".into(), "shield".into()],
+            vec![U128(50), U128(1)],
+            None,
+        );
+
+        assert_eq!(used, vec![U128(20), U128(0)]);
+        assert_eq!(
+            token.mt_batch_balance_of(accounts(1), vec!["sword".into(), "shield".into()]),
+            vec![U128(80), U128(1)]
+        );
+        assert_eq!(token.mt_balance_of(accounts(2), "sword".to_string()), U128(20));
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"nep245","version":"1.0.0","event":"mt_transfer","data":[{"old_owner_id":"charlie","new_owner_id":"bob","token_ids":["sword"],"amounts":["30"],"memo":"refund"},{"old_owner_id":"charlie","new_owner_id":"bob","token_ids":["shield"],"amounts":["1"],"memo":"refund"}]}"#
+            ]
+        );
+    }
+
+    #[test]
+    fn resolve_transfer_reverts_failed_call_and_restores_approval() {
+        let (mut context, mut token) = setup();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(NearToken::from_millinear(10))
+            .build());
+        token.mt_approve(vec!["sword".into()], vec![U128(10)], accounts(3), None);
+
+        transfer_context(&mut context, accounts(3));
+        let _ = token.mt_transfer_call(
+            accounts(2),
+            "sword".into(),
+            U128(10),
+            Some((accounts(1), 1)),
+            None,
+            "".to_string(),
+        );
+        assert!(!token.mt_is_approved(
+            vec!["sword".into()],
+            accounts(1),
+            accounts(3),
+            vec![U128(1)],
+            None
+        ));
+
+        testing_env!(
+            context.predecessor_account_id(accounts(0)).build(),
+            test_vm_config(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Failed],
+        );
+        let used = token.mt_resolve_transfer(
+            vec![accounts(1)],
+            accounts(2),
+            vec!["sword".into()],
+            vec![U128(10)],
+            Some(vec![Some(vec![(accounts(3), 1, U128(10))])]),
+        );
+
+        assert_eq!(used, vec![U128(0)]);
+        assert_eq!(token.mt_balance_of(accounts(1), "sword".to_string()), U128(100));
+        assert!(token.mt_is_approved(
+            vec!["sword".into()],
+            accounts(1),
+            accounts(3),
+            vec![U128(10)],
+            Some(vec![1])
+        ));
+    }
+
+    /// Transfers created by the last call, by receiver.
+    fn transfers() -> Vec<(AccountId, NearToken)> {
+        get_created_receipts()
+            .into_iter()
+            .flat_map(|receipt| {
+                let receiver_id = receipt.receiver_id;
+                receipt.actions.into_iter().filter_map(move |action| match action {
+                    MockAction::Transfer { deposit, .. } => Some((receiver_id.clone(), deposit)),
+                    _ => None,
+                })
+            })
+            .collect()
+    }
+
+    /// Approves `accounts(3)` for 10 swords of `accounts(1)`, who then transfers them with a
+    /// transfer call. Returns the storage usage before the transfer call.
+    fn approved_transfer_call(context: &mut VMContextBuilder, token: &mut MultiToken) -> u64 {
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(NearToken::from_millinear(10))
+            .build());
+        token.mt_approve(vec!["sword".into()], vec![U128(10)], accounts(3), None);
+        token.flush();
+        let storage_usage = env::storage_usage();
+
+        testing_env!(context
+            .storage_usage(storage_usage)
+            .predecessor_account_id(accounts(3))
+            .attached_deposit(NearToken::from_yoctonear(1))
+            .build());
+        let _ = token.mt_transfer_call(
+            accounts(2),
+            "sword".into(),
+            U128(10),
+            Some((accounts(1), 1)),
+            None,
+            "".to_string(),
+        );
+        // The storage of the exhausted approval is only refunded once the transfer is resolved
+        assert!(transfers().is_empty());
+        token.flush();
+        storage_usage
+    }
+
+    fn resolve_approved_transfer_call(
+        context: &mut VMContextBuilder,
+        token: &mut MultiToken,
+        unused: u128,
+    ) -> Vec<U128> {
+        token.flush();
+        testing_env!(
+            context
+                .storage_usage(env::storage_usage())
+                .predecessor_account_id(accounts(0))
+                .attached_deposit(NearToken::from_yoctonear(0))
+                .build(),
+            test_vm_config(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Successful(near_sdk::serde_json::to_vec(&[U128(unused)]).unwrap())],
+        );
+        let used = token.mt_resolve_transfer(
+            vec![accounts(1)],
+            accounts(2),
+            vec!["sword".into()],
+            vec![U128(10)],
+            Some(vec![Some(vec![(accounts(3), 1, U128(10))])]),
+        );
+        token.flush();
+        used
+    }
+
+    #[test]
+    fn resolve_transfer_full_refund_keeps_approval_storage() {
+        let (mut context, mut token) = setup();
+        let storage_usage = approved_transfer_call(&mut context, &mut token);
+
+        let used = resolve_approved_transfer_call(&mut context, &mut token, 10);
+
+        assert_eq!(used, vec![U128(0)]);
+        // The approval is restored in place, so no storage is used or refunded
+        assert_eq!(env::storage_usage(), storage_usage);
+        assert!(transfers().is_empty());
+        assert!(token.mt_is_approved(
+            vec!["sword".into()],
+            accounts(1),
+            accounts(3),
+            vec![U128(10)],
+            Some(vec![1])
+        ));
+    }
+
+    #[test]
+    fn resolve_transfer_refunds_storage_of_exhausted_approval() {
+        let (mut context, mut token) = setup();
+        approved_transfer_call(&mut context, &mut token);
+
+        let used = resolve_approved_transfer_call(&mut context, &mut token, 0);
+
+        assert_eq!(used, vec![U128(10)]);
+        let storage = bytes_for_approved_account_id(&accounts(3)) as u128;
+        assert_eq!(
+            transfers(),
+            vec![(accounts(1), env::storage_byte_cost().saturating_mul(storage))]
+        );
+        let key = ("sword".to_string(), accounts(1));
+        assert!(token.approvals_by_id.as_ref().unwrap().get(&key).is_none());
+    }
+
+    #[test]
+    fn resolve_transfer_full_refund_after_revoke() {
+        let (mut context, mut token) = setup();
+        approved_transfer_call(&mut context, &mut token);
+        // Revoking the exhausted approval refunds its storage
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(NearToken::from_yoctonear(1))
+            .build());
+        token.mt_revoke(vec!["sword".into()], accounts(3));
+        assert_eq!(transfers().len(), 1);
+
+        let used = resolve_approved_transfer_call(&mut context, &mut token, 10);
+
+        // The revoked approval is neither restored nor refunded again
+        assert_eq!(used, vec![U128(0)]);
+        assert!(transfers().is_empty());
+        let key = ("sword".to_string(), accounts(1));
+        assert!(token.approvals_by_id.as_ref().unwrap().get(&key).is_none());
+        assert_eq!(token.mt_balance_of(accounts(1), "sword".to_string()), U128(100));
+    }
+}
@@ -0,0 +1,190 @@
+mod core_impl;
+
+mod receiver;
+mod resolver;
+
+pub use self::core_impl::*;
+
+pub use self::receiver::{ext_mt_receiver, MultiTokenReceiver};
+pub use self::resolver::{ext_mt_resolver, MultiTokenResolver};
+
+use crate::multi_token::token::{Token, TokenId};
+use near_sdk::json_types::U128;
+use near_sdk::{ext_contract, AccountId, PromiseOrValue};
+
+/// Used for all multi tokens. The specification for the
+/// [core multi token standard] lays out the reasoning for each method.
+/// It's important to check out [MultiTokenReceiver](crate::multi_token::core::MultiTokenReceiver)
+/// and [MultiTokenResolver](crate::multi_token::core::MultiTokenResolver) to
+/// understand how the cross-contract call work.
+///
+/// [core multi token standard]: <https://github.com/near/NEPs/blob/master/neps/nep-0245.md>
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::{PanicOnDefault, AccountId, PromiseOrValue, near};
+/// use near_sdk::json_types::U128;
+/// use near_contract_standards::multi_token::{core::MultiTokenCore, MultiToken, TokenId, Token};
+///
+/// #[near(contract_state)]
+/// #[derive(PanicOnDefault)]
+/// pub struct Contract {
+///    tokens: MultiToken,
+///}
+/// #[near]
+/// impl MultiTokenCore for Contract {
+///     #[payable]
+///     fn mt_transfer(&mut self, receiver_id: AccountId, token_id: TokenId, amount: U128, approval: Option<(AccountId, u64)>, memo: Option<String>) {
+///         self.tokens.mt_transfer(receiver_id, token_id, amount, approval, memo)
+///     }
+///
+///     #[payable]
+///     fn mt_batch_transfer(&mut self, receiver_id: AccountId, token_ids: Vec<TokenId>, amounts: Vec<U128>, approvals: Option<Vec<Option<(AccountId, u64)>>>, memo: Option<String>) {
+///         self.tokens.mt_batch_transfer(receiver_id, token_ids, amounts, approvals, memo)
+///     }
+///
+///     #[payable]
+///     fn mt_transfer_call(&mut self, receiver_id: AccountId, token_id: TokenId, amount: U128, approval: Option<(AccountId, u64)>, memo: Option<String>, msg: String) -> PromiseOrValue<Vec<U128>> {
+///         self.tokens.mt_transfer_call(receiver_id, token_id, amount, approval, memo, msg)
+///     }
+///
+///     #[payable]
+///     fn mt_batch_transfer_call(&mut self, receiver_id: AccountId, token_ids: Vec<TokenId>, amounts: Vec<U128>, approvals: Option<Vec<Option<(AccountId, u64)>>>, memo: Option<String>, msg: String) -> PromiseOrValue<Vec<U128>> {
+///         self.tokens.mt_batch_transfer_call(receiver_id, token_ids, amounts, approvals, memo, msg)
+///     }
+///
+///     fn mt_token(&self, token_ids: Vec<TokenId>) -> Vec<Option<Token>> {
+///         self.tokens.mt_token(token_ids)
+///     }
+///
+///     fn mt_balance_of(&self, account_id: AccountId, token_id: TokenId) -> U128 {
+///         self.tokens.mt_balance_of(account_id, token_id)
+///     }
+///
+///     fn mt_batch_balance_of(&self, account_id: AccountId, token_ids: Vec<TokenId>) -> Vec<U128> {
+///         self.tokens.mt_batch_balance_of(account_id, token_ids)
+///     }
+///
+///     fn mt_supply(&self, token_id: TokenId) -> Option<U128> {
+///         self.tokens.mt_supply(token_id)
+///     }
+///
+///     fn mt_batch_supply(&self, token_ids: Vec<TokenId>) -> Vec<Option<U128>> {
+///         self.tokens.mt_batch_supply(token_ids)
+///     }
+/// }
+/// ```
+///
+#[ext_contract(ext_mt_core)]
+pub trait MultiTokenCore {
+    /// Simple transfer. Transfer `amount` of a given `token_id` from the current owner to
+    /// `receiver_id`.
+    ///
+    /// Requirements
+    /// * Caller of the method must attach a deposit of 1 yoctoⓃ for security purposes
+    /// * Contract MUST panic if called by someone other than the token owner or,
+    ///   if using Approval Management, one of the approved accounts
+    /// * If using Approval Management, contract MUST reduce the approved amount by
+    ///   `amount` on successful transfer
+    ///
+    /// Arguments:
+    /// * `receiver_id`: the valid NEAR account receiving the token
+    /// * `token_id`: the token to transfer
+    /// * `amount`: the number of tokens to transfer, wrapped in quotes and treated
+    ///    like a string, although the number will be stored as an unsigned integer
+    ///    with 128 bits.
+    /// * `approval` (optional): tuple of the owner whose tokens are being transferred
+    ///    and the expected approval ID, for use with Approval Management.
+    /// * `memo` (optional): for use cases that may benefit from indexing or
+    ///    providing information for a transfer
+    fn mt_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        amount: U128,
+        approval: Option<(AccountId, u64)>,
+        memo: Option<String>,
+    );
+
+    /// Batched version of `mt_transfer`. Transfers `amounts[i]` of `token_ids[i]` to
+    /// `receiver_id`, for every `i`.
+    ///
+    /// Requirements
+    /// * Same as `mt_transfer` for every token
+    /// * Contract MUST panic if the lengths of `token_ids`, `amounts` and `approvals`
+    ///   (when given) differ
+    fn mt_batch_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<U128>,
+        approvals: Option<Vec<Option<(AccountId, u64)>>>,
+        memo: Option<String>,
+    );
+
+    /// Transfer tokens and call a method on a receiver contract. A successful
+    /// workflow will end in a success execution outcome to the callback on the MT
+    /// contract at the method `mt_resolve_transfer`.
+    ///
+    /// Requirements:
+    /// * Caller of the method must attach a deposit of 1 yoctoⓃ for security
+    ///   purposes
+    /// * Contract MUST panic if called by someone other than token owner or,
+    ///   if using Approval Management, one of the approved accounts
+    /// * The receiving contract must implement `mt_on_transfer` according to the
+    ///   standard. If it does not, MT contract's `mt_resolve_transfer` MUST deal
+    ///   with the resulting failed cross-contract call and roll back the transfer.
+    /// * Contract MUST implement the behavior described in `mt_resolve_transfer`
+    ///
+    /// Arguments:
+    /// * `receiver_id`: the valid NEAR account receiving the token.
+    /// * `token_id`: the token to send.
+    /// * `amount`: the number of tokens to transfer.
+    /// * `approval` (optional): tuple of the owner and the expected approval ID.
+    /// * `memo` (optional): for use cases that may benefit from indexing or
+    ///    providing information for a transfer.
+    /// * `msg`: specifies information needed by the receiving contract in
+    ///    order to properly handle the transfer. Can indicate both a function to
+    ///    call and the parameters to pass to that function.
+    ///
+    /// Returns the amounts of tokens used by the receiver, as resolved by
+    /// `mt_resolve_transfer`.
+    fn mt_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        amount: U128,
+        approval: Option<(AccountId, u64)>,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<Vec<U128>>;
+
+    /// Batched version of `mt_transfer_call`. A single `mt_on_transfer` call is made on
+    /// the receiver with all of the transferred tokens.
+    fn mt_batch_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<U128>,
+        approvals: Option<Vec<Option<(AccountId, u64)>>>,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<Vec<U128>>;
+
+    /// Returns the tokens with the given `token_ids`, with `null` in place of
+    /// tokens that don't exist.
+    fn mt_token(&self, token_ids: Vec<TokenId>) -> Vec<Option<Token>>;
+
+    /// Returns the balance of `account_id` for `token_id`.
+    fn mt_balance_of(&self, account_id: AccountId, token_id: TokenId) -> U128;
+
+    /// Returns the balances of `account_id` for each of `token_ids`.
+    fn mt_batch_balance_of(&self, account_id: AccountId, token_ids: Vec<TokenId>) -> Vec<U128>;
+
+    /// Returns the total supply of `token_id`, or `null` if there is no such token.
+    fn mt_supply(&self, token_id: TokenId) -> Option<U128>;
+
+    /// Returns the total supply of each of `token_ids`.
+    fn mt_batch_supply(&self, token_ids: Vec<TokenId>) -> Vec<Option<U128>>;
+}
@@ -0,0 +1,35 @@
+use crate::multi_token::token::TokenId;
+use near_sdk::json_types::U128;
+use near_sdk::{ext_contract, AccountId, PromiseOrValue};
+
+/// Used when multi tokens are transferred using `mt_transfer_call` or `mt_batch_transfer_call`.
+/// This trait is implemented on the receiving contract, not on the MT contract.
+#[ext_contract(ext_mt_receiver)]
+pub trait MultiTokenReceiver {
+    /// Take some action after receiving multi tokens
+    ///
+    /// Requirements:
+    /// * Contract MUST restrict calls to this function to a set of whitelisted MT
+    ///   contracts
+    ///
+    /// Arguments:
+    /// * `sender_id`: the sender of `mt_transfer_call`
+    /// * `previous_owner_ids`: the accounts that owned the tokens prior to them being
+    ///   transferred to this contract, which can differ from `sender_id` if using
+    ///   Approval Management extension
+    /// * `token_ids`: the `token_ids` argument given to `mt_batch_transfer_call`
+    /// * `amounts`: the `amounts` argument given to `mt_batch_transfer_call`
+    /// * `msg`: information necessary for this contract to know how to process the
+    ///   request. This may include method names and/or arguments.
+    ///
+    /// Returns the number of unused tokens for each of `token_ids`, which will be
+    /// returned to their previous owners.
+    fn mt_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        previous_owner_ids: Vec<AccountId>,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<U128>,
+        msg: String,
+    ) -> PromiseOrValue<Vec<U128>>;
+}
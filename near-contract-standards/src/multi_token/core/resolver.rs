@@ -0,0 +1,65 @@
+use crate::multi_token::token::{ClearedApproval, TokenId};
+use near_sdk::json_types::U128;
+use near_sdk::{ext_contract, AccountId};
+
+/// Used when multi tokens are transferred using `mt_transfer_call`. This is the method that's
+/// called after `mt_on_transfer`. This trait is implemented on the MT contract.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::{PanicOnDefault, AccountId, near};
+/// use near_sdk::json_types::U128;
+/// use near_contract_standards::multi_token::{MultiToken, MultiTokenResolver, TokenId, ClearedApproval};
+///
+/// #[near(contract_state)]
+/// #[derive(PanicOnDefault)]
+/// pub struct Contract {
+///    tokens: MultiToken,
+///}
+/// #[near]
+/// impl MultiTokenResolver for Contract {
+///     #[private]
+///     fn mt_resolve_transfer(&mut self, previous_owner_ids: Vec<AccountId>, receiver_id: AccountId, token_ids: Vec<TokenId>, amounts: Vec<U128>, approvals: Option<Vec<Option<Vec<ClearedApproval>>>>) -> Vec<U128> {
+///         self.tokens.mt_resolve_transfer(previous_owner_ids, receiver_id, token_ids, amounts, approvals)
+///     }
+/// }
+/// ```
+///
+#[ext_contract(ext_mt_resolver)]
+pub trait MultiTokenResolver {
+    /// Finalize an `mt_transfer_call` chain of cross-contract calls.
+    ///
+    /// The `mt_transfer_call` process:
+    ///
+    /// 1. Sender calls `mt_transfer_call` on MT contract
+    /// 2. MT contract transfers tokens from sender to receiver
+    /// 3. MT contract calls `mt_on_transfer` on receiver contract
+    /// 4+. [receiver contract may make other cross-contract calls]
+    /// N. MT contract resolves promise chain with `mt_resolve_transfer`, and may
+    ///    return unused tokens to their previous owners
+    ///
+    /// Requirements:
+    /// * Contract MUST forbid calls to this function by any account except self
+    /// * If promise chain failed, contract MUST revert token transfers
+    /// * If promise chain resolves with a list of unused amounts, contract MUST
+    ///   return those amounts to the previous owners
+    ///
+    /// Arguments:
+    /// * `previous_owner_ids`: the owner of each token prior to the call to `mt_transfer_call`
+    /// * `receiver_id`: the `receiver_id` argument given to `mt_transfer_call`
+    /// * `token_ids`: the `token_ids` argument given to `mt_transfer_call`
+    /// * `amounts`: the `amounts` argument given to `mt_transfer_call`
+    /// * `approvals`: if using Approval Management, contract MUST provide the approvals
+    ///   consumed by each transfer, and restore them in case of revert.
+    ///
+    /// Returns the amount of each token that was actually transferred to `receiver_id`.
+    fn mt_resolve_transfer(
+        &mut self,
+        previous_owner_ids: Vec<AccountId>,
+        receiver_id: AccountId,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<U128>,
+        approvals: Option<Vec<Option<Vec<ClearedApproval>>>>,
+    ) -> Vec<U128>;
+}
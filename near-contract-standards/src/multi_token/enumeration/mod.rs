@@ -0,0 +1,65 @@
+mod enumeration_impl;
+
+use crate::multi_token::token::Token;
+use near_sdk::json_types::U128;
+use near_sdk::{ext_contract, AccountId};
+
+/// Offers methods helpful in determining account ownership of multi tokens and provides a way to page through tokens per owner.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::{PanicOnDefault, AccountId, near};
+/// use near_contract_standards::multi_token::{MultiToken, MultiTokenEnumeration, Token};
+/// use near_sdk::json_types::U128;
+///
+/// #[near(contract_state)]
+/// #[derive(PanicOnDefault)]
+/// pub struct Contract {
+///    tokens: MultiToken,
+///}
+///
+/// #[near]
+/// impl MultiTokenEnumeration for Contract {
+///     fn mt_tokens(&self, from_index: Option<U128>, limit: Option<u32>) -> Vec<Token> {
+///         self.tokens.mt_tokens(from_index, limit)
+///     }
+///
+///     fn mt_tokens_for_owner(&self, account_id: AccountId, from_index: Option<U128>, limit: Option<u32>) -> Vec<Token> {
+///         self.tokens.mt_tokens_for_owner(account_id, from_index, limit)
+///     }
+/// }
+/// ```
+///
+#[ext_contract(ext_mt_enumeration)]
+pub trait MultiTokenEnumeration {
+    /// Get a list of all tokens
+    ///
+    /// Arguments:
+    /// * `from_index`: a string representing an unsigned 128-bit integer,
+    ///    representing the starting index of tokens to return
+    /// * `limit`: the maximum number of tokens to return
+    ///
+    /// Returns an array of Token objects, as described in Core standard
+    fn mt_tokens(
+        &self,
+        from_index: Option<U128>, // default: "0"
+        limit: Option<u32>,       // default: unlimited (could fail due to gas limit)
+    ) -> Vec<Token>;
+
+    /// Get list of all tokens held by a given account
+    ///
+    /// Arguments:
+    /// * `account_id`: a valid NEAR account
+    /// * `from_index`: a string representing an unsigned 128-bit integer,
+    ///    representing the starting index of tokens to return
+    /// * `limit`: the maximum number of tokens to return
+    ///
+    /// Returns a paginated list of all tokens held by this account
+    fn mt_tokens_for_owner(
+        &self,
+        account_id: AccountId,
+        from_index: Option<U128>, // default: "0"
+        limit: Option<u32>,       // default: unlimited (could fail due to gas limit)
+    ) -> Vec<Token>;
+}
@@ -0,0 +1,27 @@
+/// The approval management extension for multi tokens.
+pub mod approval;
+/// The [core multi token standard](https://github.com/near/NEPs/blob/master/neps/nep-0245.md). This can be though of as the base standard, with the others being extension standards.
+pub mod core;
+/// Trait for the multi token enumeration extension.
+/// This provides useful view-only methods returning all tokens and tokens per owner.
+pub mod enumeration;
+/// Macros typically used by a contract wanting to take advantage of the multi
+/// token NEAR contract standard approach.
+mod macros;
+/// Metadata traits and implementation for multi tokens.
+/// This covers both the contract metadata and the individual token metadata.
+pub mod metadata;
+/// The Token struct for the multi token.
+mod token;
+pub use self::token::{Approval, ClearedApproval, Token, TokenId};
+
+/// MT utility functions
+mod utils;
+pub use utils::*;
+
+pub use self::approval::MultiTokenApproval;
+pub use self::core::MultiToken;
+pub use self::core::MultiTokenResolver;
+pub use self::enumeration::MultiTokenEnumeration;
+
+pub mod events;
@@ -0,0 +1,29 @@
+use crate::multi_token::metadata::TokenMetadata;
+use near_sdk::json_types::U128;
+use near_sdk::{near, AccountId};
+
+/// Note that token IDs for multi tokens are strings on NEAR, same as for NFTs.
+pub type TokenId = String;
+
+/// Information about a single token type. `owner_id` is the account that minted the token; a
+/// multi token can have any number of holders, which can be queried with `mt_balance_of`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[near(serializers=[json])]
+pub struct Token {
+    pub token_id: TokenId,
+    pub owner_id: Option<AccountId>,
+    pub metadata: Option<TokenMetadata>,
+}
+
+/// An approval granted by a token holder to another account, allowing it to transfer up to
+/// `amount` tokens on the holder's behalf.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[near(serializers=[borsh, json])]
+pub struct Approval {
+    pub amount: u128,
+    pub approval_id: u64,
+}
+
+/// Approval consumed by a transfer: `(approved account, approval id, amount)`. Passed to
+/// `mt_resolve_transfer` so that it can be restored if the transfer is reverted.
+pub type ClearedApproval = (AccountId, u64, U128);
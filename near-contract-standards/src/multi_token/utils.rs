@@ -0,0 +1,17 @@
+use near_sdk::{env, AccountId, Promise};
+use std::mem::size_of;
+
+/// Storage used by a single approval: the account ID, the approved amount and the approval ID.
+pub fn bytes_for_approved_account_id(account_id: &AccountId) -> u64 {
+    // The extra 4 bytes are coming from Borsh serialization to store the length of the string.
+    account_id.as_str().len() as u64 + 4 + size_of::<u128>() as u64 + size_of::<u64>() as u64
+}
+
+pub fn refund_approved_account_ids<'a, I>(account_id: AccountId, approved_account_ids: I) -> Promise
+where
+    I: Iterator<Item = &'a AccountId>,
+{
+    let storage_released: u64 = approved_account_ids.map(bytes_for_approved_account_id).sum();
+    Promise::new(account_id)
+        .transfer(env::storage_byte_cost().saturating_mul(storage_released.into()))
+}
@@ -143,3 +143,46 @@ macro_rules! impl_non_fungible_token_enumeration {
         }
     };
 }
+
+/// Non-fungible token payouts allow marketplaces to split the proceeds of a sale
+/// between the token owner and the royalty holders. Takes name of the Contract struct,
+/// the inner field for the token and the inner field for the royalties.
+#[macro_export]
+macro_rules! impl_non_fungible_token_payout {
+    ($contract: ident, $token: ident, $royalties: ident) => {
+        use $crate::non_fungible_token::payout::NonFungibleTokenPayout;
+
+        #[near]
+        impl NonFungibleTokenPayout for $contract {
+            fn nft_payout(
+                &self,
+                token_id: TokenId,
+                balance: near_sdk::json_types::U128,
+                max_len_payout: Option<u32>,
+            ) -> $crate::non_fungible_token::payout::Payout {
+                self.$token.nft_payout(&self.$royalties, token_id, balance, max_len_payout)
+            }
+
+            #[payable]
+            fn nft_transfer_payout(
+                &mut self,
+                receiver_id: AccountId,
+                token_id: TokenId,
+                approval_id: Option<u64>,
+                memo: Option<String>,
+                balance: near_sdk::json_types::U128,
+                max_len_payout: Option<u32>,
+            ) -> $crate::non_fungible_token::payout::Payout {
+                self.$token.nft_transfer_payout(
+                    &self.$royalties,
+                    receiver_id,
+                    token_id,
+                    approval_id,
+                    memo,
+                    balance,
+                    max_len_payout,
+                )
+            }
+        }
+    };
+}
@@ -0,0 +1,80 @@
+mod payout_impl;
+
+pub use payout_impl::*;
+
+use crate::non_fungible_token::token::TokenId;
+use near_sdk::json_types::U128;
+use near_sdk::{ext_contract, near, AccountId};
+use std::collections::HashMap;
+
+/// Amounts each account should receive from a sale, as described by the
+/// [royalties and payouts standard](https://nomicon.io/Standards/Tokens/NonFungibleToken/Payout).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[near(serializers=[json])]
+pub struct Payout {
+    pub payout: HashMap<AccountId, U128>,
+}
+
+/// Offers methods for marketplaces to find out how the proceeds of a sale should be split
+/// between the token owner and the royalty holders.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::{PanicOnDefault, AccountId, near};
+/// use near_sdk::json_types::U128;
+/// use near_contract_standards::non_fungible_token::{NonFungibleToken, TokenId};
+/// use near_contract_standards::non_fungible_token::payout::{NonFungibleTokenPayout, Payout, Royalties};
+///
+/// #[near(contract_state)]
+/// #[derive(PanicOnDefault)]
+/// pub struct Contract {
+///    tokens: NonFungibleToken,
+///    royalties: Royalties,
+///}
+///
+/// #[near]
+/// impl NonFungibleTokenPayout for Contract {
+///     fn nft_payout(&self, token_id: TokenId, balance: U128, max_len_payout: Option<u32>) -> Payout {
+///         self.tokens.nft_payout(&self.royalties, token_id, balance, max_len_payout)
+///     }
+///
+///     #[payable]
+///     fn nft_transfer_payout(&mut self, receiver_id: AccountId, token_id: TokenId, approval_id: Option<u64>, memo: Option<String>, balance: U128, max_len_payout: Option<u32>) -> Payout {
+///         self.tokens.nft_transfer_payout(&self.royalties, receiver_id, token_id, approval_id, memo, balance, max_len_payout)
+///     }
+/// }
+/// ```
+///
+#[ext_contract(ext_nft_payout)]
+pub trait NonFungibleTokenPayout {
+    /// Given a `token_id` and NEAR-denominated balance, return the `Payout` struct
+    /// for the given token.
+    ///
+    /// Requirements
+    /// * Contract MUST panic if the payout doesn't fit in `max_len_payout` accounts
+    ///
+    /// Arguments:
+    /// * `token_id`: the token being sold
+    /// * `balance`: the sale price, in yoctoⓃ
+    /// * `max_len_payout` (optional): the maximum number of accounts the caller is able
+    ///   to pay out to
+    fn nft_payout(&self, token_id: TokenId, balance: U128, max_len_payout: Option<u32>) -> Payout;
+
+    /// Given a `token_id` and NEAR-denominated balance, transfer the token
+    /// and return the `Payout` struct for the given token. Panic if the
+    /// length of the payout exceeds `max_len_payout.`
+    ///
+    /// Requirements
+    /// * Same as `nft_transfer`, including the 1 yoctoⓃ deposit and approval checks
+    /// * The payout is computed for the owner of the token before the transfer
+    fn nft_transfer_payout(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+        balance: U128,
+        max_len_payout: Option<u32>,
+    ) -> Payout;
+}
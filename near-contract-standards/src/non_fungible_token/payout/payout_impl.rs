@@ -0,0 +1,272 @@
+use super::Payout;
+use crate::non_fungible_token::token::TokenId;
+use crate::non_fungible_token::NonFungibleToken;
+use near_sdk::collections::LookupMap;
+use near_sdk::json_types::U128;
+use near_sdk::{assert_one_yocto, env, near, require, AccountId, IntoStorageKey};
+use std::collections::HashMap;
+
+/// Royalties are expressed in basis points, this being 100% of the sale price.
+pub const ROYALTY_TOTAL_BASIS_POINTS: u16 = 10_000;
+
+/// Default limit on the number of royalty holders of a single token.
+pub const DEFAULT_MAX_ROYALTY_PAYEES: u32 = 10;
+
+/// Royalty storage extension for [`NonFungibleToken`]. Royalties are kept in a separate struct
+/// so that the state layout of existing [`NonFungibleToken`] contracts is left untouched.
+#[near]
+pub struct Royalties {
+    // royalty holders of each token, in basis points of the sale price
+    pub royalties_by_id: LookupMap<TokenId, HashMap<AccountId, u16>>,
+
+    // maximum number of royalty holders a single token can have
+    pub max_payees: u32,
+}
+
+impl Royalties {
+    pub fn new<S>(prefix: S, max_payees: u32) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self { royalties_by_id: LookupMap::new(prefix), max_payees }
+    }
+
+    /// Sets the royalties of `token_id`, replacing any previous ones. An empty map removes the
+    /// royalties of the token.
+    ///
+    /// Panics if the royalties add up to more than 100% or have more than `max_payees` holders.
+    pub fn set(
+        &mut self,
+        #[allow(clippy::ptr_arg)] token_id: &TokenId,
+        royalty: HashMap<AccountId, u16>,
+    ) {
+        if royalty.is_empty() {
+            self.royalties_by_id.remove(token_id);
+            return;
+        }
+        require!(royalty.len() as u64 <= self.max_payees as u64, "Too many royalty payees");
+        let total = royalty.values().map(|bps| *bps as u64).sum::<u64>();
+        require!(
+            total <= ROYALTY_TOTAL_BASIS_POINTS as u64,
+            "Royalties can't exceed 100% of the sale price"
+        );
+        self.royalties_by_id.insert(token_id, &royalty);
+    }
+
+    /// Returns the royalties of `token_id`, which is empty if none were set.
+    pub fn get(&self, #[allow(clippy::ptr_arg)] token_id: &TokenId) -> HashMap<AccountId, u16> {
+        self.royalties_by_id.get(token_id).unwrap_or_default()
+    }
+
+    /// Removes the royalties of `token_id`, typically when the token is burned.
+    pub fn remove(
+        &mut self,
+        #[allow(clippy::ptr_arg)] token_id: &TokenId,
+    ) -> Option<HashMap<AccountId, u16>> {
+        self.royalties_by_id.remove(token_id)
+    }
+
+    /// Splits `balance` between the royalty holders of `token_id` and `owner_id`, who receives
+    /// whatever is left after royalties.
+    ///
+    /// Panics if the payout doesn't fit in `max_len_payout` accounts.
+    pub fn create_payout(
+        &self,
+        #[allow(clippy::ptr_arg)] token_id: &TokenId,
+        owner_id: &AccountId,
+        balance: u128,
+        max_len_payout: Option<u32>,
+    ) -> Payout {
+        let royalty = self.get(token_id);
+        let mut payout = HashMap::with_capacity(royalty.len() + 1);
+        let mut paid: u128 = 0;
+        for (account_id, basis_points) in royalty {
+            let amount = apply_basis_points(balance, basis_points);
+            paid += amount;
+            payout.insert(account_id, U128(amount));
+        }
+        // The owner gets the rest, on top of their own royalty if they have one.
+        let owner_amount = payout.entry(owner_id.clone()).or_insert(U128(0));
+        owner_amount.0 += balance - paid;
+
+        if let Some(max_len_payout) = max_len_payout {
+            require!(
+                payout.len() as u64 <= max_len_payout as u64,
+                format!("Payout has {} accounts, exceeding max_len_payout", payout.len())
+            );
+        }
+        Payout { payout }
+    }
+}
+
+/// Computes `balance * basis_points / 10_000` without overflowing.
+fn apply_basis_points(balance: u128, basis_points: u16) -> u128 {
+    let total = ROYALTY_TOTAL_BASIS_POINTS as u128;
+    let basis_points = basis_points as u128;
+    balance / total * basis_points + balance % total * basis_points / total
+}
+
+impl NonFungibleToken {
+    /// Returns the payout of a sale of `token_id` for `balance`, paying its current owner.
+    pub fn nft_payout(
+        &self,
+        royalties: &Royalties,
+        token_id: TokenId,
+        balance: U128,
+        max_len_payout: Option<u32>,
+    ) -> Payout {
+        let owner_id =
+            self.owner_by_id.get(&token_id).unwrap_or_else(|| env::panic_str("Token not found"));
+        royalties.create_payout(&token_id, &owner_id, balance.0, max_len_payout)
+    }
+
+    /// Transfers `token_id` exactly like `nft_transfer` does, and returns the payout of the sale
+    /// for `balance`, paying the owner prior to the transfer.
+    #[allow(clippy::too_many_arguments)]
+    pub fn nft_transfer_payout(
+        &mut self,
+        royalties: &Royalties,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+        balance: U128,
+        max_len_payout: Option<u32>,
+    ) -> Payout {
+        assert_one_yocto();
+        let sender_id = env::predecessor_account_id();
+        // Compute the payout first, so that the transfer is not done if it doesn't fit.
+        let payout = self.nft_payout(royalties, token_id.clone(), balance, max_len_payout);
+        self.internal_transfer(&sender_id, &receiver_id, &token_id, approval_id, memo);
+        payout
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::non_fungible_token::core::NonFungibleTokenCore;
+    use crate::non_fungible_token::NonFungibleTokenApproval;
+    use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+    use near_sdk::{testing_env, NearToken};
+
+    fn setup() -> (VMContextBuilder, NonFungibleToken, Royalties) {
+        let mut context = VMContextBuilder::new();
+        context
+            .current_account_id(accounts(0))
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(NearToken::from_near(1));
+        testing_env!(context.build());
+        let mut tokens = NonFungibleToken::new(
+            b"t".to_vec(),
+            accounts(0),
+            None::<Vec<u8>>,
+            None::<Vec<u8>>,
+            Some(b"a".to_vec()),
+        );
+        tokens.internal_mint("0".to_string(), accounts(1), None);
+        let mut royalties = Royalties::new(b"r".to_vec(), DEFAULT_MAX_ROYALTY_PAYEES);
+        royalties.set(&"0".to_string(), HashMap::from([(accounts(2), 1_000), (accounts(3), 250)]));
+        (context, tokens, royalties)
+    }
+
+    #[test]
+    fn payout() {
+        let (_, tokens, royalties) = setup();
+        let payout = tokens.nft_payout(&royalties, "0".to_string(), U128(1_000_000), Some(3));
+        assert_eq!(
+            payout.payout,
+            HashMap::from([
+                (accounts(1), U128(875_000)),
+                (accounts(2), U128(100_000)),
+                (accounts(3), U128(25_000)),
+            ])
+        );
+    }
+
+    #[test]
+    fn payout_without_royalties() {
+        let (_, tokens, mut royalties) = setup();
+        royalties.set(&"0".to_string(), HashMap::new());
+        let payout = tokens.nft_payout(&royalties, "0".to_string(), U128(u128::MAX), Some(1));
+        assert_eq!(payout.payout, HashMap::from([(accounts(1), U128(u128::MAX))]));
+    }
+
+    #[test]
+    fn payout_does_not_overflow() {
+        let (_, tokens, royalties) = setup();
+        let payout = tokens.nft_payout(&royalties, "0".to_string(), U128(u128::MAX), None);
+        let total: u128 = payout.payout.values().map(|amount| amount.0).sum();
+        assert_eq!(total, u128::MAX);
+    }
+
+    #[test]
+    #[should_panic(expected = "exceeding max_len_payout")]
+    fn payout_exceeds_max_len() {
+        let (_, tokens, royalties) = setup();
+        tokens.nft_payout(&royalties, "0".to_string(), U128(100), Some(2));
+    }
+
+    #[test]
+    #[should_panic(expected = "Royalties can't exceed 100% of the sale price")]
+    fn royalties_above_cap() {
+        let (_, _, mut royalties) = setup();
+        royalties
+            .set(&"1".to_string(), HashMap::from([(accounts(2), 9_000), (accounts(3), 1_001)]));
+    }
+
+    #[test]
+    #[should_panic(expected = "Too many royalty payees")]
+    fn too_many_payees() {
+        let (_, _, mut royalties) = setup();
+        royalties.max_payees = 1;
+        royalties.set(&"1".to_string(), HashMap::from([(accounts(2), 1), (accounts(3), 1)]));
+    }
+
+    #[test]
+    fn transfer_payout_by_approved_account() {
+        let (mut context, mut tokens, royalties) = setup();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(NearToken::from_millinear(10))
+            .build());
+        tokens.nft_approve("0".to_string(), accounts(4), None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(4))
+            .attached_deposit(NearToken::from_yoctonear(1))
+            .build());
+        let payout = tokens.nft_transfer_payout(
+            &royalties,
+            accounts(5),
+            "0".to_string(),
+            Some(1),
+            None,
+            U128(10_000),
+            Some(3),
+        );
+
+        assert_eq!(payout.payout[&accounts(1)], U128(8_750));
+        assert_eq!(tokens.nft_token("0".to_string()).unwrap().owner_id, accounts(5));
+        assert_eq!(get_logs().len(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Sender not approved")]
+    fn transfer_payout_requires_approval() {
+        let (mut context, mut tokens, royalties) = setup();
+        testing_env!(context
+            .predecessor_account_id(accounts(4))
+            .attached_deposit(NearToken::from_yoctonear(1))
+            .build());
+        tokens.nft_transfer_payout(
+            &royalties,
+            accounts(5),
+            "0".to_string(),
+            None,
+            None,
+            U128(10_000),
+            None,
+        );
+    }
+}
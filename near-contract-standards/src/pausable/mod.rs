@@ -0,0 +1,90 @@
+//! Pausing of individual contract features during an incident.
+//!
+//! [`Pausable`] stores the set of paused feature names. A feature is any name the contract
+//! chooses, e.g. the name of the method it guards. Methods opt in by calling
+//! [`Pausable::assert_not_paused`] first, and the pause/unpause methods check the caller through
+//! a pluggable [`PauseAuthority`], which is implemented for
+//! [`AccessControl`](crate::access_control::AccessControl) and for closures.
+//!
+//! # Examples
+//! See [`PausableCore`] for example usage.
+
+pub mod events;
+mod pausable_impl;
+
+pub use pausable_impl::*;
+
+use near_sdk::ext_contract;
+
+/// Methods to inspect and change which features of a contract are paused.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::{near, PanicOnDefault, AccountId};
+/// use near_sdk::json_types::U128;
+/// use near_contract_standards::access_control::AccessControl;
+/// use near_contract_standards::pausable::{Pausable, PausableCore};
+/// use near_contract_standards::fungible_token::FungibleToken;
+///
+/// #[near(contract_state)]
+/// #[derive(PanicOnDefault)]
+/// pub struct Contract {
+///     token: FungibleToken,
+///     acl: AccessControl,
+///     pausable: Pausable,
+/// }
+///
+/// #[near]
+/// impl Contract {
+///     #[payable]
+///     pub fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128) {
+///         self.pausable.assert_not_paused("ft_transfer");
+///         // ...
+///     }
+/// }
+///
+/// #[near]
+/// impl PausableCore for Contract {
+///     fn pa_is_paused(&self, feature: String) -> bool {
+///         self.pausable.pa_is_paused(feature)
+///     }
+///
+///     fn pa_all_paused(&self) -> Vec<String> {
+///         self.pausable.pa_all_paused()
+///     }
+///
+///     #[payable]
+///     fn pa_pause_feature(&mut self, feature: String) -> bool {
+///         self.pausable.pause(feature, &self.acl)
+///     }
+///
+///     #[payable]
+///     fn pa_unpause_feature(&mut self, feature: String) -> bool {
+///         self.pausable.unpause(&feature, &self.acl)
+///     }
+/// }
+/// ```
+///
+#[ext_contract(ext_pausable)]
+pub trait PausableCore {
+    /// Returns whether `feature` is paused.
+    fn pa_is_paused(&self, feature: String) -> bool;
+
+    /// Returns all the paused features.
+    fn pa_all_paused(&self) -> Vec<String>;
+
+    /// Pauses `feature`. Returns whether it wasn't already paused.
+    ///
+    /// Requirements:
+    /// * Caller of the method must be allowed to pause by the contract's authority
+    /// * Caller of the method must attach a deposit of 1 yoctoⓃ for security purposes
+    fn pa_pause_feature(&mut self, feature: String) -> bool;
+
+    /// Unpauses `feature`. Returns whether it was paused.
+    ///
+    /// Requirements:
+    /// * Caller of the method must be allowed to unpause by the contract's authority
+    /// * Caller of the method must attach a deposit of 1 yoctoⓃ for security purposes
+    fn pa_unpause_feature(&mut self, feature: String) -> bool;
+}
@@ -0,0 +1,217 @@
+use crate::access_control::AccessControl;
+use crate::pausable::events::{Paused, Unpaused};
+use near_sdk::store::IterableSet;
+use near_sdk::{assert_one_yocto, env, near, IntoStorageKey};
+
+/// Role which, besides the owner, may pause and unpause features of a contract using
+/// [`AccessControl`] as its [`PauseAuthority`].
+pub const PAUSE_MANAGER_ROLE: &str = "pause_manager";
+
+/// Decides who may pause and unpause features. Implementations panic if the predecessor is not
+/// allowed to.
+pub trait PauseAuthority {
+    fn assert_can_pause(&self, feature: &str);
+
+    fn assert_can_unpause(&self, feature: &str) {
+        self.assert_can_pause(feature)
+    }
+}
+
+/// Allows the owner and accounts with the [`PAUSE_MANAGER_ROLE`] role.
+impl PauseAuthority for AccessControl {
+    fn assert_can_pause(&self, _feature: &str) {
+        self.assert_owner_or_any_role(&[PAUSE_MANAGER_ROLE]);
+    }
+}
+
+/// Allows arbitrary checks, e.g. `&|_: &str| require!(env::predecessor_account_id() == owner)`.
+impl<F: Fn(&str)> PauseAuthority for F {
+    fn assert_can_pause(&self, feature: &str) {
+        self(feature)
+    }
+}
+
+/// Implementation of a storage-backed set of paused features.
+#[near]
+pub struct Pausable {
+    /// Names of the paused features.
+    pub paused: IterableSet<String>,
+}
+
+impl Pausable {
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self { paused: IterableSet::new(prefix) }
+    }
+
+    /// Returns whether `feature` is paused.
+    pub fn is_paused(&self, feature: &str) -> bool {
+        self.paused.contains(feature)
+    }
+
+    /// Panics if `feature` is paused. Call this first in every method which can be paused.
+    pub fn assert_not_paused(&self, feature: &str) {
+        if self.is_paused(feature) {
+            env::panic_str(&format!("Pausable: {} is paused", feature));
+        }
+    }
+
+    /// Panics unless `feature` is paused, for methods only available during an incident.
+    pub fn assert_paused(&self, feature: &str) {
+        if !self.is_paused(feature) {
+            env::panic_str(&format!("Pausable: {} is not paused", feature));
+        }
+    }
+
+    /// Pauses `feature` after checking the caller with `authority` and the attached deposit.
+    /// Returns whether it wasn't already paused.
+    pub fn pause(&mut self, feature: String, authority: &impl PauseAuthority) -> bool {
+        assert_one_yocto();
+        authority.assert_can_pause(&feature);
+        self.internal_pause(feature)
+    }
+
+    /// Unpauses `feature` after checking the caller with `authority` and the attached deposit.
+    /// Returns whether it was paused.
+    pub fn unpause(&mut self, feature: &str, authority: &impl PauseAuthority) -> bool {
+        assert_one_yocto();
+        authority.assert_can_unpause(feature);
+        self.internal_unpause(feature)
+    }
+
+    /// Pauses `feature` without any checks. Returns whether it wasn't already paused.
+    pub fn internal_pause(&mut self, feature: String) -> bool {
+        let by = env::predecessor_account_id();
+        if !self.paused.insert(feature.clone()) {
+            return false;
+        }
+        Paused { feature: &feature, by: &by }.emit();
+        true
+    }
+
+    /// Unpauses `feature` without any checks. Returns whether it was paused.
+    pub fn internal_unpause(&mut self, feature: &str) -> bool {
+        if !self.paused.remove(feature) {
+            return false;
+        }
+        Unpaused { feature, by: &env::predecessor_account_id() }.emit();
+        true
+    }
+}
+
+impl super::PausableCore for Pausable {
+    fn pa_is_paused(&self, feature: String) -> bool {
+        self.is_paused(&feature)
+    }
+
+    fn pa_all_paused(&self) -> Vec<String> {
+        self.paused.iter().cloned().collect()
+    }
+
+    /// Without an authority only the contract itself may pause; contracts normally implement
+    /// this with [`Pausable::pause`] and their own authority instead.
+    fn pa_pause_feature(&mut self, feature: String) -> bool {
+        self.pause(feature, &assert_self)
+    }
+
+    fn pa_unpause_feature(&mut self, feature: String) -> bool {
+        self.unpause(&feature, &assert_self)
+    }
+}
+
+fn assert_self(_feature: &str) {
+    near_sdk::assert_self();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::access_control::AccessControlCore;
+    use crate::pausable::PausableCore;
+    use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+    use near_sdk::{require, testing_env, AccountId, NearToken};
+
+    fn setup() -> (VMContextBuilder, Pausable, AccessControl) {
+        let mut context = VMContextBuilder::new();
+        context
+            .current_account_id(accounts(0))
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(NearToken::from_yoctonear(1));
+        testing_env!(context.build());
+        (context, Pausable::new(b"p".to_vec()), AccessControl::new(b"a".to_vec(), accounts(0)))
+    }
+
+    fn call_from(context: &mut VMContextBuilder, account_id: AccountId) {
+        testing_env!(context.predecessor_account_id(account_id).build());
+    }
+
+    #[test]
+    fn pause_and_unpause() {
+        let (_, mut pausable, acl) = setup();
+        pausable.assert_not_paused("ft_transfer");
+        assert!(pausable.pause("ft_transfer".to_string(), &acl));
+        assert!(!pausable.pause("ft_transfer".to_string(), &acl));
+        assert!(pausable.pa_is_paused("ft_transfer".to_string()));
+        assert!(!pausable.pa_is_paused("nft_transfer".to_string()));
+        assert_eq!(pausable.pa_all_paused(), vec!["ft_transfer".to_string()]);
+        pausable.assert_paused("ft_transfer");
+
+        assert!(pausable.unpause("ft_transfer", &acl));
+        assert!(!pausable.unpause("ft_transfer", &acl));
+        pausable.assert_not_paused("ft_transfer");
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"pausable","version":"1.0.0","event":"paused","data":[{"feature":"ft_transfer","by":"alice"}]}"#,
+                r#"EVENT_JSON:{"standard":"pausable","version":"1.0.0","event":"unpaused","data":[{"feature":"ft_transfer","by":"alice"}]}"#,
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Pausable: ft_transfer is paused")]
+    fn paused_feature_panics() {
+        let (_, mut pausable, acl) = setup();
+        pausable.pause("ft_transfer".to_string(), &acl);
+        pausable.assert_not_paused("ft_transfer");
+    }
+
+    #[test]
+    fn pause_manager_role() {
+        let (mut context, mut pausable, mut acl) = setup();
+        acl.acl_grant_role(PAUSE_MANAGER_ROLE.to_string(), accounts(1));
+        call_from(&mut context, accounts(1));
+        assert!(pausable.pause("ft_transfer".to_string(), &acl));
+    }
+
+    #[test]
+    #[should_panic(expected = "Method requires the owner or one of the roles")]
+    fn pause_without_authority() {
+        let (mut context, mut pausable, acl) = setup();
+        call_from(&mut context, accounts(1));
+        pausable.pause("ft_transfer".to_string(), &acl);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only guardian may unpause")]
+    fn closure_authority() {
+        let (mut context, mut pausable, _) = setup();
+        let guardian = |_: &str| {
+            require!(env::predecessor_account_id() == accounts(2), "Only guardian may unpause")
+        };
+        call_from(&mut context, accounts(2));
+        pausable.pause("ft_transfer".to_string(), &guardian);
+        call_from(&mut context, accounts(1));
+        pausable.unpause("ft_transfer", &guardian);
+    }
+
+    #[test]
+    #[should_panic(expected = "Requires attached deposit of exactly 1 yoctoNEAR")]
+    fn pause_without_deposit() {
+        let (mut context, mut pausable, acl) = setup();
+        testing_env!(context.attached_deposit(NearToken::from_near(0)).build());
+        pausable.pause("ft_transfer".to_string(), &acl);
+    }
+}
@@ -0,0 +1,177 @@
+//! Events emitted by the [`StagedUpgrade`](super::StagedUpgrade) component.
+//!
+//! These follow the events format (nep-297), using `staged_upgrade` as the standard name:
+//! <https://github.com/near/NEPs/blob/master/specs/Standards/EventsFormat.md>
+//!
+//! The events are [`CodeStaged`], [`CodeUnstaged`], [`UpgradeStarted`], [`StateMigrated`],
+//! [`UpgradeCompleted`] and [`UpgradeFailed`]. They can be logged by calling `.emit()` on them.
+
+use crate::event::NearEvent;
+use near_sdk::json_types::Base58CryptoHash;
+use near_sdk::serde::Serialize;
+
+/// Data to log when new code gets staged. To log this event,
+/// call [`.emit()`](CodeStaged::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CodeStaged {
+    pub code_hash: Base58CryptoHash,
+    /// Nanoseconds timestamp after which the code can be deployed.
+    pub deployable_at: u64,
+}
+
+impl CodeStaged {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        new_staged_upgrade_v1(StagedUpgradeEventKind::CodeStaged(&[self])).emit()
+    }
+}
+
+/// Data to log when staged code gets removed without being deployed. To log this event,
+/// call [`.emit()`](CodeUnstaged::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CodeUnstaged {
+    pub code_hash: Base58CryptoHash,
+}
+
+impl CodeUnstaged {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        new_staged_upgrade_v1(StagedUpgradeEventKind::CodeUnstaged(&[self])).emit()
+    }
+}
+
+/// Data to log when the deploy and migration of staged code gets scheduled. To log this event,
+/// call [`.emit()`](UpgradeStarted::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct UpgradeStarted {
+    pub code_hash: Base58CryptoHash,
+    pub from_version: u32,
+}
+
+impl UpgradeStarted {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        new_staged_upgrade_v1(StagedUpgradeEventKind::UpgradeStarted(&[self])).emit()
+    }
+}
+
+/// Data to log when the new code migrated the contract state. To log this event,
+/// call [`.emit()`](StateMigrated::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StateMigrated {
+    pub from_version: u32,
+    pub to_version: u32,
+}
+
+impl StateMigrated {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        new_staged_upgrade_v1(StagedUpgradeEventKind::StateMigrated(&[self])).emit()
+    }
+}
+
+/// Data to log when the new code got deployed and migrated successfully. To log this event,
+/// call [`.emit()`](UpgradeCompleted::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct UpgradeCompleted {
+    pub code_hash: Base58CryptoHash,
+    pub state_version: u32,
+}
+
+impl UpgradeCompleted {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        new_staged_upgrade_v1(StagedUpgradeEventKind::UpgradeCompleted(&[self])).emit()
+    }
+}
+
+/// Data to log when the migration failed and the deploy was rolled back. To log this event,
+/// call [`.emit()`](UpgradeFailed::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct UpgradeFailed {
+    pub code_hash: Base58CryptoHash,
+    pub state_version: u32,
+}
+
+impl UpgradeFailed {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        new_staged_upgrade_v1(StagedUpgradeEventKind::UpgradeFailed(&[self])).emit()
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub(crate) struct StagedUpgradeEvent<'a> {
+    version: &'static str,
+    #[serde(flatten)]
+    event_kind: StagedUpgradeEventKind<'a>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(tag = "event", content = "data")]
+#[serde(rename_all = "snake_case")]
+enum StagedUpgradeEventKind<'a> {
+    CodeStaged(&'a [CodeStaged]),
+    CodeUnstaged(&'a [CodeUnstaged]),
+    UpgradeStarted(&'a [UpgradeStarted]),
+    StateMigrated(&'a [StateMigrated]),
+    UpgradeCompleted(&'a [UpgradeCompleted]),
+    UpgradeFailed(&'a [UpgradeFailed]),
+}
+
+fn new_staged_upgrade_v1(event_kind: StagedUpgradeEventKind) -> NearEvent {
+    NearEvent::StagedUpgrade(StagedUpgradeEvent { version: "1.0.0", event_kind })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils;
+
+    #[test]
+    fn code_staged() {
+        CodeStaged { code_hash: [0; 32].into(), deployable_at: 100 }.emit();
+        assert_eq!(
+            test_utils::get_logs()[0],
+            r#"EVENT_JSON:{"standard":"staged_upgrade","version":"1.0.0","event":"code_staged","data":[{"code_hash":"11111111111111111111111111111111","deployable_at":100}]}"#
+        );
+    }
+
+    #[test]
+    fn state_migrated() {
+        StateMigrated { from_version: 1, to_version: 2 }.emit();
+        assert_eq!(
+            test_utils::get_logs()[0],
+            r#"EVENT_JSON:{"standard":"staged_upgrade","version":"1.0.0","event":"state_migrated","data":[{"from_version":1,"to_version":2}]}"#
+        );
+    }
+
+    #[test]
+    fn upgrade_failed() {
+        UpgradeFailed { code_hash: [0; 32].into(), state_version: 1 }.emit();
+        assert_eq!(
+            test_utils::get_logs()[0],
+            r#"EVENT_JSON:{"standard":"staged_upgrade","version":"1.0.0","event":"upgrade_failed","data":[{"code_hash":"11111111111111111111111111111111","state_version":1}]}"#
+        );
+    }
+}
@@ -0,0 +1,143 @@
+//! Upgrades of the contract code through a staging area, with a versioned state migration.
+//!
+//! The flow is:
+//! 1. [`StagedUpgrade::stage_code`] stores the new WASM and its `sha256` hash. It can only be
+//!    deployed once the staging duration has passed, which gives users time to review it.
+//! 2. [`StagedUpgrade::deploy`] verifies the staged code against the hash approved by the
+//!    caller and deploys it in a single batch together with a call to the new code's
+//!    [`MIGRATE_METHOD`], which receives the current state version as `from_version`.
+//! 3. The new code's `migrate` converts the state and calls
+//!    [`StagedUpgrade::complete_migration`] to record the new state version.
+//! 4. [`StagedUpgrade::resolve_deploy`] runs as a callback and either clears the staging area or,
+//!    if the migration panicked, keeps it. Since the deploy and the migration are actions of the
+//!    same receipt, a failed migration rolls back the deploy as well, and the old code keeps
+//!    running on the old state.
+//!
+//! Both the old and the new code must expose [`StagedUpgradeResolver::up_resolve_deploy`], as the
+//! callback runs on whichever code is deployed after the batch.
+//!
+//! # Examples
+//! See [`StagedUpgradeCore`] for example usage.
+
+pub mod events;
+mod upgrade_impl;
+
+pub use upgrade_impl::*;
+
+use near_sdk::json_types::{Base58CryptoHash, Base64VecU8, U64};
+use near_sdk::{ext_contract, near, Promise};
+
+/// Code waiting in the staging area, as returned by [`StagedUpgradeCore::up_staged_code`].
+#[near(serializers=[json])]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StagedCode {
+    pub code_hash: Base58CryptoHash,
+    /// Nanoseconds timestamp after which the code can be deployed.
+    pub deployable_at: U64,
+}
+
+/// Methods to stage and deploy new code for the contract.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::{env, near, PanicOnDefault, Promise};
+/// use near_sdk::json_types::{Base58CryptoHash, Base64VecU8};
+/// use near_contract_standards::access_control::AccessControl;
+/// use near_contract_standards::staged_upgrade::{
+///     StagedCode, StagedUpgrade, StagedUpgradeCore, StagedUpgradeResolver,
+/// };
+///
+/// #[near(contract_state)]
+/// #[derive(PanicOnDefault)]
+/// pub struct Contract {
+///     acl: AccessControl,
+///     upgrade: StagedUpgrade,
+/// }
+///
+/// #[near]
+/// impl Contract {
+///     /// Called by the batch of [`StagedUpgrade::deploy`] right after the new code is deployed.
+///     #[private]
+///     #[init(ignore_state)]
+///     pub fn migrate(from_version: u32) -> Self {
+///         let mut contract: Self = env::state_read().expect("Contract is not initialized");
+///         // Convert the state from `from_version` here.
+///         contract.upgrade.complete_migration(from_version + 1);
+///         contract
+///     }
+/// }
+///
+/// #[near]
+/// impl StagedUpgradeCore for Contract {
+///     fn up_state_version(&self) -> u32 {
+///         self.upgrade.state_version
+///     }
+///
+///     fn up_staged_code(&self) -> Option<StagedCode> {
+///         self.upgrade.staged_code()
+///     }
+///
+///     fn up_stage_code(&mut self, code: Base64VecU8) {
+///         self.acl.assert_owner();
+///         self.upgrade.stage_code(code.into());
+///     }
+///
+///     fn up_unstage_code(&mut self) {
+///         self.acl.assert_owner();
+///         self.upgrade.unstage_code();
+///     }
+///
+///     fn up_deploy_code(&mut self, code_hash: Base58CryptoHash) -> Promise {
+///         self.acl.assert_owner();
+///         self.upgrade.deploy(code_hash)
+///     }
+/// }
+///
+/// #[near]
+/// impl StagedUpgradeResolver for Contract {
+///     #[private]
+///     fn up_resolve_deploy(&mut self, code_hash: Base58CryptoHash) -> bool {
+///         self.upgrade.resolve_deploy(code_hash)
+///     }
+/// }
+/// ```
+///
+#[ext_contract(ext_staged_upgrade)]
+pub trait StagedUpgradeCore {
+    /// Returns the version of the contract state.
+    fn up_state_version(&self) -> u32;
+
+    /// Returns the code waiting in the staging area, if any.
+    fn up_staged_code(&self) -> Option<StagedCode>;
+
+    /// Stages `code`, replacing any previously staged code.
+    ///
+    /// Requirements:
+    /// * Caller of the method must be allowed to upgrade the contract
+    fn up_stage_code(&mut self, code: Base64VecU8);
+
+    /// Removes the staged code.
+    ///
+    /// Requirements:
+    /// * Caller of the method must be allowed to upgrade the contract
+    fn up_unstage_code(&mut self);
+
+    /// Deploys the staged code and migrates the state.
+    ///
+    /// Requirements:
+    /// * Caller of the method must be allowed to upgrade the contract
+    /// * `code_hash` must match the `sha256` hash of the staged code
+    /// * The staging duration must have passed since the code was staged
+    fn up_deploy_code(&mut self, code_hash: Base58CryptoHash) -> Promise;
+}
+
+/// Callback which finalizes an upgrade.
+#[ext_contract(ext_staged_upgrade_resolver)]
+pub trait StagedUpgradeResolver {
+    /// Clears the staging area if the deploy and migration succeeded. Returns whether they did.
+    ///
+    /// Requirements:
+    /// * Must only be callable by the contract itself
+    fn up_resolve_deploy(&mut self, code_hash: Base58CryptoHash) -> bool;
+}
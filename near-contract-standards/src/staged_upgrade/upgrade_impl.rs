@@ -0,0 +1,260 @@
+use crate::staged_upgrade::events::{
+    CodeStaged, CodeUnstaged, StateMigrated, UpgradeCompleted, UpgradeFailed, UpgradeStarted,
+};
+use crate::staged_upgrade::StagedCode;
+use near_sdk::json_types::Base58CryptoHash;
+use near_sdk::serde_json::json;
+use near_sdk::{
+    env, near, require, CryptoHash, Duration, Gas, GasWeight, IntoStorageKey, NearToken, Promise,
+    PromiseResult, Timestamp,
+};
+
+/// Name of the method called on the new code right after it is deployed.
+pub const MIGRATE_METHOD: &str = "migrate";
+
+const GAS_FOR_RESOLVE_DEPLOY: Gas = Gas::from_tgas(10);
+
+/// Implementation of the staging area and of the state version.
+#[near]
+pub struct StagedUpgrade {
+    /// Version of the contract state, bumped by [`StagedUpgrade::complete_migration`].
+    pub state_version: u32,
+
+    /// Minimal time between staging code and deploying it.
+    pub staging_duration: Duration,
+
+    /// `sha256` hash of the staged code, if any.
+    pub staged_code_hash: Option<CryptoHash>,
+
+    /// Nanoseconds timestamp after which the staged code can be deployed.
+    pub deployable_at: Timestamp,
+
+    /// Key under which the staged code is written, to avoid deserializing it with the contract.
+    code_key: Vec<u8>,
+}
+
+impl StagedUpgrade {
+    pub fn new<S>(prefix: S, state_version: u32, staging_duration: Duration) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self {
+            state_version,
+            staging_duration,
+            staged_code_hash: None,
+            deployable_at: 0,
+            code_key: prefix.into_storage_key(),
+        }
+    }
+
+    /// Returns the code waiting in the staging area, if any.
+    pub fn staged_code(&self) -> Option<StagedCode> {
+        self.staged_code_hash.map(|code_hash| StagedCode {
+            code_hash: code_hash.into(),
+            deployable_at: self.deployable_at.into(),
+        })
+    }
+
+    /// Stages `code`, replacing any previously staged code. The caller must be checked
+    /// beforehand.
+    pub fn stage_code(&mut self, code: Vec<u8>) {
+        let code_hash = env::sha256_array(&code);
+        // Writes directly into storage to avoid serialization penalty by using default struct.
+        env::storage_write(&self.code_key, &code);
+        self.staged_code_hash = Some(code_hash);
+        self.deployable_at = env::block_timestamp() + self.staging_duration;
+        CodeStaged { code_hash: code_hash.into(), deployable_at: self.deployable_at }.emit();
+    }
+
+    /// Removes the staged code. The caller must be checked beforehand.
+    pub fn unstage_code(&mut self) {
+        let code_hash =
+            self.staged_code_hash.take().unwrap_or_else(|| env::panic_str("No code is staged"));
+        env::storage_remove(&self.code_key);
+        CodeUnstaged { code_hash: code_hash.into() }.emit();
+    }
+
+    /// Deploys the staged code and calls [`MIGRATE_METHOD`] on it with the current state version
+    /// as `from_version`, followed by a callback to `up_resolve_deploy`. The caller must be
+    /// checked beforehand.
+    ///
+    /// The staged code is kept until the callback confirms the upgrade, so that a failed
+    /// migration can be retried or the code unstaged.
+    pub fn deploy(&self, code_hash: Base58CryptoHash) -> Promise {
+        let staged_code_hash =
+            self.staged_code_hash.unwrap_or_else(|| env::panic_str("No code is staged"));
+        require!(
+            CryptoHash::from(code_hash) == staged_code_hash,
+            "Code hash doesn't match the staged code"
+        );
+        if env::block_timestamp() < self.deployable_at {
+            env::panic_str(&format!(
+                "Deploy code too early: staging ends on {}",
+                self.deployable_at
+            ));
+        }
+        let code = env::storage_read(&self.code_key)
+            .unwrap_or_else(|| env::panic_str("No upgrade code available"));
+        require!(env::sha256_array(&code) == staged_code_hash, "Staged code is corrupted");
+
+        UpgradeStarted { code_hash, from_version: self.state_version }.emit();
+        let current_account_id = env::current_account_id();
+        Promise::new(current_account_id.clone())
+            .deploy_contract(code)
+            .function_call_weight(
+                MIGRATE_METHOD.to_string(),
+                json!({ "from_version": self.state_version }).to_string().into_bytes(),
+                NearToken::from_near(0),
+                Gas::from_gas(0),
+                GasWeight::default(),
+            )
+            .then(Promise::new(current_account_id).function_call(
+                "up_resolve_deploy".to_string(),
+                json!({ "code_hash": code_hash }).to_string().into_bytes(),
+                NearToken::from_near(0),
+                GAS_FOR_RESOLVE_DEPLOY,
+            ))
+    }
+
+    /// Records that the state was migrated to `to_version`. Called by the new code's migrate
+    /// method.
+    pub fn complete_migration(&mut self, to_version: u32) {
+        require!(
+            to_version > self.state_version,
+            "State version must increase with every migration"
+        );
+        StateMigrated { from_version: self.state_version, to_version }.emit();
+        self.state_version = to_version;
+    }
+
+    /// Resolves the batch scheduled by [`StagedUpgrade::deploy`]. Clears the staging area and
+    /// returns `true` if the deploy and migration succeeded, and returns `false` otherwise, in
+    /// which case both were rolled back.
+    pub fn resolve_deploy(&mut self, code_hash: Base58CryptoHash) -> bool {
+        require!(env::promise_results_count() == 1, "Expected the result of the upgrade");
+        match env::promise_result(0) {
+            PromiseResult::Successful(_) => {
+                if self.staged_code_hash == Some(code_hash.into()) {
+                    self.staged_code_hash = None;
+                    env::storage_remove(&self.code_key);
+                }
+                UpgradeCompleted { code_hash, state_version: self.state_version }.emit();
+                true
+            }
+            PromiseResult::Failed => {
+                UpgradeFailed { code_hash, state_version: self.state_version }.emit();
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{get_logs, VMContextBuilder};
+    use near_sdk::{test_vm_config, testing_env, RuntimeFeesConfig};
+
+    const CODE: &[u8] = b"\0asm new code";
+    const STAGING_DURATION: Duration = 1_000;
+
+    fn setup() -> (VMContextBuilder, StagedUpgrade) {
+        let mut context = VMContextBuilder::new();
+        context.block_timestamp(10);
+        testing_env!(context.build());
+        (context, StagedUpgrade::new(b"u".to_vec(), 1, STAGING_DURATION))
+    }
+
+    fn code_hash() -> Base58CryptoHash {
+        env::sha256_array(CODE).into()
+    }
+
+    fn resolve_with(
+        context: &mut VMContextBuilder,
+        upgrade: &mut StagedUpgrade,
+        result: PromiseResult,
+    ) -> bool {
+        testing_env!(
+            context.build(),
+            test_vm_config(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![result],
+        );
+        upgrade.resolve_deploy(code_hash())
+    }
+
+    #[test]
+    fn stage_and_unstage() {
+        let (_, mut upgrade) = setup();
+        upgrade.stage_code(CODE.to_vec());
+        assert_eq!(
+            upgrade.staged_code(),
+            Some(StagedCode { code_hash: code_hash(), deployable_at: 1_010.into() })
+        );
+        assert_eq!(env::storage_read(b"u").as_deref(), Some(CODE));
+
+        upgrade.unstage_code();
+        assert_eq!(upgrade.staged_code(), None);
+        assert!(!env::storage_has_key(b"u"));
+        assert_eq!(get_logs().len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Deploy code too early: staging ends on 1010")]
+    fn deploy_too_early() {
+        let (_, mut upgrade) = setup();
+        upgrade.stage_code(CODE.to_vec());
+        upgrade.deploy(code_hash());
+    }
+
+    #[test]
+    #[should_panic(expected = "Code hash doesn't match the staged code")]
+    fn deploy_with_wrong_hash() {
+        let (mut context, mut upgrade) = setup();
+        upgrade.stage_code(CODE.to_vec());
+        testing_env!(context.block_timestamp(1_010).build());
+        upgrade.deploy([1; 32].into());
+    }
+
+    #[test]
+    fn successful_upgrade() {
+        let (mut context, mut upgrade) = setup();
+        upgrade.stage_code(CODE.to_vec());
+        testing_env!(context.block_timestamp(1_010).build());
+        let _ = upgrade.deploy(code_hash());
+        assert_eq!(
+            get_logs(),
+            vec![format!(
+                r#"EVENT_JSON:{{"standard":"staged_upgrade","version":"1.0.0","event":"upgrade_started","data":[{{"code_hash":"{}","from_version":1}}]}}"#,
+                String::from(&code_hash())
+            )]
+        );
+
+        upgrade.complete_migration(2);
+        assert!(resolve_with(&mut context, &mut upgrade, PromiseResult::Successful(vec![])));
+        assert_eq!(upgrade.state_version, 2);
+        assert_eq!(upgrade.staged_code(), None);
+        assert!(!env::storage_has_key(b"u"));
+    }
+
+    #[test]
+    fn failed_upgrade_keeps_staged_code() {
+        let (mut context, mut upgrade) = setup();
+        upgrade.stage_code(CODE.to_vec());
+        testing_env!(context.block_timestamp(1_010).build());
+        let _ = upgrade.deploy(code_hash());
+
+        assert!(!resolve_with(&mut context, &mut upgrade, PromiseResult::Failed));
+        assert_eq!(upgrade.state_version, 1);
+        assert_eq!(upgrade.staged_code().map(|staged| staged.code_hash), Some(code_hash()));
+        assert!(env::storage_has_key(b"u"));
+    }
+
+    #[test]
+    #[should_panic(expected = "State version must increase with every migration")]
+    fn migration_must_increase_version() {
+        let (_, mut upgrade) = setup();
+        upgrade.complete_migration(1);
+    }
+}
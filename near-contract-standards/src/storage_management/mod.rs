@@ -1,4 +1,5 @@
-use near_sdk::{ext_contract, near, AccountId, NearToken};
+use near_sdk::store::storage_delta;
+use near_sdk::{env, ext_contract, near, AccountId, NearToken, Promise};
 
 #[near(serializers=[borsh, json])]
 pub struct StorageBalance {
@@ -118,3 +119,128 @@ pub trait StorageManagement {
 
     fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance>;
 }
+
+/// Runs `f` and charges `payer` for the storage it used, out of the attached deposit.
+///
+/// The storage written and freed by the collections of [`near_sdk::store`] in `f` is accounted
+/// with [`storage_delta`], and the storage cost of the bytes added is taken from
+/// [`env::attached_deposit`]. The rest of the deposit is refunded to `payer`, along with the cost
+/// of the bytes released if `f` freed more storage than it wrote. This removes the need to measure
+/// the storage of an entry up front, as [`FungibleToken`](crate::fungible_token::FungibleToken)
+/// does for its accounts.
+///
+/// Values written directly with [`env::storage_write`] are not charged. The collections only
+/// write their changes when flushed, so they must be flushed in `f` for their storage to be
+/// charged. The attached deposit is used as is, so this must be called once per method.
+///
+/// # Panics
+///
+/// Panics if the attached deposit doesn't cover the storage added.
+///
+/// # Examples
+///
+/// ```
+/// use near_contract_standards::storage_management::charge_storage;
+/// use near_sdk::store::LookupMap;
+/// use near_sdk::{env, near, PanicOnDefault};
+///
+/// #[near(contract_state)]
+/// #[derive(PanicOnDefault)]
+/// pub struct Contract {
+///     messages: LookupMap<near_sdk::AccountId, String>,
+/// }
+///
+/// #[near]
+/// impl Contract {
+///     #[payable]
+///     pub fn set_message(&mut self, message: String) {
+///         let account_id = env::predecessor_account_id();
+///         charge_storage(&account_id, || {
+///             self.messages.insert(account_id.clone(), message);
+///             self.messages.flush();
+///         });
+///     }
+/// }
+/// ```
+pub fn charge_storage<R>(payer: &AccountId, f: impl FnOnce() -> R) -> R {
+    let (result, delta) = storage_delta(f);
+    let deposit = env::attached_deposit();
+    let bytes = delta.net().unsigned_abs() as u128;
+    let cost = env::storage_byte_cost().saturating_mul(bytes);
+    let refund = if delta.net() > 0 {
+        deposit.checked_sub(cost).unwrap_or_else(|| {
+            env::panic_str(&format!(
+                "The attached deposit of {} yoctoNEAR is less than the {} required to cover the storage of {} bytes",
+                deposit.as_yoctonear(),
+                cost.as_yoctonear(),
+                bytes
+            ))
+        })
+    } else {
+        deposit.saturating_add(cost)
+    };
+    if !refund.is_zero() {
+        Promise::new(payer.clone()).transfer(refund);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::mock::MockAction;
+    use near_sdk::store::LookupMap;
+    use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    // The prefix and the key, the value and the overhead of a record.
+    const ENTRY_BYTES: u128 = 2 + 4 + 40;
+
+    fn setup(deposit: NearToken) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(deposit)
+            .build());
+    }
+
+    fn transfers() -> Vec<NearToken> {
+        get_created_receipts()
+            .into_iter()
+            .flat_map(|receipt| receipt.actions)
+            .filter_map(|action| match action {
+                MockAction::Transfer { deposit, .. } => Some(deposit),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn charges_and_refunds_deposit() {
+        let cost = env::storage_byte_cost().saturating_mul(ENTRY_BYTES);
+        setup(NearToken::from_near(1));
+        let mut map = LookupMap::new(b"m");
+        charge_storage(&accounts(1), || {
+            map.insert(1u8, 2u32);
+            map.flush();
+        });
+        assert_eq!(transfers(), vec![NearToken::from_near(1).saturating_sub(cost)]);
+
+        setup(NearToken::from_near(0));
+        charge_storage(&accounts(1), || {
+            map.remove(&1);
+            map.flush();
+        });
+        assert_eq!(transfers(), vec![cost]);
+    }
+
+    #[test]
+    #[should_panic(expected = "required to cover the storage of 46 bytes")]
+    fn insufficient_deposit() {
+        setup(NearToken::from_yoctonear(1));
+        let mut map = LookupMap::new(b"m");
+        charge_storage(&accounts(1), || {
+            map.insert(1u8, 2u32);
+            map.flush();
+        });
+    }
+}
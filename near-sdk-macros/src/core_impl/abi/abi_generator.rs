@@ -1,9 +1,10 @@
 use proc_macro2::{Span, TokenStream as TokenStream2};
-use quote::{format_ident, quote};
+use quote::{format_ident, quote, ToTokens};
 use syn::{parse_quote, Attribute, Expr, Lit::Str, Meta::NameValue, MetaNameValue, Type};
 
 use crate::core_impl::{
-    utils, BindgenArgType, ImplItemMethodInfo, ItemImplInfo, MethodKind, ReturnKind, SerializerType,
+    utils, BindgenArgType, DepositRequirement, Guard, ImplItemMethodInfo, ItemImplInfo, LockGuard,
+    MethodGuards, MethodKind, ReturnKind, SerializerType,
 };
 
 pub fn generate(i: &ItemImplInfo) -> TokenStream2 {
@@ -84,6 +85,14 @@ impl ImplItemMethodInfo {
             Some(doc) => quote! { ::std::option::Option::Some(::std::string::String::from(#doc)) },
             None => quote! { ::std::option::Option::None },
         };
+        // The requirements are kept in the doc, as it's the only field of the function that
+        // tooling keeps when the ABI chunks are combined.
+        let function_doc = match self.abi_requirements() {
+            Some(requirements) => quote! {
+                ::std::option::Option::Some(#requirements.append_to_doc(#function_doc))
+            },
+            None => function_doc,
+        };
         let mut modifiers = vec![];
         let kind = match &attr_signature_info.method_kind {
             MethodKind::View(_) => quote! { ::near_sdk::__private::AbiFunctionKind::View },
@@ -198,6 +207,92 @@ impl ImplItemMethodInfo {
         }
     }
 
+    /// Generates the requirements of this function, if it has some.
+    ///
+    /// # Example:
+    /// The following function:
+    /// ```ignore
+    /// #[near(role = "minter")]
+    /// #[gas(min = "50 Tgas")]
+    /// pub fn mint(&mut self) { }
+    /// ```
+    /// will produce this struct, which is appended to the doc of the function:
+    /// ```ignore
+    /// near_sdk::__private::AbiFunctionRequirements {
+    ///     deposit: ::std::option::Option::None,
+    ///     min_gas: ::std::option::Option::Some(near_sdk::Gas::from_gas(50000000000000u64)),
+    ///     guards: ::std::vec![near_sdk::__private::AbiGuard::Role(
+    ///         ::std::string::String::from("minter"),
+    ///     )],
+    ///     locks: ::std::vec![],
+    /// }
+    /// ```
+    pub fn abi_requirements(&self) -> Option<TokenStream2> {
+        let guards = &self.attr_signature_info.guards;
+        if guards == &MethodGuards::default() {
+            return None;
+        }
+
+        let deposit = match &guards.deposit {
+            Some(DepositRequirement::Min(amount)) => {
+                let value = amount.value;
+                quote! {
+                    ::std::option::Option::Some(::near_sdk::__private::AbiDepositRequirement::Min(
+                        ::near_sdk::NearToken::from_yoctonear(#value),
+                    ))
+                }
+            }
+            Some(DepositRequirement::Exact(amount)) => {
+                let value = amount.value;
+                quote! {
+                    ::std::option::Option::Some(::near_sdk::__private::AbiDepositRequirement::Exact(
+                        ::near_sdk::NearToken::from_yoctonear(#value),
+                    ))
+                }
+            }
+            None => quote! { ::std::option::Option::None },
+        };
+        let min_gas = match &guards.min_gas {
+            // The parser checks that the minimum gas fits in a `u64`.
+            Some(amount) => {
+                let value = amount.value as u64;
+                quote! { ::std::option::Option::Some(::near_sdk::Gas::from_gas(#value)) }
+            }
+            None => quote! { ::std::option::Option::None },
+        };
+        let guard_tokens = guards.guards.iter().map(|guard| match guard {
+            Guard::Function(path) => {
+                let path = path.to_token_stream().to_string().replace(' ', "");
+                quote! {
+                    ::near_sdk::__private::AbiGuard::Function(::std::string::String::from(#path))
+                }
+            }
+            Guard::Owner => quote! { ::near_sdk::__private::AbiGuard::Owner },
+            Guard::Role(role) => quote! {
+                ::near_sdk::__private::AbiGuard::Role(::std::string::String::from(#role))
+            },
+        });
+        let lock_tokens = guards.locks.iter().map(|lock| {
+            let variant = match lock {
+                LockGuard::Lock(_) => quote! { Lock },
+                LockGuard::RejectLocked(_) => quote! { RejectLocked },
+            };
+            let arg = lock.arg().to_string();
+            quote! {
+                ::near_sdk::__private::AbiLockOperation::#variant(::std::string::String::from(#arg))
+            }
+        });
+
+        Some(quote! {
+            ::near_sdk::__private::AbiFunctionRequirements {
+                deposit: #deposit,
+                min_gas: #min_gas,
+                guards: ::std::vec![#(#guard_tokens),*],
+                locks: ::std::vec![#(#lock_tokens),*],
+            }
+        })
+    }
+
     fn abi_result_tokens(&self) -> TokenStream2 {
         use ReturnKind::*;
 
@@ -345,6 +440,49 @@ mod tests {
         local_insta_assert_snapshot!(pretty_print_fn_body_syn_str(actual));
     }
     
+    #[test]
+    fn test_generate_abi_guards() {
+        let impl_type: Type = syn::parse_str("Test").unwrap();
+        let mut method = parse_quote! {
+            /// I am a function.
+            #[near(role = "minter")]
+            #[deposit(min = "0.01 NEAR")]
+            #[gas(min = "50 Tgas")]
+            pub fn mint(&mut self, amount: u64) { }
+        };
+        let method_info = ImplItemMethodInfo::new(&mut method, None, impl_type).unwrap().unwrap();
+        let actual = method_info.abi_struct();
+
+        local_insta_assert_snapshot!(pretty_print_fn_body_syn_str(actual));
+    }
+
+    #[test]
+    fn test_generate_abi_requirements() {
+        let impl_type: Type = syn::parse_str("Test").unwrap();
+        let mut method = parse_quote! {
+            #[near(owner_only, guard = "Self::assert_not_paused")]
+            #[deposit(exact = "1 yoctoNEAR")]
+            #[gas(min = "50 Tgas")]
+            pub fn withdraw(&mut self, amount: u64) { }
+        };
+        let method_info = ImplItemMethodInfo::new(&mut method, None, impl_type).unwrap().unwrap();
+        let actual = method_info.abi_requirements().unwrap();
+
+        local_insta_assert_snapshot!(pretty_print_fn_body_syn_str(actual));
+    }
+
+    #[test]
+    fn test_generate_abi_no_requirements() {
+        let impl_type: Type = syn::parse_str("Test").unwrap();
+        let mut method = parse_quote! {
+            #[payable]
+            pub fn deposit(&mut self) { }
+        };
+        let method_info = ImplItemMethodInfo::new(&mut method, None, impl_type).unwrap().unwrap();
+
+        assert!(method_info.abi_requirements().is_none());
+    }
+
     #[test]
     fn test_generate_abi_private_callback_vec() {
         let impl_type: Type = syn::parse_str("Test").unwrap();
@@ -243,8 +243,8 @@ impl AttrSigInfo {
                             return syn::Error::new_spanned(ty, "Function parameters marked with \
                                 #[callback_result] should have type Result<T, PromiseError>").into_compile_error()
                         };
-                        let deserialize = deserialize_data(serializer_ty);
-                        let deserialization_branch = match ok_type {
+                        let deserialize = deserialize_result_data(serializer_ty);
+                        let deserialization = match ok_type {
                             // The unit type in this context is a bit special because functions
                             // without an explicit return type do not serialize their response.
                             // But when someone tries to refer to their callback result with
@@ -257,19 +257,23 @@ impl AttrSigInfo {
                             // deserialization otherwise.
                             syn::Type::Tuple(type_tuple) if type_tuple.elems.is_empty() =>
                                 quote! {
-                                    ::near_sdk::PromiseResult::Successful(data) if data.is_empty() =>
+                                    ::std::result::Result::Ok(data) if data.is_empty() =>
                                         ::std::result::Result::Ok(()),
-                                    ::near_sdk::PromiseResult::Successful(data) => ::std::result::Result::Ok(#deserialize)
+                                    ::std::result::Result::Ok(data) => #deserialize
                                 },
                             _ =>
                                 quote! {
-                                    ::near_sdk::PromiseResult::Successful(data) => ::std::result::Result::Ok(#deserialize)
+                                    ::std::result::Result::Ok(data) => #deserialize
                                 }
                         };
+                        let max_len = match &arg.callback_max_len {
+                            Some(max_len) => quote! { #max_len },
+                            None => quote! { ::std::primitive::usize::MAX },
+                        };
                         let result = quote! {
-                            match ::near_sdk::env::promise_result(#idx) {
-                                #deserialization_branch,
-                                ::near_sdk::PromiseResult::Failed => ::std::result::Result::Err(::near_sdk::PromiseError::Failed),
+                            match ::near_sdk::env::promise_result_checked(#idx, #max_len) {
+                                #deserialization,
+                                ::std::result::Result::Err(err) => ::std::result::Result::Err(err),
                             }
                         };
                         quote! {
@@ -307,6 +311,22 @@ impl AttrSigInfo {
     }
 }
 
+/// Deserializes `data` into a `Result` which holds the deserialization error if it fails.
+fn deserialize_result_data(ty: &SerializerType) -> TokenStream2 {
+    match ty {
+        SerializerType::JSON => quote! {
+            ::near_sdk::serde_json::from_slice(&data).map_err(|err| {
+                ::near_sdk::PromiseError::DeserializationFailed(::std::string::ToString::to_string(&err))
+            })
+        },
+        SerializerType::Borsh => quote! {
+            ::near_sdk::borsh::BorshDeserialize::try_from_slice(&data).map_err(|err| {
+                ::near_sdk::PromiseError::DeserializationFailed(::std::string::ToString::to_string(&err))
+            })
+        },
+    }
+}
+
 fn deserialize_data(ty: &SerializerType) -> TokenStream2 {
     match ty {
         SerializerType::JSON => quote! {
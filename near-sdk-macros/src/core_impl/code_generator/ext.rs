@@ -1,6 +1,7 @@
 use crate::core_impl::{serializer, AttrSigInfo};
 use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
 use quote::{format_ident, quote, ToTokens};
+use syn::ext::IdentExt;
 use syn::{parse_quote, Attribute, Generics, Path, Signature};
 
 /// Generates inner ext code for structs and modules. If intended for a struct, generic details
@@ -117,7 +118,7 @@ fn generate_ext_function(attr_signature_info: &AttrSigInfo) -> TokenStream2 {
         serializer::generate_serializer(attr_signature_info, &attr_signature_info.input_serializer);
 
     let AttrSigInfo { non_bindgen_attrs, ident, original_sig, .. } = attr_signature_info;
-    let ident_str = ident.to_string();
+    let ident_str = ident.unraw().to_string();
     let mut new_non_bindgen_attrs = TokenStream2::new();
     for attribute in non_bindgen_attrs.iter() {
         if is_fn_attribute_to_forward(attribute) {
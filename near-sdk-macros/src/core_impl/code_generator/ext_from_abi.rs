@@ -0,0 +1,345 @@
+use crate::core_impl::info_extractor::ItemTraitInfo;
+use inflector::Inflector;
+use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use serde_json::{Map, Value};
+use syn::{Error, ItemTrait};
+
+/// Types of the definitions of the JSON schemas which have a counterpart in `near_sdk`.
+const KNOWN_DEFINITIONS: [(&str, &str); 3] = [
+    ("AccountId", "::near_sdk::AccountId"),
+    ("NearToken", "::near_sdk::NearToken"),
+    ("NearGas", "::near_sdk::Gas"),
+];
+
+/// Generates the `ext` module of a contract from its NEAR ABI, as `#[ext_contract]` does from a
+/// trait describing the contract.
+///
+/// Every function of the ABI becomes a method of the `Ext` struct, which arguments are
+/// serialized as specified by the ABI. The types of the arguments are derived from their
+/// schemas, and JSON arguments which schema has no Rust counterpart, such as objects, are
+/// passed as `serde_json::Value`.
+pub(crate) fn generate_ext_from_abi(mod_name: &Ident, abi: &Value) -> syn::Result<TokenStream2> {
+    let error = |message: String| Error::new(mod_name.span(), message);
+    let body = abi.get("body").ok_or_else(|| error("The ABI has no `body`".to_string()))?;
+    let functions = body
+        .get("functions")
+        .and_then(Value::as_array)
+        .ok_or_else(|| error("The ABI has no `body.functions`".to_string()))?;
+    let empty = Map::new();
+    let definitions = body
+        .get("root_schema")
+        .and_then(|root_schema| root_schema.get("definitions"))
+        .and_then(Value::as_object)
+        .unwrap_or(&empty);
+
+    let methods = functions
+        .iter()
+        .map(|function| function_signature(function, definitions))
+        .collect::<Result<Vec<_>, String>>()
+        .map_err(error)?;
+    let trait_ident = Ident::new(&mod_name.to_string().to_pascal_case(), mod_name.span());
+    let mut item_trait: ItemTrait = syn::parse2(quote! {
+        pub trait #trait_ident {
+            #(#methods)*
+        }
+    })?;
+    let item_trait_info = ItemTraitInfo::new(&mut item_trait, Some(mod_name.clone()))?;
+    Ok(item_trait_info.wrap_trait_ext())
+}
+
+/// Returns the signature of a trait method describing `function`.
+fn function_signature(
+    function: &Value,
+    definitions: &Map<String, Value>,
+) -> Result<TokenStream2, String> {
+    let name = function
+        .get("name")
+        .and_then(Value::as_str)
+        .ok_or_else(|| "A function of the ABI has no `name`".to_string())?;
+    let ident = to_ident(name).map_err(|err| format!("Invalid name of function: {}", err))?;
+    let Some(params) = function.get("params") else {
+        return Ok(quote! { fn #ident(&self); });
+    };
+    let borsh = match params.get("serialization_type").and_then(Value::as_str) {
+        Some("json") => false,
+        Some("borsh") => true,
+        other => {
+            return Err(format!("Unsupported serialization type {:?} of function {}", other, name))
+        }
+    };
+    let args = params.get("args").and_then(Value::as_array).map(Vec::as_slice).unwrap_or_default();
+    let args = args
+        .iter()
+        .map(|arg| {
+            let arg_name = arg
+                .get("name")
+                .and_then(Value::as_str)
+                .ok_or_else(|| format!("An argument of function {} has no `name`", name))?;
+            let arg_ident = to_ident(arg_name).map_err(|err| {
+                format!("Invalid name of an argument of function {}: {}", name, err)
+            })?;
+            let schema = arg.get("type_schema").unwrap_or(&Value::Null);
+            if borsh {
+                let declaration = schema.get("declaration").and_then(Value::as_str).unwrap_or("");
+                let ty = borsh_type(declaration).ok_or_else(|| {
+                    format!(
+                        "Borsh type `{}` of argument {} of function {} can't be mapped to a Rust type",
+                        declaration, arg_name, name
+                    )
+                })?;
+                Ok(quote! { #[serializer(borsh)] #arg_ident: #ty })
+            } else {
+                let ty = json_type(schema, definitions, 0);
+                Ok(quote! { #arg_ident: #ty })
+            }
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    Ok(quote! { fn #ident(&self, #(#args),*); })
+}
+
+/// Returns the identifier of a function or argument named `name` in the ABI, which is a raw
+/// identifier for the keywords which can be one.
+fn to_ident(name: &str) -> Result<Ident, String> {
+    let mut chars = name.chars();
+    let is_identifier = chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !is_identifier || matches!(name, "_" | "self" | "Self" | "super" | "crate") {
+        return Err(format!("`{}` is not a valid Rust identifier", name));
+    }
+    Ok(syn::parse_str(name).unwrap_or_else(|_| Ident::new_raw(name, Span::call_site())))
+}
+
+/// Returns the Rust type of a JSON schema, following the references to `definitions` up to a
+/// few times in case they are recursive.
+fn json_type(schema: &Value, definitions: &Map<String, Value>, depth: usize) -> TokenStream2 {
+    const MAX_DEPTH: usize = 8;
+    let value = quote! { ::near_sdk::serde_json::Value };
+    if depth > MAX_DEPTH {
+        return value;
+    }
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        let name = reference.trim_start_matches("#/definitions/");
+        if let Some((_, path)) = KNOWN_DEFINITIONS.iter().find(|(known, _)| *known == name) {
+            return path.parse().unwrap();
+        }
+        return match definitions.get(name) {
+            Some(definition) => json_type(definition, definitions, depth + 1),
+            None => value,
+        };
+    }
+    // `Option<T>` is described as either `T` or `null`.
+    let is_null = |schema: &Value| schema.get("type").and_then(Value::as_str) == Some("null");
+    for key in ["anyOf", "oneOf"] {
+        if let Some([first, second]) = schema.get(key).and_then(Value::as_array).map(Vec::as_slice)
+        {
+            let variant = match (is_null(first), is_null(second)) {
+                (false, true) => first,
+                (true, false) => second,
+                _ => continue,
+            };
+            let ty = json_type(variant, definitions, depth + 1);
+            return quote! { ::std::option::Option<#ty> };
+        }
+    }
+    if let Some(all_of) = schema.get("allOf").and_then(Value::as_array) {
+        if let [schema] = all_of.as_slice() {
+            return json_type(schema, definitions, depth + 1);
+        }
+    }
+    let (ty, nullable) = match schema.get("type") {
+        Some(Value::String(ty)) => (ty.as_str(), false),
+        Some(Value::Array(types)) => match types.as_slice() {
+            [Value::String(ty), Value::String(null)] | [Value::String(null), Value::String(ty)]
+                if null == "null" =>
+            {
+                (ty.as_str(), true)
+            }
+            _ => return value,
+        },
+        _ => return value,
+    };
+    let ty = match ty {
+        "string" => quote! { ::std::string::String },
+        "boolean" => quote! { bool },
+        "integer" => {
+            let format = schema.get("format").and_then(Value::as_str).unwrap_or("int64");
+            match integer_type(format) {
+                Some(ty) => ty,
+                None => return value,
+            }
+        }
+        "number" => match schema.get("format").and_then(Value::as_str) {
+            Some("float") => quote! { f32 },
+            _ => quote! { f64 },
+        },
+        "array" => match schema.get("items") {
+            Some(items) if items.is_object() => {
+                let ty = json_type(items, definitions, depth + 1);
+                quote! { ::std::vec::Vec<#ty> }
+            }
+            _ => return value,
+        },
+        "null" => quote! { () },
+        _ => return value,
+    };
+    if nullable {
+        quote! { ::std::option::Option<#ty> }
+    } else {
+        ty
+    }
+}
+
+/// Returns the Rust integer type of a JSON schema integer format.
+fn integer_type(format: &str) -> Option<TokenStream2> {
+    let ty = match format {
+        "uint" => "usize".to_string(),
+        "int" => "isize".to_string(),
+        _ => match format.strip_prefix("uint") {
+            Some(bits) => format!("u{}", bits),
+            None => format!("i{}", format.strip_prefix("int")?),
+        },
+    };
+    primitive_type(&ty)
+}
+
+/// Returns the Rust type of a Borsh schema declaration, for the primitive types along with the
+/// vectors, options and arrays of them.
+fn borsh_type(declaration: &str) -> Option<TokenStream2> {
+    if let Some(inner) = declaration.strip_prefix("Vec<").and_then(|d| d.strip_suffix('>')) {
+        let ty = borsh_type(inner)?;
+        return Some(quote! { ::std::vec::Vec<#ty> });
+    }
+    if let Some(inner) = declaration.strip_prefix("Option<").and_then(|d| d.strip_suffix('>')) {
+        let ty = borsh_type(inner)?;
+        return Some(quote! { ::std::option::Option<#ty> });
+    }
+    if let Some(inner) = declaration.strip_prefix('[').and_then(|d| d.strip_suffix(']')) {
+        let (ty, len) = inner.rsplit_once(';')?;
+        let ty = borsh_type(ty.trim())?;
+        let len = proc_macro2::Literal::usize_unsuffixed(len.trim().parse().ok()?);
+        return Some(quote! { [#ty; #len] });
+    }
+    match declaration {
+        "String" | "string" => Some(quote! { ::std::string::String }),
+        "()" => Some(quote! { () }),
+        _ => primitive_type(declaration),
+    }
+}
+
+fn primitive_type(name: &str) -> Option<TokenStream2> {
+    match name {
+        "bool" | "u8" | "u16" | "u32" | "u64" | "u128" | "usize" | "i8" | "i16" | "i32" | "i64"
+        | "i128" | "isize" | "f32" | "f64" => {
+            let ident = format_ident!("{}", name);
+            Some(quote! { #ident })
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core_impl::utils::test_helpers::{
+        local_insta_assert_snapshot, pretty_print_syn_str,
+    };
+    use syn::parse_quote;
+
+    #[test]
+    fn ext_from_abi() {
+        let abi = serde_json::json!({
+            "schema_version": "0.4.0",
+            "body": {
+                "functions": [
+                    {
+                        "name": "get",
+                        "kind": "view",
+                        "params": {
+                            "serialization_type": "json",
+                            "args": [
+                                { "name": "account_id", "type_schema": { "$ref": "#/definitions/AccountId" } },
+                                { "name": "keys", "type_schema": { "type": "array", "items": { "type": "string" } } },
+                                { "name": "limit", "type_schema": { "type": ["integer", "null"], "format": "uint32" } },
+                                { "name": "filter", "type_schema": { "$ref": "#/definitions/Filter" } }
+                            ]
+                        }
+                    },
+                    {
+                        "name": "set",
+                        "kind": "call",
+                        "params": {
+                            "serialization_type": "borsh",
+                            "args": [
+                                { "name": "key", "type_schema": { "declaration": "[u8; 32]" } },
+                                { "name": "value", "type_schema": { "declaration": "Option<u128>" } }
+                            ]
+                        }
+                    },
+                    { "name": "clear", "kind": "call" }
+                ],
+                "root_schema": {
+                    "definitions": {
+                        "AccountId": { "type": "string" },
+                        "Filter": { "type": "object" }
+                    }
+                }
+            }
+        });
+        let mod_name: Ident = parse_quote! { ext_store };
+        let actual = generate_ext_from_abi(&mod_name, &abi).unwrap();
+
+        local_insta_assert_snapshot!(pretty_print_syn_str(&actual).unwrap());
+    }
+
+    #[test]
+    fn ext_from_abi_invalid_names() {
+        let mod_name: Ident = parse_quote! { ext_store };
+        let abi = |function: &str, arg: &str| {
+            serde_json::json!({
+                "body": {
+                    "functions": [{
+                        "name": function,
+                        "params": { "serialization_type": "json", "args": [{ "name": arg }] }
+                    }]
+                }
+            })
+        };
+        let error = |function, arg| {
+            generate_ext_from_abi(&mod_name, &abi(function, arg)).unwrap_err().to_string()
+        };
+
+        assert!(generate_ext_from_abi(&mod_name, &abi("type", "fn")).is_ok());
+        for name in ["self", "Self", "super", "crate", "_", "", "get-value", "1st"] {
+            assert_eq!(
+                error(name, "key"),
+                format!("Invalid name of function: `{}` is not a valid Rust identifier", name)
+            );
+        }
+        assert_eq!(
+            error("get", "self"),
+            "Invalid name of an argument of function get: `self` is not a valid Rust identifier"
+        );
+    }
+
+    #[test]
+    fn ext_from_abi_unsupported_borsh_type() {
+        let abi = serde_json::json!({
+            "body": {
+                "functions": [{
+                    "name": "set",
+                    "params": {
+                        "serialization_type": "borsh",
+                        "args": [{ "name": "value", "type_schema": { "declaration": "Config" } }]
+                    }
+                }]
+            }
+        });
+        let mod_name: Ident = parse_quote! { ext_store };
+        let err = generate_ext_from_abi(&mod_name, &abi).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Borsh type `Config` of argument value of function set can't be mapped to a Rust type"
+        );
+    }
+}
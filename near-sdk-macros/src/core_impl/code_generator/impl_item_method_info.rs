@@ -1,7 +1,9 @@
-use crate::core_impl::info_extractor::{ImplItemMethodInfo, SerializerType};
+use crate::core_impl::info_extractor::{
+    DepositRequirement, Guard, ImplItemMethodInfo, LockGuard, SerializerType,
+};
 use crate::core_impl::{MethodKind, ReturnKind};
 use proc_macro2::TokenStream as TokenStream2;
-use quote::quote;
+use quote::{format_ident, quote};
 use syn::Receiver;
 
 impl ImplItemMethodInfo {
@@ -12,7 +14,72 @@ impl ImplItemMethodInfo {
         let ident = &self.attr_signature_info.ident;
 
         let panic_hook = self.panic_hook_tokens();
+        let wrapper_body = self.wrapper_body_tokens();
+        let release_lock_wrapper = self.release_lock_wrapper();
 
+        quote! {
+            #non_bindgen_attrs
+            #[cfg(target_arch = "wasm32")]
+            #[no_mangle]
+            pub extern "C" fn #ident() {
+                #panic_hook
+                #wrapper_body
+            }
+            #release_lock_wrapper
+        }
+    }
+
+    /// Generate the private method releasing the locks acquired with `#[near(lock = "...")]`,
+    /// which is called once the promise chain returned by the method is executed.
+    fn release_lock_wrapper(&self) -> TokenStream2 {
+        if !self.acquires_locks() {
+            return quote! {};
+        }
+        let non_bindgen_attrs = self.non_bindgen_attrs_tokens();
+        let ident = self.release_lock_method();
+        let panic_hook = self.panic_hook_tokens();
+
+        quote! {
+            #non_bindgen_attrs
+            #[cfg(target_arch = "wasm32")]
+            #[no_mangle]
+            pub extern "C" fn #ident() {
+                #panic_hook
+                ::near_sdk::__private::release_lock();
+            }
+        }
+    }
+
+    fn acquires_locks(&self) -> bool {
+        let locks = &self.attr_signature_info.guards.locks;
+        locks.iter().any(|lock| matches!(lock, LockGuard::Lock(_)))
+    }
+
+    /// Name of the method releasing the locks, starting with `RELEASE_LOCK_METHOD_PREFIX`.
+    fn release_lock_method(&self) -> syn::Ident {
+        format_ident!("__near_release_lock_{}", self.attr_signature_info.ident)
+    }
+
+    /// Generate the native counterpart of [`Self::method_wrapper`], which is an associated
+    /// function of the contract used to execute the method in-process from unit tests. It does
+    /// not set up the panic hook, so that panics unwind to the caller.
+    pub fn native_method_wrapper(&self) -> TokenStream2 {
+        let non_bindgen_attrs = self.non_bindgen_attrs_tokens();
+
+        let ident = format_ident!("__near_native_{}", self.attr_signature_info.ident);
+
+        let wrapper_body = self.wrapper_body_tokens();
+
+        quote! {
+            #non_bindgen_attrs
+            #[doc(hidden)]
+            pub fn #ident() {
+                #wrapper_body
+            }
+        }
+    }
+
+    fn wrapper_body_tokens(&self) -> TokenStream2 {
         let arg_struct = self.arg_struct_tokens();
         let arg_parsing = self.arg_parsing_tokens();
 
@@ -21,6 +88,9 @@ impl ImplItemMethodInfo {
 
         let deposit_check = self.deposit_check_tokens();
         let is_private_check = self.private_check_tokens();
+        let gas_check = self.gas_check_tokens();
+        let guard_check = self.guard_check_tokens();
+        let lock_check = self.lock_check_tokens();
         let state_check = self.state_check_tokens();
 
         let body = match self.attr_signature_info.returns.kind {
@@ -32,20 +102,17 @@ impl ImplItemMethodInfo {
         };
 
         quote! {
-            #non_bindgen_attrs
-            #[cfg(target_arch = "wasm32")]
-            #[no_mangle]
-            pub extern "C" fn #ident() {
-                #panic_hook
-                #is_private_check
-                #deposit_check
-                #arg_struct
-                #arg_parsing
-                #callback_deser
-                #callback_vec_deser
-                #state_check
-                #body
-            }
+            #is_private_check
+            #deposit_check
+            #gas_check
+            #guard_check
+            #arg_struct
+            #arg_parsing
+            #callback_deser
+            #callback_vec_deser
+            #lock_check
+            #state_check
+            #body
         }
     }
 
@@ -64,6 +131,7 @@ impl ImplItemMethodInfo {
     fn value_return_body_tokens(&self) -> TokenStream2 {
         let contract_init = self.contract_init_tokens();
         let method_invocation_with_return = self.method_invocation_with_return_tokens();
+        let lock_release = self.lock_release_tokens();
         let contract_ser = self.contract_ser_tokens();
         let value_ser = self.value_ser_tokens();
         let value_return = self.value_return_tokens();
@@ -71,6 +139,7 @@ impl ImplItemMethodInfo {
         quote! {
             #contract_init
             #method_invocation_with_return
+            #lock_release
             #value_ser
             #value_return
             #contract_ser
@@ -147,6 +216,31 @@ impl ImplItemMethodInfo {
     fn deposit_check_tokens(&self) -> TokenStream2 {
         use MethodKind::*;
 
+        let ident = &self.attr_signature_info.ident;
+        match &self.attr_signature_info.guards.deposit {
+            Some(DepositRequirement::Min(amount)) => {
+                let value = amount.value;
+                let error =
+                    format!("Method {} requires a deposit of at least {}", ident, amount.text);
+                return quote! {
+                    if ::near_sdk::env::attached_deposit().as_yoctonear() < #value {
+                        ::near_sdk::env::panic_str(#error);
+                    }
+                };
+            }
+            Some(DepositRequirement::Exact(amount)) => {
+                let value = amount.value;
+                let error =
+                    format!("Method {} requires a deposit of exactly {}", ident, amount.text);
+                return quote! {
+                    if ::near_sdk::env::attached_deposit().as_yoctonear() != #value {
+                        ::near_sdk::env::panic_str(#error);
+                    }
+                };
+            }
+            None => {}
+        }
+
         let reject_deposit_code = || {
             // If method is not payable, do a check to make sure that it doesn't consume deposit
             let error = format!("Method {} doesn't accept deposit", self.attr_signature_info.ident);
@@ -178,6 +272,105 @@ impl ImplItemMethodInfo {
         }
     }
 
+    fn gas_check_tokens(&self) -> TokenStream2 {
+        match &self.attr_signature_info.guards.min_gas {
+            Some(amount) => {
+                let value = amount.value as u64;
+                let error = format!(
+                    "Method {} requires at least {} of prepaid gas",
+                    self.attr_signature_info.ident, amount.text
+                );
+                quote! {
+                    if ::near_sdk::env::prepaid_gas().as_gas() < #value {
+                        ::near_sdk::env::panic_str(#error);
+                    }
+                }
+            }
+            None => quote! {},
+        }
+    }
+
+    /// Calls the guards of the method. When the method has a receiver, the contract is read
+    /// from the state beforehand and passed to the guards by reference.
+    fn guard_check_tokens(&self) -> TokenStream2 {
+        let guards = &self.attr_signature_info.guards.guards;
+        if guards.is_empty() {
+            return quote! {};
+        }
+        let struct_type = &self.struct_type;
+        let receiver = self.receiver();
+        let contract_arg = receiver.map(|_| quote! { &contract });
+        let calls = guards.iter().map(|guard| match guard {
+            Guard::Function(path) => {
+                // The wrapper is outside of the `impl` block, so `Self` has to be replaced.
+                let path = match path.segments.first() {
+                    Some(first) if path.segments.len() > 1 && first.ident == "Self" => {
+                        let rest = path.segments.iter().skip(1);
+                        quote! { #struct_type::#(#rest)::* }
+                    }
+                    _ => quote! { #path },
+                };
+                quote! { #path(#contract_arg); }
+            }
+            Guard::Owner => quote! { #struct_type::assert_owner(&contract); },
+            Guard::Role(role) => quote! { #struct_type::assert_role(&contract, #role); },
+        });
+        let contract_deser = receiver.map(|receiver| {
+            let mutability = receiver.mutability;
+            quote! {
+                let #mutability contract: #struct_type = ::near_sdk::env::state_read().unwrap_or_default();
+            }
+        });
+        quote! {
+            #contract_deser
+            #(#calls)*
+        }
+    }
+
+    /// Operations on the locks of the keys held by the arguments, once they are deserialized.
+    /// The keys which are locked are kept serialized, as the arguments are moved into the method.
+    fn lock_check_tokens(&self) -> TokenStream2 {
+        let locks = self.attr_signature_info.guards.locks.iter().map(|lock| match lock {
+            LockGuard::Lock(arg) => {
+                let key = format_ident!("__near_lock_{}", arg);
+                quote! { let #key = ::near_sdk::__private::acquire_lock(&#arg); }
+            }
+            LockGuard::RejectLocked(arg) => {
+                quote! { ::near_sdk::CallbackLock::require_unlocked(&#arg); }
+            }
+        });
+        quote! { #(#locks)* }
+    }
+
+    /// Schedules the release of the locks acquired by the method once the promise chain it
+    /// returns is executed, whether its callback succeeds or not.
+    fn lock_release_tokens(&self) -> TokenStream2 {
+        let release_method = self.release_lock_method().to_string();
+        let releases = self.attr_signature_info.guards.locks.iter().filter_map(|lock| match lock {
+            LockGuard::Lock(arg) => {
+                let key = format_ident!("__near_lock_{}", arg);
+                Some(quote! {
+                    ::near_sdk::__private::ReleaseLockAfter::release_lock_after(
+                        &result,
+                        #key,
+                        #release_method,
+                    );
+                })
+            }
+            LockGuard::RejectLocked(_) => None,
+        });
+        quote! { #(#releases)* }
+    }
+
+    /// The receiver of Call and View methods.
+    fn receiver(&self) -> Option<&Receiver> {
+        match &self.attr_signature_info.method_kind {
+            MethodKind::Call(call_method) => call_method.receiver.as_ref(),
+            MethodKind::View(view_method) => view_method.receiver.as_ref(),
+            MethodKind::Init(_) => None,
+        }
+    }
+
     fn private_check_tokens(&self) -> TokenStream2 {
         if self.attr_signature_info.is_private() {
             let error = format!("Method {} is private", self.attr_signature_info.ident);
@@ -223,6 +416,10 @@ impl ImplItemMethodInfo {
         let arg_list = self.attr_signature_info.arg_list();
 
         let contract_deser = |receiver: &Receiver| {
+            // The guards have already read the contract from the state.
+            if !self.attr_signature_info.guards.guards.is_empty() {
+                return quote! {};
+            }
             let mutability = receiver.mutability;
 
             quote! {
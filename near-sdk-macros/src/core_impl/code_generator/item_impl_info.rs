@@ -1,15 +1,30 @@
 use crate::core_impl::ext::generate_ext_function_wrappers;
 use crate::ItemImplInfo;
 use proc_macro2::TokenStream as TokenStream2;
-use quote::ToTokens;
+use quote::{quote, ToTokens};
 use syn::{spanned::Spanned, Ident};
 
 impl ItemImplInfo {
     /// Generate the code that wraps
-    pub fn wrapper_code(&self) -> TokenStream2 {
+    pub fn wrapper_code(&self, native_dispatch: bool) -> TokenStream2 {
         let mut res = TokenStream2::new();
+        let mut native_wrappers = TokenStream2::new();
         for method in &self.methods {
             res.extend(method.method_wrapper());
+            if native_dispatch {
+                native_wrappers.extend(method.native_method_wrapper());
+            }
+        }
+        // Only emitted for the impl blocks which opt in with `#[near(native_dispatch)]`, as the
+        // wrappers require bounds the contract may not satisfy natively, e.g. a `Default` state.
+        if native_dispatch {
+            let ty = &self.ty;
+            res.extend(quote! {
+                #[cfg(not(target_arch = "wasm32"))]
+                impl #ty {
+                    #native_wrappers
+                }
+            });
         }
         res
     }
@@ -170,6 +185,17 @@ mod tests {
         local_insta_assert_snapshot!(pretty_print_syn_str(&actual).unwrap());
     }
 
+    #[test]
+    fn callback_result_max_len() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemFn = parse_quote! {
+            #[private] pub fn method(&self, #[callback_result(max_len = 64)] #[serializer(borsh)] x: Result<(), PromiseError>) { }
+        };
+        let method_info = ImplItemMethodInfo::new(&mut method, None, impl_type).unwrap().unwrap();
+        let actual = method_info.method_wrapper();
+        local_insta_assert_snapshot!(pretty_print_syn_str(&actual).unwrap());
+    }
+
     #[test]
     fn callback_args_vec() {
         let impl_type: Type = syn::parse_str("Hello").unwrap();
@@ -334,6 +360,46 @@ mod tests {
         local_insta_assert_snapshot!(pretty_print_syn_str(&actual).unwrap());
     }
 
+    #[test]
+    fn guarded_method() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemFn = parse_quote! {
+            #[near(owner_only, guard = "Self::assert_not_paused")]
+            #[deposit(exact = "1 yoctoNEAR")]
+            #[gas(min = "20 Tgas")]
+            pub fn method(&mut self, k: u64) { }
+        };
+        let method_info = ImplItemMethodInfo::new(&mut method, None, impl_type).unwrap().unwrap();
+        let actual = method_info.method_wrapper();
+        local_insta_assert_snapshot!(pretty_print_syn_str(&actual).unwrap());
+    }
+
+    #[test]
+    fn guarded_init_method() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemFn = parse_quote! {
+            #[init]
+            #[near(guard = "assert_whitelisted")]
+            #[deposit(min = "0.1 NEAR")]
+            pub fn method() -> Self { }
+        };
+        let method_info = ImplItemMethodInfo::new(&mut method, None, impl_type).unwrap().unwrap();
+        let actual = method_info.method_wrapper();
+        local_insta_assert_snapshot!(pretty_print_syn_str(&actual).unwrap());
+    }
+
+    #[test]
+    fn locked_method() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemFn = parse_quote! {
+            #[near(lock = "account_id", reject_locked = "token_id")]
+            pub fn method(&mut self, account_id: AccountId, token_id: String) -> Promise { }
+        };
+        let method_info = ImplItemMethodInfo::new(&mut method, None, impl_type).unwrap().unwrap();
+        let actual = method_info.method_wrapper();
+        local_insta_assert_snapshot!(pretty_print_syn_str(&actual).unwrap());
+    }
+
     #[test]
     fn handle_no_self() {
         let impl_type: Type = syn::parse_str("Hello").unwrap();
@@ -7,6 +7,7 @@ mod item_trait_info;
 mod item_impl_info;
 
 pub(crate) mod ext;
+pub(crate) mod ext_from_abi;
 pub(crate) mod metadata;
 
 pub(crate) mod serializer;
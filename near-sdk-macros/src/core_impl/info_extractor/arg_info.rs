@@ -2,7 +2,7 @@ use crate::core_impl::info_extractor::SerializerType;
 use crate::core_impl::utils;
 use proc_macro2::{Span, TokenStream};
 use quote::ToTokens;
-use syn::{Attribute, Error, Ident, Pat, PatType, Token, Type};
+use syn::{Attribute, Error, Expr, Ident, Meta, MetaNameValue, Pat, PatType, Token, Type};
 
 pub enum BindgenArgType {
     /// Argument that we read from `env::input()`.
@@ -38,6 +38,8 @@ pub struct ArgInfo {
     pub bindgen_ty: BindgenArgType,
     /// Type of serializer that we use for this argument.
     pub serializer_ty: SerializerType,
+    /// Maximum length of the promise result of a `#[callback_result(max_len = ...)]` argument.
+    pub callback_max_len: Option<Expr>,
     /// Spans of all occurrences of the `Self` token, if any.
     pub self_occurrences: Vec<Span>,
     /// The original `PatType` of the argument.
@@ -79,6 +81,7 @@ impl ArgInfo {
         let mut bindgen_ty = BindgenArgType::Regular;
         // In the absence of serialization attributes this is a JSON serialization.
         let mut serializer_ty = SerializerType::JSON;
+        let mut callback_max_len = None;
         let mut more_errors: Vec<Error> = Vec::new();
         for attr in &original.attrs {
             let attr_str = attr.path().to_token_stream().to_string();
@@ -88,6 +91,18 @@ impl ArgInfo {
                 }
                 "callback_result" => {
                     bindgen_ty = BindgenArgType::CallbackResultArg;
+                    if let Meta::List(_) = attr.meta {
+                        match attr.parse_args::<MetaNameValue>() {
+                            Ok(arg) if arg.path.is_ident("max_len") => {
+                                callback_max_len = Some(arg.value);
+                            }
+                            Ok(arg) => more_errors.push(Error::new_spanned(
+                                arg.path,
+                                "Only `max_len` can be specified for `#[callback_result]`.",
+                            )),
+                            Err(e) => more_errors.push(e),
+                        }
+                    }
                 }
                 "callback_vec" => {
                     bindgen_ty = BindgenArgType::CallbackArgVec;
@@ -149,6 +164,7 @@ impl ArgInfo {
                 ty: ty.clone(),
                 bindgen_ty,
                 serializer_ty,
+                callback_max_len,
                 self_occurrences: sanitize_self.self_occurrences.clone(),
                 original: original.clone(),
             }),
@@ -1,8 +1,9 @@
 use super::visitor::Visitor;
 use super::{
-    ArgInfo, BindgenArgType, HandleResultAttr, InitAttr, MethodKind, SerializerAttr, SerializerType,
+    ArgInfo, BindgenArgType, HandleResultAttr, InitAttr, LockGuard, MethodGuards, MethodKind,
+    SerializerAttr, SerializerType,
 };
-use crate::core_impl::{utils, Returns};
+use crate::core_impl::{utils, ReturnKind, Returns};
 use proc_macro2::{Span, TokenStream as TokenStream2};
 use quote::ToTokens;
 use syn::spanned::Spanned;
@@ -20,6 +21,8 @@ pub struct AttrSigInfo {
     pub method_kind: MethodKind,
     /// What this function returns.
     pub returns: Returns,
+    /// Access rules checked before the arguments are deserialized.
+    pub guards: MethodGuards,
     /// The serializer that we use for `env::input()`.
     pub input_serializer: SerializerType,
     /// The original method signature.
@@ -115,6 +118,15 @@ impl AttrSigInfo {
                 "private" => {
                     visitor.visit_private_attr(attr)?;
                 }
+                "near" => {
+                    visitor.visit_near_attr(attr)?;
+                }
+                "deposit" => {
+                    visitor.visit_deposit_attr(attr)?;
+                }
+                "gas" => {
+                    visitor.visit_gas_attr(attr)?;
+                }
                 "result_serializer" => {
                     if args.borsh.is_some() && args.json.is_some() {
                         return Err(Error::new(
@@ -161,7 +173,7 @@ impl AttrSigInfo {
             }
         }
 
-        let (method_kind, returns) = visitor.build()?;
+        let (method_kind, returns, guards) = visitor.build()?;
 
         self_occurrences.extend(args.iter().flat_map(|arg| arg.self_occurrences.clone()));
 
@@ -180,6 +192,7 @@ impl AttrSigInfo {
             args,
             method_kind,
             returns,
+            guards,
             input_serializer: SerializerType::JSON,
             original_sig: original_sig.clone(),
         };
@@ -196,6 +209,25 @@ impl AttrSigInfo {
                 ));
             };
         result.input_serializer = input_serializer;
+
+        for lock in &result.guards.locks {
+            let arg = lock.arg();
+            if !result.input_args().any(|input_arg| &input_arg.ident == arg) {
+                return Err(Error::new(
+                    arg.span(),
+                    format!("`{}` is not an argument of the method.", arg),
+                ));
+            }
+            if matches!(lock, LockGuard::Lock(_))
+                && !matches!(result.returns.kind, ReturnKind::General(_))
+            {
+                return Err(Error::new(
+                    arg.span(),
+                    "Methods acquiring a lock must return a `Promise` or a `PromiseOrValue`, \
+                    after which the lock is released.",
+                ));
+            }
+        }
         Ok(result)
     }
 
@@ -0,0 +1,222 @@
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{Attribute, Error, Expr, Ident, Lit, LitStr, Meta, MetaNameValue, Path, Token};
+
+/// Access rules of a method. All but the locks are checked before its arguments are
+/// deserialized.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct MethodGuards {
+    /// Checks declared with `#[near(guard = "...")]`, `#[near(owner_only)]` and
+    /// `#[near(role = "...")]`, in the order of declaration.
+    pub guards: Vec<Guard>,
+    /// Requirement declared with `#[deposit(...)]` on the attached deposit.
+    pub deposit: Option<DepositRequirement>,
+    /// Minimum prepaid gas declared with `#[gas(min = "...")]`.
+    pub min_gas: Option<Amount>,
+    /// Operations declared with `#[near(lock = "...", reject_locked = "...")]` on the
+    /// `CallbackLock` of arguments, in the order of declaration.
+    pub locks: Vec<LockGuard>,
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub enum Guard {
+    /// A function which panics if the method can't be called.
+    Function(Path),
+    /// Calls `Self::assert_owner(&self)`.
+    Owner,
+    /// Calls `Self::assert_role(&self, role)`.
+    Role(String),
+}
+
+/// An operation on the `CallbackLock` of the key held by an argument.
+#[derive(Clone, PartialEq, Eq)]
+pub enum LockGuard {
+    /// Locks the key, failing if it's already locked, until the promise chain returned by the
+    /// method is executed.
+    Lock(Ident),
+    /// Fails if the key is locked.
+    RejectLocked(Ident),
+}
+
+impl LockGuard {
+    pub fn arg(&self) -> &Ident {
+        match self {
+            LockGuard::Lock(arg) | LockGuard::RejectLocked(arg) => arg,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub enum DepositRequirement {
+    Min(Amount),
+    Exact(Amount),
+}
+
+/// An amount of yoctoNEAR or gas, along with how it was written in the attribute.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Amount {
+    pub value: u128,
+    pub text: String,
+}
+
+const NEAR_UNITS: [(&str, u32); 6] =
+    [("yocto", 0), ("yoctoNEAR", 0), ("yoctoⓃ", 0), ("milliNEAR", 21), ("NEAR", 24), ("Ⓝ", 24)];
+
+const GAS_UNITS: [(&str, u32); 3] = [("gas", 0), ("Ggas", 9), ("Tgas", 12)];
+
+impl MethodGuards {
+    /// Whether some guards need the state of the contract.
+    pub fn reads_state(&self) -> bool {
+        self.guards.iter().any(|guard| matches!(guard, Guard::Owner | Guard::Role(_)))
+    }
+
+    /// Parses `#[near(guard = "...", owner_only, role = "...")]` and the lock operations.
+    pub fn visit_near_attr(&mut self, attr: &Attribute) -> syn::Result<()> {
+        for meta in attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)? {
+            match &meta {
+                Meta::Path(path) if path.is_ident("owner_only") => self.guards.push(Guard::Owner),
+                Meta::NameValue(name_value) if name_value.path.is_ident("guard") => {
+                    let path = lit_str(&name_value.value)?.parse::<Path>()?;
+                    self.guards.push(Guard::Function(path));
+                }
+                Meta::NameValue(name_value) if name_value.path.is_ident("role") => {
+                    self.guards.push(Guard::Role(lit_str(&name_value.value)?.value()));
+                }
+                Meta::NameValue(name_value) if name_value.path.is_ident("lock") => {
+                    self.locks.push(LockGuard::Lock(lit_str(&name_value.value)?.parse()?));
+                }
+                Meta::NameValue(name_value) if name_value.path.is_ident("reject_locked") => {
+                    self.locks.push(LockGuard::RejectLocked(lit_str(&name_value.value)?.parse()?));
+                }
+                _ => {
+                    return Err(Error::new(
+                        meta.span(),
+                        "Only `guard`, `owner_only`, `role`, `lock` and `reject_locked` can be \
+                        specified for `#[near]` on a method.",
+                    ))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Parses `#[deposit(min = "...")]` or `#[deposit(exact = "...")]`.
+    pub fn visit_deposit_attr(&mut self, attr: &Attribute) -> syn::Result<()> {
+        let name_value = attr.parse_args::<MetaNameValue>()?;
+        let amount = parse_amount(lit_str(&name_value.value)?, &NEAR_UNITS)?;
+        self.deposit = Some(if name_value.path.is_ident("min") {
+            DepositRequirement::Min(amount)
+        } else if name_value.path.is_ident("exact") {
+            DepositRequirement::Exact(amount)
+        } else {
+            return Err(Error::new(
+                name_value.path.span(),
+                "Only one of `min` or `exact` can be specified for `#[deposit]`.",
+            ));
+        });
+        Ok(())
+    }
+
+    /// Parses `#[gas(min = "...")]`.
+    pub fn visit_gas_attr(&mut self, attr: &Attribute) -> syn::Result<()> {
+        let name_value = attr.parse_args::<MetaNameValue>()?;
+        if !name_value.path.is_ident("min") {
+            return Err(Error::new(
+                name_value.path.span(),
+                "Only `min` can be specified for `#[gas]`.",
+            ));
+        }
+        let lit = lit_str(&name_value.value)?;
+        let amount = parse_amount(lit, &GAS_UNITS)?;
+        if u64::try_from(amount.value).is_err() {
+            return Err(Error::new(lit.span(), "The minimum gas doesn't fit in a `u64`."));
+        }
+        self.min_gas = Some(amount);
+        Ok(())
+    }
+}
+
+fn lit_str(expr: &Expr) -> syn::Result<&LitStr> {
+    match expr {
+        Expr::Lit(syn::ExprLit { lit: Lit::Str(lit), .. }) => Ok(lit),
+        _ => Err(Error::new(expr.span(), "Expected a string literal.")),
+    }
+}
+
+/// Parses an amount such as `"1 yoctoNEAR"` or `"0.5 Tgas"` into the smallest unit.
+fn parse_amount(lit: &LitStr, units: &[(&str, u32)]) -> syn::Result<Amount> {
+    let text = lit.value();
+    let error = || {
+        let units = units.iter().map(|(unit, _)| *unit).collect::<Vec<_>>().join(", ");
+        Error::new(
+            lit.span(),
+            format!("Invalid amount `{}`, expected a number followed by one of: {}.", text, units),
+        )
+    };
+    let split =
+        text.find(|c: char| !c.is_ascii_digit() && c != '.' && c != '_').ok_or_else(error)?;
+    let (number, unit) = text.split_at(split);
+    let decimals = units
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(unit.trim()))
+        .map(|(_, decimals)| *decimals)
+        .ok_or_else(error)?;
+    let number = number.replace('_', "");
+    let (whole, fraction) = number.split_once('.').unwrap_or((&number, ""));
+    if whole.is_empty() || fraction.len() > decimals as usize || fraction.contains('.') {
+        return Err(error());
+    }
+    let digits = format!("{}{:0<width$}", whole, fraction, width = decimals as usize);
+    let value = digits.parse::<u128>().map_err(|_| error())?;
+    Ok(Amount { value, text: text.trim().to_string() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    #[test]
+    fn amounts() {
+        let parse = |text: &str, units: &[(&str, u32)]| {
+            parse_amount(&LitStr::new(text, proc_macro2::Span::call_site()), units)
+                .map(|amount| amount.value)
+                .map_err(|err| err.to_string())
+        };
+        assert_eq!(parse("1 yocto", &NEAR_UNITS), Ok(1));
+        assert_eq!(parse("0.1 NEAR", &NEAR_UNITS), Ok(10u128.pow(23)));
+        assert_eq!(parse("1_000 milliNEAR", &NEAR_UNITS), Ok(10u128.pow(24)));
+        assert_eq!(parse("20 Tgas", &GAS_UNITS), Ok(20 * 10u128.pow(12)));
+        assert_eq!(parse("1.5tgas", &GAS_UNITS), Ok(15 * 10u128.pow(11)));
+        assert_eq!(
+            parse("0.5 yocto", &NEAR_UNITS),
+            Err("Invalid amount `0.5 yocto`, expected a number followed by one of: yocto, \
+                yoctoNEAR, yoctoⓃ, milliNEAR, NEAR, Ⓝ."
+                .to_string())
+        );
+        assert!(parse("20", &GAS_UNITS).is_err());
+        assert!(parse("Tgas", &GAS_UNITS).is_err());
+    }
+
+    #[test]
+    fn near_attr() {
+        let mut guards = MethodGuards::default();
+        let attr: Attribute = parse_quote! { #[near(owner_only, guard = "Self::not_paused")] };
+        guards.visit_near_attr(&attr).unwrap();
+        let attr: Attribute = parse_quote! { #[near(role = "minter")] };
+        guards.visit_near_attr(&attr).unwrap();
+        let not_paused: Path = parse_quote! { Self::not_paused };
+        assert!(
+            guards.guards
+                == [Guard::Owner, Guard::Function(not_paused), Guard::Role("minter".to_string())]
+        );
+
+        let attr: Attribute = parse_quote! { #[near(payable)] };
+        let err = guards.visit_near_attr(&attr).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Only `guard`, `owner_only`, `role`, `lock` and `reject_locked` can be specified for \
+            `#[near]` on a method."
+        );
+    }
+}
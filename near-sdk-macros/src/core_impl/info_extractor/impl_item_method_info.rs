@@ -112,4 +112,53 @@ mod tests {
         let expected = "View function can't be payable.";
         assert_eq!(expected.to_string(), actual.to_string());
     }
+
+    #[test]
+    fn view_deposit_fails() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = parse_quote! {
+            #[deposit(min = "1 yocto")]
+            pub fn method(&self) { }
+        };
+        let actual = ImplItemMethodInfo::new(&mut method, None, impl_type).map(|_| ()).unwrap_err();
+        let expected = "View function can't require a deposit.";
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
+    #[test]
+    fn lock_without_promise_fails() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = parse_quote! {
+            #[near(lock = "account_id")]
+            pub fn method(&mut self, account_id: AccountId) { }
+        };
+        let actual = ImplItemMethodInfo::new(&mut method, None, impl_type).map(|_| ()).unwrap_err();
+        let expected = "Methods acquiring a lock must return a `Promise` or a `PromiseOrValue`, \
+            after which the lock is released.";
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
+    #[test]
+    fn lock_unknown_argument_fails() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = parse_quote! {
+            #[near(lock = "receiver_id")]
+            pub fn method(&mut self, account_id: AccountId) { }
+        };
+        let actual = ImplItemMethodInfo::new(&mut method, None, impl_type).map(|_| ()).unwrap_err();
+        let expected = "`receiver_id` is not an argument of the method.";
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
+    #[test]
+    fn owner_only_without_receiver_fails() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = parse_quote! {
+            #[near(owner_only)]
+            pub fn method() { }
+        };
+        let actual = ImplItemMethodInfo::new(&mut method, None, impl_type).map(|_| ()).unwrap_err();
+        let expected = "`owner_only` and `role` guards can only be used on methods with a `self` receiver.";
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
 }
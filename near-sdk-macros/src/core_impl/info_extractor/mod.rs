@@ -26,6 +26,9 @@ mod item_impl_info;
 mod init_attr;
 pub use init_attr::InitAttr;
 
+mod guard_attr;
+pub use guard_attr::{DepositRequirement, Guard, LockGuard, MethodGuards};
+
 mod visitor;
 
 pub use item_impl_info::ItemImplInfo;
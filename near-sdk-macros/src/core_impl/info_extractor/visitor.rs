@@ -1,5 +1,6 @@
-use super::{HandleResultAttr, InitAttr, MethodKind, ReturnKind, SerializerAttr};
+use super::{HandleResultAttr, InitAttr, MethodGuards, MethodKind, ReturnKind, SerializerAttr};
 use crate::core_impl::{utils, CallMethod, InitMethod, Returns, SerializerType, ViewMethod};
+use proc_macro2::Span;
 use quote::ToTokens;
 use syn::{spanned::Spanned, Attribute, Error, FnArg, Receiver, ReturnType, Signature, Type};
 
@@ -17,6 +18,9 @@ struct ParsedData {
     ignores_state: bool,
     result_serializer: SerializerType,
     receiver: Option<Receiver>,
+    guards: MethodGuards,
+    // Span of the first guard which needs the state, to report a missing receiver.
+    state_guard_span: Option<Span>,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq)]
@@ -44,6 +48,8 @@ impl Default for ParsedData {
             ignores_state: Default::default(),
             result_serializer: SerializerType::JSON,
             receiver: Default::default(),
+            guards: Default::default(),
+            state_guard_span: Default::default(),
         }
     }
 }
@@ -107,6 +113,34 @@ impl Visitor {
         Ok(())
     }
 
+    pub fn visit_near_attr(&mut self, attr: &Attribute) -> syn::Result<()> {
+        self.parsed_data.guards.visit_near_attr(attr)?;
+        if self.parsed_data.guards.reads_state() && self.parsed_data.state_guard_span.is_none() {
+            self.parsed_data.state_guard_span = Some(attr.span());
+        }
+        Ok(())
+    }
+
+    pub fn visit_deposit_attr(&mut self, attr: &Attribute) -> syn::Result<()> {
+        use VisitorKind::*;
+
+        match self.kind {
+            Call | Init => {
+                // A method requiring a deposit has to accept it.
+                self.parsed_data.is_payable = true;
+                self.parsed_data.guards.visit_deposit_attr(attr)
+            }
+            View => {
+                let message = format!("{} function can't require a deposit.", self.kind);
+                Err(Error::new(attr.span(), message))
+            }
+        }
+    }
+
+    pub fn visit_gas_attr(&mut self, attr: &Attribute) -> syn::Result<()> {
+        self.parsed_data.guards.visit_gas_attr(attr)
+    }
+
     pub fn visit_result_serializer_attr(
         &mut self,
         attr: &Attribute,
@@ -168,8 +202,9 @@ impl Visitor {
         }
     }
 
-    /// Get the method data and the return type from the visited attributes and arguments.
-    pub fn build(mut self) -> syn::Result<(MethodKind, Returns)> {
+    /// Get the method data, the return type and the guards from the visited attributes and
+    /// arguments.
+    pub fn build(mut self) -> syn::Result<(MethodKind, Returns, MethodGuards)> {
         use VisitorKind::*;
 
         // Must be called last which is why it's called here.
@@ -178,9 +213,23 @@ impl Visitor {
         let Visitor { kind, parsed_data, .. } = self;
 
         let ParsedData {
-            is_payable, is_private, ignores_state, result_serializer, receiver, ..
+            is_payable,
+            is_private,
+            ignores_state,
+            result_serializer,
+            receiver,
+            guards,
+            state_guard_span,
+            ..
         } = parsed_data;
 
+        if let (Some(span), None) = (state_guard_span, &receiver) {
+            return Err(Error::new(
+                span,
+                "`owner_only` and `role` guards can only be used on methods with a `self` receiver.",
+            ));
+        }
+
         let method_kind = match kind {
             Call => {
                 MethodKind::Call(CallMethod { is_payable, is_private, result_serializer, receiver })
@@ -189,7 +238,7 @@ impl Visitor {
             View => MethodKind::View(ViewMethod { is_private, result_serializer, receiver }),
         };
 
-        Ok((method_kind, returns))
+        Ok((method_kind, returns, guards))
     }
 }
 
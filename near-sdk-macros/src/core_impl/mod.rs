@@ -4,9 +4,11 @@ mod code_generator;
 mod contract_metadata;
 mod event;
 mod info_extractor;
+mod storage_prefix;
 mod utils;
 pub(crate) use code_generator::*;
 pub(crate) use contract_metadata::contract_source_metadata_const;
 pub(crate) use contract_metadata::ContractMetadata;
 pub(crate) use event::{get_event_version, near_events};
 pub(crate) use info_extractor::*;
+pub(crate) use storage_prefix::{auto_prefix, storage_prefixes_impl, AutoPrefix};
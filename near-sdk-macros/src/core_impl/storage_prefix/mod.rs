@@ -0,0 +1,212 @@
+use darling::{Error, FromMeta};
+use inflector::Inflector;
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::{Attribute, Fields, ItemStruct, LitByteStr, Type};
+
+/// Collections of `near_sdk::store` and `near_sdk::collections`, whose fields are given a prefix
+/// by `auto_prefix` without being marked with `#[near(auto_prefix)]`.
+const COLLECTIONS: &[&str] = &[
+    "LookupMap",
+    "LookupSet",
+    "IterableMap",
+    "IterableSet",
+    "UnorderedMap",
+    "UnorderedSet",
+    "TreeMap",
+    "LegacyTreeMap",
+    "Vector",
+    "Deque",
+    "PriorityQueue",
+    "ExpiringMap",
+    "Lazy",
+    "LazyOption",
+];
+
+/// Index of the first byte of the key of the contract state, `b"STATE"`, which can't be used as a
+/// single byte prefix.
+const STATE_KEY_FIRST_BYTE: usize = b'S' as usize;
+
+/// How `auto_prefix` derives the storage prefix of a field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AutoPrefix {
+    /// The name of the field followed by `:`.
+    Name,
+    /// A single byte, the index of the field among the prefixed fields.
+    Index,
+}
+
+impl FromMeta for AutoPrefix {
+    fn from_word() -> Result<Self, Error> {
+        Ok(Self::Name)
+    }
+
+    fn from_string(value: &str) -> Result<Self, Error> {
+        match value {
+            "name" => Ok(Self::Name),
+            "index" => Ok(Self::Index),
+            _ => Err(Error::unknown_value(value)),
+        }
+    }
+}
+
+/// Removes the `#[near(auto_prefix)]` attributes from the fields of `input` and, if `auto_prefix`
+/// is set, generates the `<Struct>StorageKey` enum with a variant per collection field.
+pub(crate) fn auto_prefix(
+    input: &mut ItemStruct,
+    auto_prefix: Option<AutoPrefix>,
+    near_sdk_crate: &TokenStream,
+) -> syn::Result<TokenStream> {
+    let mut prefixed = Vec::new();
+    for field in input.fields.iter_mut() {
+        let marked = take_auto_prefix_attr(&mut field.attrs)?;
+        if marked && auto_prefix.is_none() {
+            return Err(syn::Error::new_spanned(
+                &field.ident,
+                "#[near(auto_prefix)] on a field requires `auto_prefix` on the struct",
+            ));
+        }
+        if marked || is_collection(&field.ty) {
+            match &field.ident {
+                Some(ident) => prefixed.push(ident.unraw()),
+                None if auto_prefix.is_some() => {
+                    return Err(syn::Error::new_spanned(
+                        &field.ty,
+                        "auto_prefix is only supported on structs with named fields",
+                    ))
+                }
+                None => {}
+            }
+        }
+    }
+    let Some(auto_prefix) = auto_prefix else {
+        return Ok(quote! {});
+    };
+    if auto_prefix == AutoPrefix::Index && prefixed.len() > STATE_KEY_FIRST_BYTE {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            format!(
+                "auto_prefix = \"index\" supports at most {} prefixed fields, as the prefix b\"S\" \
+                 would overlap the key of the contract state",
+                STATE_KEY_FIRST_BYTE
+            ),
+        ));
+    }
+
+    let vis = &input.vis;
+    let name = &input.ident;
+    let enum_name = format_ident!("{}StorageKey", name);
+    let enum_doc = format!("Storage prefixes of the collections of [`{}`].", name);
+    let variants: Vec<_> = prefixed
+        .iter()
+        .map(|field| format_ident!("{}", field.to_string().to_pascal_case(), span = field.span()))
+        .collect();
+    let docs = prefixed.iter().map(|field| format!("Prefix of the `{}` field.", field));
+    let prefixes = prefixed.iter().enumerate().map(|(index, field)| {
+        let prefix = match auto_prefix {
+            AutoPrefix::Name => format!("{}:", field).into_bytes(),
+            AutoPrefix::Index => vec![index as u8],
+        };
+        LitByteStr::new(&prefix, Span::call_site())
+    });
+
+    Ok(quote! {
+        #[doc = #enum_doc]
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        #vis enum #enum_name {
+            #(
+                #[doc = #docs]
+                #variants,
+            )*
+        }
+
+        #[automatically_derived]
+        impl #near_sdk_crate::IntoStorageKey for #enum_name {
+            fn into_storage_key(self) -> ::std::vec::Vec<u8> {
+                match self {
+                    #(Self::#variants => #prefixes.to_vec(),)*
+                }
+            }
+        }
+    })
+}
+
+/// Generates the implementation of `StoragePrefixes` for `input`, which reports the prefixes of
+/// the fields implementing it.
+pub(crate) fn storage_prefixes_impl(
+    input: &ItemStruct,
+    near_sdk_crate: &TokenStream,
+) -> TokenStream {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let fields = input.fields.iter().enumerate().map(|(index, field)| {
+        let (member, path) = match &field.ident {
+            Some(ident) => (quote! { #ident }, ident.unraw().to_string()),
+            None => {
+                let index = syn::Index::from(index);
+                (quote! { #index }, index.index.to_string())
+            }
+        };
+        let cfgs = field.attrs.iter().filter(|attr| attr.path().is_ident("cfg"));
+        quote! {
+            #(#cfgs)*
+            (&#near_sdk_crate::__private::PrefixProbe(&self.#member)).collect_storage_prefixes(
+                &#near_sdk_crate::__private::field_path(path, #path),
+                prefixes,
+            );
+        }
+    });
+    let unit = matches!(input.fields, Fields::Unit);
+    let allow_unused = unit.then(|| quote! { #[allow(unused_variables)] });
+
+    quote! {
+        #[automatically_derived]
+        impl #impl_generics #near_sdk_crate::store::StoragePrefixes for #name #ty_generics #where_clause {
+            #allow_unused
+            fn storage_prefixes(
+                &self,
+                path: &str,
+                prefixes: &mut ::std::vec::Vec<#near_sdk_crate::store::StoragePrefix>,
+            ) {
+                #[allow(unused_imports)]
+                use #near_sdk_crate::__private::{CollectStoragePrefixes as _, SkipStoragePrefixes as _};
+                #(#fields)*
+            }
+        }
+    }
+}
+
+/// Removes the `#[near(auto_prefix)]` attributes from `attrs`, returning whether there was one.
+fn take_auto_prefix_attr(attrs: &mut Vec<Attribute>) -> syn::Result<bool> {
+    let mut marked = false;
+    let mut result = Ok(());
+    attrs.retain(|attr| {
+        if !attr.path().is_ident("near") {
+            return true;
+        }
+        match attr.parse_args::<syn::Ident>() {
+            Ok(ident) if ident == "auto_prefix" => marked = true,
+            _ => {
+                result = Err(syn::Error::new_spanned(
+                    attr,
+                    "only #[near(auto_prefix)] is supported on fields",
+                ))
+            }
+        }
+        false
+    });
+    result.map(|()| marked)
+}
+
+/// Whether `ty` is one of the [`COLLECTIONS`], by the last segment of its path.
+fn is_collection(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| COLLECTIONS.iter().any(|name| segment.ident == name)),
+        _ => false,
+    }
+}
@@ -41,6 +41,8 @@ struct NearMacroArgs {
     contract_state: Option<bool>,
     contract_metadata: Option<core_impl::ContractMetadata>,
     inside_nearsdk: Option<bool>,
+    auto_prefix: Option<AutoPrefix>,
+    native_dispatch: Option<bool>,
 }
 
 /// This attribute macro is used to enhance the near_bindgen macro.
@@ -92,6 +94,46 @@ struct NearMacroArgs {
 /// }
 /// ```
 ///
+/// The `auto_prefix` argument generates a `<Struct>StorageKey` enum, with a variant per field of
+/// a collection type from `near_sdk::store` or `near_sdk::collections`, and per field marked with
+/// `#[near(auto_prefix)]`. The prefix of a variant is the name of its field followed by `:`, or
+/// with `auto_prefix = "index"`, a single byte: the index of the field among the prefixed ones.
+///
+/// # Example
+/// ```ignore
+/// #[near(contract_state, auto_prefix)]
+/// struct MyStruct {
+///     balances: LookupMap<AccountId, u128>,
+///     #[near(auto_prefix)]
+///     token: FungibleToken,
+/// }
+///
+/// impl Default for MyStruct {
+///     fn default() -> Self {
+///         Self {
+///             balances: LookupMap::new(MyStructStorageKey::Balances),
+///             token: FungibleToken::new(MyStructStorageKey::Token),
+///         }
+///     }
+/// }
+/// ```
+///
+/// Structs serialized with Borsh also implement `near_sdk::store::StoragePrefixes`, so that
+/// `near_sdk::store::check_storage_prefixes` can check in unit tests that the prefixes of all
+/// collections of the contract state, including the nested ones, don't overlap.
+///
+/// On an impl section, the `native_dispatch` argument also generates the methods which execute
+/// the exported methods natively, so that `near_sdk::test_utils::native_contract` can deploy the
+/// contract on a `near_sdk::test_utils::ReceiptExecutor`. They are not generated for wasm32, and
+/// they require the contract state to implement `Default` if it is read, as the exported methods do.
+///
+/// ```ignore
+/// #[near(native_dispatch)]
+/// impl Contract {
+///     pub fn get_status(&self) -> String { ... }
+/// }
+/// ```
+///
 /// As well, the macro supports arguments like `event_json` and `contract_metadata`.
 ///
 #[proc_macro_attribute]
@@ -124,6 +166,16 @@ pub fn near(attr: TokenStream, item: TokenStream) -> TokenStream {
 
     let mut expanded: proc_macro2::TokenStream = quote! {};
 
+    if near_macro_args.native_dispatch.is_some() && syn::parse::<ItemImpl>(item.clone()).is_err() {
+        return TokenStream::from(
+            syn::Error::new(
+                Span::call_site(),
+                "`native_dispatch` can only be used on impl sections.",
+            )
+            .to_compile_error(),
+        );
+    }
+
     if near_macro_args.contract_state.unwrap_or(false) {
         if let Some(metadata) = near_macro_args.contract_metadata {
             expanded = quote! {#[#near_sdk_crate::near_bindgen(#metadata)]}
@@ -207,10 +259,25 @@ pub fn near(attr: TokenStream, item: TokenStream) -> TokenStream {
         };
     }
 
-    if let Ok(input) = syn::parse::<ItemStruct>(item.clone()) {
+    if let Ok(mut input) = syn::parse::<ItemStruct>(item.clone()) {
+        let storage_key = match core_impl::auto_prefix(
+            &mut input,
+            near_macro_args.auto_prefix,
+            &near_sdk_crate,
+        ) {
+            Ok(storage_key) => storage_key,
+            Err(err) => return TokenStream::from(err.to_compile_error()),
+        };
+        let storage_prefixes = if has_borsh && !near_macro_args.inside_nearsdk.unwrap_or(false) {
+            core_impl::storage_prefixes_impl(&input, &near_sdk_crate)
+        } else {
+            quote! {}
+        };
         expanded = quote! {
             #expanded
             #input
+            #storage_key
+            #storage_prefixes
         };
     } else if let Ok(input) = syn::parse::<ItemEnum>(item.clone()) {
         expanded = quote! {
@@ -218,8 +285,13 @@ pub fn near(attr: TokenStream, item: TokenStream) -> TokenStream {
             #input
         };
     } else if let Ok(input) = syn::parse::<ItemImpl>(item) {
+        let bindgen_args = if near_macro_args.native_dispatch.unwrap_or(false) {
+            quote! {(native_dispatch)}
+        } else {
+            quote! {}
+        };
         expanded = quote! {
-            #[#near_sdk_crate::near_bindgen]
+            #[#near_sdk_crate::near_bindgen #bindgen_args]
             #input
         };
     } else {
@@ -345,7 +417,7 @@ pub fn near_bindgen(attr: TokenStream, item: TokenStream) -> TokenStream {
 
         let metadata_impl_gen = syn::parse::<ItemImpl>(metadata_impl_gen)
             .expect("failed to generate contract metadata");
-        process_impl_block(metadata_impl_gen)
+        process_impl_block(metadata_impl_gen, false)
     };
 
     if let Ok(input) = syn::parse::<ItemStruct>(item.clone()) {
@@ -392,6 +464,18 @@ pub fn near_bindgen(attr: TokenStream, item: TokenStream) -> TokenStream {
             #metadata_impl_gen
         })
     } else if let Ok(input) = syn::parse::<ItemImpl>(item) {
+        let native_dispatch =
+            match syn::parse::<Option<Ident>>(attr) {
+                Ok(None) => false,
+                Ok(Some(arg)) if arg == "native_dispatch" => true,
+                _ => return TokenStream::from(
+                    syn::Error::new(
+                        Span::call_site(),
+                        "Only `native_dispatch` can be passed to `near_bindgen` on impl sections.",
+                    )
+                    .to_compile_error(),
+                ),
+            };
         for method in &input.items {
             if let ImplItem::Fn(m) = method {
                 let ident = &m.sig.ident;
@@ -406,7 +490,7 @@ pub fn near_bindgen(attr: TokenStream, item: TokenStream) -> TokenStream {
                 }
             }
         }
-        match process_impl_block(input) {
+        match process_impl_block(input, native_dispatch) {
             Ok(output) => output,
             Err(output) => output,
         }
@@ -426,10 +510,12 @@ pub fn near_bindgen(attr: TokenStream, item: TokenStream) -> TokenStream {
 //
 // # Arguments
 // * input - impl block to process.
+// * native_dispatch - whether to generate the methods executing the exported methods natively.
 //
 // The Result has a TokenStream error type, because those need to be propagated to the compiler.
 fn process_impl_block(
     mut input: ItemImpl,
+    native_dispatch: bool,
 ) -> Result<proc_macro2::TokenStream, proc_macro2::TokenStream> {
     let item_impl_info = match ItemImplInfo::new(&mut input) {
         Ok(x) => x,
@@ -441,7 +527,7 @@ fn process_impl_block(
     #[cfg(feature = "__abi-generate")]
     let abi_generated = abi::generate(&item_impl_info);
 
-    let generated_code = item_impl_info.wrapper_code();
+    let generated_code = item_impl_info.wrapper_code(native_dispatch);
 
     // Add wrapper methods for ext call API
     let ext_generated_code = item_impl_info.generate_ext_wrapper_code();
@@ -507,6 +593,75 @@ pub fn ext_contract(attr: TokenStream, item: TokenStream) -> TokenStream {
     }
 }
 
+/// `ext_contract_from_abi` generates the same module as [`ext_contract`] from the NEAR ABI of a
+/// contract, instead of a trait mirroring its interface. The path of the ABI JSON file is relative
+/// to the directory of the crate's manifest, and the crate is rebuilt when the file changes.
+///
+/// The arguments of each function are serialized with JSON or Borsh as specified by the ABI,
+/// and their types are derived from their schemas. JSON arguments which type has no Rust
+/// counterpart, such as structs, are passed as `near_sdk::serde_json::Value`.
+///
+/// # Examples
+///
+/// ```ignore
+/// near_sdk::ext_contract_from_abi!(ext_calculator, "res/calculator_abi.json");
+///
+/// ext_calculator::ext("calculator.near".parse().unwrap()).mult(2, 3);
+/// ```
+#[proc_macro]
+pub fn ext_contract_from_abi(item: TokenStream) -> TokenStream {
+    struct ExtFromAbiInput {
+        mod_name: Ident,
+        path: syn::LitStr,
+    }
+
+    impl syn::parse::Parse for ExtFromAbiInput {
+        fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+            let mod_name = input.parse()?;
+            input.parse::<syn::Token![,]>()?;
+            let path = input.parse()?;
+            input.parse::<Option<syn::Token![,]>>()?;
+            Ok(Self { mod_name, path })
+        }
+    }
+
+    let ExtFromAbiInput { mod_name, path } = match syn::parse(item) {
+        Ok(input) => input,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let full_path = std::path::Path::new(&manifest_dir).join(path.value());
+    let abi = match std::fs::read_to_string(&full_path)
+        .map_err(|err| err.to_string())
+        .and_then(|abi| serde_json::from_str(&abi).map_err(|err| err.to_string()))
+    {
+        Ok(abi) => abi,
+        Err(err) => {
+            return syn::Error::new(
+                path.span(),
+                format!("Failed to read the ABI from {}: {}", full_path.display(), err),
+            )
+            .to_compile_error()
+            .into()
+        }
+    };
+    let ext_api = match ext_from_abi::generate_ext_from_abi(&mod_name, &abi) {
+        Ok(ext_api) => ext_api,
+        // Point at the path of the ABI, as the error is in its content.
+        Err(err) => {
+            return syn::Error::new(path.span(), format!("Invalid ABI {}: {}", path.value(), err))
+                .to_compile_error()
+                .into()
+        }
+    };
+    let full_path = full_path.to_string_lossy();
+    TokenStream::from(quote! {
+        #ext_api
+        // Tracks the ABI file, so that the module is generated again when it changes.
+        const _: &[u8] = ::std::include_bytes!(#full_path);
+    })
+}
+
 // The below attributes a marker-attributes and therefore they are no-op.
 
 /// `callback` is a marker attribute it does not generate code by itself.
@@ -842,6 +997,42 @@ pub fn function_error(item: TokenStream) -> TokenStream {
     })
 }
 
+/// `native_contract` lists the methods of a contract type which can be executed in-process by
+/// `near_sdk::test_utils::ReceiptExecutor`. Every method must be exported by a `#[near]` impl
+/// block of the type with `#[near(native_dispatch)]`, which generates the native methods.
+///
+/// ```ignore
+/// let contract = near_sdk::test_utils::native_contract!(Contract { new, get_status, set_status });
+/// ```
+#[proc_macro]
+pub fn native_contract(item: TokenStream) -> TokenStream {
+    struct NativeContractInput {
+        ty: syn::Path,
+        methods: syn::punctuated::Punctuated<Ident, syn::Token![,]>,
+    }
+
+    impl syn::parse::Parse for NativeContractInput {
+        fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+            let ty = input.parse()?;
+            let content;
+            syn::braced!(content in input);
+            let methods = content.parse_terminated(Ident::parse, syn::Token![,])?;
+            Ok(Self { ty, methods })
+        }
+    }
+
+    let NativeContractInput { ty, methods } = match syn::parse(item) {
+        Ok(input) => input,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let names = methods.iter().map(|method| method.to_string());
+    let wrappers = methods.iter().map(|method| quote::format_ident!("__near_native_{}", method));
+    TokenStream::from(quote! {
+        ::near_sdk::test_utils::NativeContract::new()
+            #(.with_method(#names, #ty::#wrappers))*
+    })
+}
+
 /// NOTE: This is an internal implementation for `#[near_bindgen(events(standard = ...))]` attribute.
 ///
 /// This derive macro is used to inject the necessary wrapper and logic to auto format
@@ -13,6 +13,8 @@ fn compilation_tests() {
     t.pass("compilation_tests/init_function.rs");
     t.pass("compilation_tests/init_ignore_state.rs");
     t.pass("compilation_tests/no_default.rs");
+    t.pass("compilation_tests/state_without_default.rs");
+    t.compile_fail("compilation_tests/native_dispatch_struct.rs");
     // TODO: unignore upon resolution of https://github.com/near/near-sdk-rs/issues/1211
     // t.pass("compilation_tests/lifetime_method_result.rs");
     t.pass("compilation_tests/lifetime_method.rs");
@@ -40,4 +42,6 @@ fn compilation_tests() {
     t.compile_fail("compilation_tests/contract_metadata_fn_name.rs");
     t.pass("compilation_tests/contract_metadata_bindgen.rs");
     t.pass("compilation_tests/types.rs");
+    t.pass("compilation_tests/ext_contract_from_abi.rs");
+    t.compile_fail("compilation_tests/ext_contract_from_abi_invalid.rs");
 }
@@ -0,0 +1,15 @@
+//! Generating the `ext` module from an ABI with function and argument names which are keywords.
+
+use near_sdk::{ext_contract_from_abi, AccountId, Promise};
+
+// Paths are relative to the manifest of the crate built by trybuild, in `target/tests/trybuild`.
+ext_contract_from_abi!(ext_keywords, "../../../../near-sdk/compilation_tests/abi/keywords_abi.json");
+
+#[allow(dead_code)]
+fn calls(account_id: AccountId) -> Promise {
+    ext_keywords::ext(account_id.clone())
+        .r#type("value".to_string(), 1)
+        .and(ext_keywords::ext(account_id).match_all(vec![1, 2]))
+}
+
+fn main() {}
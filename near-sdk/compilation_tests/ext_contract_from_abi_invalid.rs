@@ -0,0 +1,11 @@
+//! Names of the ABI which are not Rust identifiers are reported at the path of the ABI.
+
+use near_sdk::ext_contract_from_abi;
+
+// Paths are relative to the manifest of the crate built by trybuild, in `target/tests/trybuild`.
+ext_contract_from_abi!(
+    ext_invalid,
+    "../../../../near-sdk/compilation_tests/abi/invalid_names_abi.json"
+);
+
+fn main() {}
@@ -0,0 +1,11 @@
+//! `native_dispatch` is only supported on impl sections.
+
+use near_sdk::near;
+
+#[near(contract_state, native_dispatch)]
+#[derive(Default)]
+pub struct Counter {
+    value: u8,
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+//! Contract state without `Default`, which only has view methods.
+
+use near_sdk::near;
+
+#[near(contract_state)]
+pub struct Counter {
+    value: u8,
+}
+
+#[near]
+impl Counter {
+    pub fn get(&self) -> u8 {
+        self.value
+    }
+}
+
+fn main() {}
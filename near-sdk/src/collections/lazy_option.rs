@@ -8,6 +8,7 @@ use std::marker::PhantomData;
 use borsh::{to_vec, BorshDeserialize, BorshSerialize};
 
 use crate::env;
+use crate::store::prefixes::{StoragePrefix, StoragePrefixes};
 use crate::IntoStorageKey;
 use near_sdk_macros::near;
 
@@ -200,6 +201,12 @@ where
     }
 }
 
+impl<T> StoragePrefixes for LazyOption<T> {
+    fn storage_prefixes(&self, path: &str, prefixes: &mut Vec<StoragePrefix>) {
+        prefixes.push(StoragePrefix::new(path, &self.storage_key));
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(test)]
 mod tests {
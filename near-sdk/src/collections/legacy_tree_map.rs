@@ -10,6 +10,7 @@ use std::ops::Bound;
 
 use crate::collections::UnorderedMap;
 use crate::collections::{append, Vector};
+use crate::store::prefixes::{field_path, StoragePrefix, StoragePrefixes};
 use crate::IntoStorageKey;
 
 /// TreeMap based on AVL-tree
@@ -691,6 +692,13 @@ where
     }
 }
 
+impl<K, V> StoragePrefixes for LegacyTreeMap<K, V> {
+    fn storage_prefixes(&self, path: &str, prefixes: &mut Vec<StoragePrefix>) {
+        self.val.storage_prefixes(&field_path(path, "values"), prefixes);
+        self.tree.storage_prefixes(&field_path(path, "nodes"), prefixes);
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(test)]
 mod tests {
@@ -6,6 +6,7 @@ use std::marker::PhantomData;
 use borsh::{to_vec, BorshDeserialize, BorshSerialize};
 
 use crate::collections::append_slice;
+use crate::store::prefixes::{StoragePrefix, StoragePrefixes};
 use crate::{env, IntoStorageKey};
 use near_sdk_macros::near;
 
@@ -210,6 +211,12 @@ where
     }
 }
 
+impl<K, V> StoragePrefixes for LookupMap<K, V> {
+    fn storage_prefixes(&self, path: &str, prefixes: &mut Vec<StoragePrefix>) {
+        prefixes.push(StoragePrefix::new(path, &self.key_prefix));
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(test)]
 mod tests {
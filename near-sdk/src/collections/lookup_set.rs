@@ -7,6 +7,7 @@ use borsh::{to_vec, BorshSerialize};
 use near_sdk_macros::near;
 
 use crate::collections::append_slice;
+use crate::store::prefixes::{StoragePrefix, StoragePrefixes};
 use crate::{env, IntoStorageKey};
 
 const ERR_ELEMENT_SERIALIZATION: &str = "Cannot serialize element with Borsh";
@@ -142,6 +143,12 @@ where
     }
 }
 
+impl<T> StoragePrefixes for LookupSet<T> {
+    fn storage_prefixes(&self, path: &str, prefixes: &mut Vec<StoragePrefix>) {
+        prefixes.push(StoragePrefix::new(path, &self.element_prefix));
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(test)]
 mod tests {
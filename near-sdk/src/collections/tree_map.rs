@@ -3,6 +3,7 @@ use std::ops::Bound;
 
 use crate::collections::LookupMap;
 use crate::collections::{append, Vector};
+use crate::store::prefixes::{field_path, StoragePrefix, StoragePrefixes};
 use crate::{env, IntoStorageKey};
 use near_sdk_macros::near;
 
@@ -927,6 +928,13 @@ where
     }
 }
 
+impl<K, V> StoragePrefixes for TreeMap<K, V> {
+    fn storage_prefixes(&self, path: &str, prefixes: &mut Vec<StoragePrefix>) {
+        self.val.storage_prefixes(&field_path(path, "values"), prefixes);
+        self.tree.storage_prefixes(&field_path(path, "nodes"), prefixes);
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(test)]
 mod tests {
@@ -5,6 +5,7 @@ mod iter;
 pub use iter::Iter;
 
 use crate::collections::{append, append_slice, Vector};
+use crate::store::prefixes::{field_path, StoragePrefix, StoragePrefixes};
 use crate::{env, IntoStorageKey};
 use borsh::{to_vec, BorshDeserialize, BorshSerialize};
 use near_sdk_macros::near;
@@ -337,6 +338,14 @@ where
     }
 }
 
+impl<K, V> StoragePrefixes for UnorderedMap<K, V> {
+    fn storage_prefixes(&self, path: &str, prefixes: &mut Vec<StoragePrefix>) {
+        prefixes.push(StoragePrefix::new(&field_path(path, "index"), &self.key_index_prefix));
+        self.keys.storage_prefixes(&field_path(path, "keys"), prefixes);
+        self.values.storage_prefixes(&field_path(path, "values"), prefixes);
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(test)]
 mod tests {
@@ -1,6 +1,7 @@
 //! A set implemented on a trie. Unlike `std::collections::HashSet` the elements in this set are not
 //! hashed but are instead serialized.
 use crate::collections::{append, append_slice, Vector};
+use crate::store::prefixes::{field_path, StoragePrefix, StoragePrefixes};
 use crate::{env, IntoStorageKey};
 use borsh::{to_vec, BorshDeserialize, BorshSerialize};
 use near_sdk_macros::near;
@@ -255,6 +256,13 @@ where
     }
 }
 
+impl<T> StoragePrefixes for UnorderedSet<T> {
+    fn storage_prefixes(&self, path: &str, prefixes: &mut Vec<StoragePrefix>) {
+        prefixes.push(StoragePrefix::new(&field_path(path, "index"), &self.element_index_prefix));
+        self.elements.storage_prefixes(&field_path(path, "elements"), prefixes);
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(test)]
 mod tests {
@@ -8,6 +8,7 @@ use borsh::{to_vec, BorshDeserialize, BorshSerialize};
 use near_sdk_macros::near;
 
 use crate::collections::append_slice;
+use crate::store::prefixes::{StoragePrefix, StoragePrefixes};
 use crate::{env, IntoStorageKey};
 
 const ERR_INCONSISTENT_STATE: &str = "The collection is an inconsistent state. Did previous smart contract execution terminate unexpectedly?";
@@ -375,6 +376,12 @@ where
     }
 }
 
+impl<T> StoragePrefixes for Vector<T> {
+    fn storage_prefixes(&self, path: &str, prefixes: &mut Vec<StoragePrefix>) {
+        prefixes.push(StoragePrefix::new(path, &self.prefix));
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(test)]
 mod tests {
@@ -26,6 +26,10 @@ const REGISTER_EXPECTED_ERR: &str =
 const ATOMIC_OP_REGISTER: u64 = u64::MAX - 2;
 /// Register used to record evicted values from the storage.
 const EVICTED_REGISTER: u64 = u64::MAX - 1;
+/// Register used for the values read by storage iterators, while their keys use
+/// `ATOMIC_OP_REGISTER`.
+#[cfg(feature = "unstable")]
+const STORAGE_ITER_VALUE_REGISTER: u64 = u64::MAX - 3;
 
 /// Key used to store the state of the contract.
 const STATE_KEY: &[u8] = b"STATE";
@@ -759,7 +763,41 @@ pub fn promise_result(result_idx: u64) -> PromiseResult {
             let data = expect_register(read_register(ATOMIC_OP_REGISTER));
             PromiseResult::Successful(data)
         }
-        Err(PromiseError::Failed) => PromiseResult::Failed,
+        Err(_) => PromiseResult::Failed,
+    }
+}
+
+/// Returns the execution result of the promise under `result_idx` which caused the callback, or
+/// why it is not usable: the promise failed or is not ready, or its result is longer than
+/// `max_len` bytes, in which case the result is not read into memory.
+///
+/// # Examples
+/// ```
+/// # use near_sdk::{env, testing_env, test_utils::VMContextBuilder, PromiseError, PromiseResult};
+/// # testing_env!(
+/// #     VMContextBuilder::new().build(),
+/// #     near_sdk::test_vm_config(),
+/// #     near_sdk::RuntimeFeesConfig::test(),
+/// #     Default::default(),
+/// #     vec![PromiseResult::Successful(b"0123456789".to_vec()), PromiseResult::Failed],
+/// # );
+/// assert_eq!(env::promise_result_checked(0, 16), Ok(b"0123456789".to_vec()));
+/// assert_eq!(env::promise_result_checked(0, 8), Err(PromiseError::TooLong(10)));
+/// assert_eq!(env::promise_result_checked(1, 16), Err(PromiseError::Failed));
+/// ```
+pub fn promise_result_checked(result_idx: u64, max_len: usize) -> Result<Vec<u8>, PromiseError> {
+    match unsafe { sys::promise_result(result_idx, ATOMIC_OP_REGISTER) } {
+        0 => Err(PromiseError::NotReady),
+        1 => {
+            let len = expect_register(register_len(ATOMIC_OP_REGISTER));
+            let len = usize::try_from(len).unwrap_or(usize::MAX);
+            if len > max_len {
+                return Err(PromiseError::TooLong(len));
+            }
+            Ok(expect_register(read_register(ATOMIC_OP_REGISTER)))
+        }
+        2 => Err(PromiseError::Failed),
+        _ => abort(),
     }
 }
 
@@ -806,6 +844,20 @@ pub fn promise_yield_create(
     }
 }
 
+/// Same as [`promise_yield_create`], and returns the resumption token (data id) along with the
+/// promise index instead of writing it to a register.
+pub(crate) fn promise_yield_create_with_data_id(
+    function_name: &str,
+    arguments: &[u8],
+    gas: Gas,
+    weight: GasWeight,
+) -> (PromiseIndex, CryptoHash) {
+    let promise_index =
+        promise_yield_create(function_name, arguments, gas, weight, ATOMIC_OP_REGISTER);
+    //* SAFETY: promise_yield_create writes the 32 bytes of the data id to the register.
+    (promise_index, unsafe { read_register_fixed_32(ATOMIC_OP_REGISTER) })
+}
+
 /// Accepts a resumption token `data_id` created by promise_yield_create on the local account.
 /// `data` is a payload to be passed to the callback method as a promise input. Returns false if
 /// no promise yield with the specified `data_id` is found. Returns true otherwise, guaranteeing
@@ -946,6 +998,34 @@ pub fn storage_read(key: &[u8]) -> Option<Vec<u8>> {
         _ => abort(),
     }
 }
+/// Sets the value stored under the given key as the return value of the contract, like
+/// [`value_return`] with the result of [`storage_read`]. The value is passed from the storage to
+/// the return value through a register, without being copied into the memory of the contract.
+/// Returns `false` and doesn't set the return value if there is no value for the key.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::env;
+/// use near_sdk::test_utils::get_return_value;
+///
+/// env::storage_write(b"key", b"value");
+/// assert!(env::value_return_from_storage(b"key"));
+/// assert_eq!(get_return_value(), Some(b"value".to_vec()));
+/// assert!(!env::value_return_from_storage(b"missing"));
+/// ```
+pub fn value_return_from_storage(key: &[u8]) -> bool {
+    match unsafe { sys::storage_read(key.len() as _, key.as_ptr() as _, ATOMIC_OP_REGISTER) } {
+        0 => false,
+        // A length of `u64::MAX` makes the host read the value from the register passed as the
+        // pointer.
+        1 => {
+            unsafe { sys::value_return(u64::MAX, ATOMIC_OP_REGISTER) };
+            true
+        }
+        _ => abort(),
+    }
+}
 /// Removes the value stored under the given key.
 /// If key-value existed returns `true`, otherwise `false`.
 pub fn storage_remove(key: &[u8]) -> bool {
@@ -968,6 +1048,91 @@ pub fn storage_has_key(key: &[u8]) -> bool {
     }
 }
 
+/// Iterates over all key-values in the storage whose keys start with `prefix`, in the
+/// lexicographic order of the keys.
+///
+/// This is useful for migration and debugging tooling which needs to enumerate or clean up
+/// everything stored under a collection prefix. Note that the protocol currently disables the
+/// underlying host functions, so these calls fail when executed on chain and are only supported
+/// by the mocked blockchain used in unit tests, which iterates over a snapshot of the storage
+/// taken when the iterator is created.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::env;
+///
+/// env::storage_write(b"a/1", b"one");
+/// env::storage_write(b"a/2", b"two");
+/// env::storage_write(b"b/1", b"other");
+///
+/// let entries: Vec<_> = env::storage_iter_prefix(b"a/").collect();
+/// assert_eq!(entries, vec![(b"a/1".to_vec(), b"one".to_vec()), (b"a/2".to_vec(), b"two".to_vec())]);
+/// ```
+#[cfg(feature = "unstable")]
+pub fn storage_iter_prefix(prefix: &[u8]) -> impl Iterator<Item = (Vec<u8>, Vec<u8>)> {
+    let iterator_id = unsafe { sys::storage_iter_prefix(prefix.len() as _, prefix.as_ptr() as _) };
+    StorageIter { iterator_id }
+}
+
+/// Iterates over all key-values in the storage whose keys are between `start` (inclusive) and
+/// `end` (exclusive), in the lexicographic order of the keys. The iterator is empty unless
+/// `start < end`.
+///
+/// The same restrictions as for [`storage_iter_prefix`] apply.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::env;
+///
+/// env::storage_write(b"a", b"1");
+/// env::storage_write(b"b", b"2");
+/// env::storage_write(b"c", b"3");
+///
+/// let keys: Vec<_> = env::storage_iter_range(b"a", b"c").map(|(key, _)| key).collect();
+/// assert_eq!(keys, vec![b"a".to_vec(), b"b".to_vec()]);
+/// ```
+#[cfg(feature = "unstable")]
+pub fn storage_iter_range(start: &[u8], end: &[u8]) -> impl Iterator<Item = (Vec<u8>, Vec<u8>)> {
+    let iterator_id = unsafe {
+        sys::storage_iter_range(
+            start.len() as _,
+            start.as_ptr() as _,
+            end.len() as _,
+            end.as_ptr() as _,
+        )
+    };
+    StorageIter { iterator_id }
+}
+
+#[cfg(feature = "unstable")]
+struct StorageIter {
+    iterator_id: u64,
+}
+
+#[cfg(feature = "unstable")]
+impl Iterator for StorageIter {
+    type Item = (Vec<u8>, Vec<u8>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match unsafe {
+            sys::storage_iter_next(
+                self.iterator_id,
+                ATOMIC_OP_REGISTER,
+                STORAGE_ITER_VALUE_REGISTER,
+            )
+        } {
+            0 => None,
+            1 => Some((
+                expect_register(read_register(ATOMIC_OP_REGISTER)),
+                expect_register(read_register(STORAGE_ITER_VALUE_REGISTER)),
+            )),
+            _ => abort(),
+        }
+    }
+}
+
 // ############################################
 // # Saving and loading of the contract state #
 // ############################################
@@ -1163,6 +1328,29 @@ mod tests {
         assert_eq!(super::random_seed(), [8; 32]);
     }
 
+    #[cfg(not(target_arch = "wasm32"))]
+    #[cfg(feature = "unstable")]
+    #[test]
+    fn storage_iter_cleanup_prefix() {
+        use super::*;
+
+        crate::testing_env!(crate::test_utils::VMContextBuilder::new().build());
+        storage_write(b"m\x02", b"2");
+        storage_write(b"m\x01", b"1");
+        storage_write(b"n\x01", b"other");
+
+        // The iterator works on a snapshot, so the keys can be removed while iterating.
+        for (key, _) in storage_iter_prefix(b"m") {
+            assert!(storage_remove(&key));
+        }
+        assert_eq!(storage_iter_prefix(b"m").count(), 0);
+        assert_eq!(
+            storage_iter_range(b"", b"\xff").collect::<Vec<_>>(),
+            vec![(b"n\x01".to_vec(), b"other".to_vec())]
+        );
+        assert_eq!(storage_iter_range(b"n", b"m").count(), 0);
+    }
+
     #[cfg(not(target_arch = "wasm32"))]
     #[cfg(feature = "unstable")]
     #[test]
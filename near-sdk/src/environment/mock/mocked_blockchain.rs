@@ -1,15 +1,18 @@
-use super::Receipt;
+use super::{BlockchainSnapshot, Receipt, YieldStatus, YieldedReceipt};
 use crate::mock::MockAction;
 // TODO replace with near_vm_logic::mocks::mock_memory::MockedMemory after updating version from 0.17
 use crate::mock::mocked_memory::MockedMemory;
+use crate::test_utils::profile::{GasProfile, GasProfiler, ProfileCheckpoint};
 use crate::test_utils::VMContextBuilder;
 use crate::types::{NearToken, PromiseResult};
-use crate::VMContext;
-use near_parameters::{RuntimeConfigStore, RuntimeFeesConfig};
+use crate::{CryptoHash, Gas, VMContext};
+use near_parameters::{ExtCosts, RuntimeConfigStore, RuntimeFeesConfig};
 use near_primitives_core::version::PROTOCOL_VERSION;
-use near_vm_runner::logic::mocks::mock_external::MockedExternal;
+use near_vm_runner::logic::mocks::mock_external::{MockAction as LogicMockAction, MockedExternal};
 use near_vm_runner::logic::types::{PromiseResult as VmPromiseResult, ReceiptIndex};
-use near_vm_runner::logic::{ExecutionResultState, External, MemoryLike, VMLogic};
+use near_vm_runner::logic::{
+    ExecutionResultState, External, MemSlice, MemoryLike, ReturnData, VMLogic,
+};
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::marker::PhantomData;
@@ -28,9 +31,27 @@ where
     // We keep ownership over logic fixture so that references in `VMLogic` are valid.
     #[allow(dead_code)]
     logic_fixture: LogicFixture,
+    /// Context and promise results the blockchain was created with, to take snapshots of it.
+    context: VMContext,
+    promise_results: Vec<PromiseResult>,
+    /// Logs emitted before the state of the blockchain was restored from a snapshot.
+    restored_logs: Vec<String>,
+    /// Remaining key-values of the storage iterators, in reverse order, indexed by iterator id.
+    storage_iters: RefCell<Vec<Vec<StorageEntry>>>,
+    /// Receipt index of each promise created so far, or `None` for joint promises.
+    promise_receipts: RefCell<Vec<Option<ReceiptIndex>>>,
+    /// Value returned by the contract, or index of the receipt which result it returns.
+    return_data: RefCell<ReturnData>,
+    /// Promises yielded by the contract, including in the executions before this one, so that
+    /// they can be resumed.
+    yields: RefCell<Vec<YieldedReceipt>>,
+    /// Gas profiles recorded in profiling mode, see [`Self::enable_gas_profiling`].
+    gas_profiler: RefCell<Option<GasProfiler>>,
     _memory: PhantomData<Memory>,
 }
 
+type StorageEntry = (Vec<u8>, Vec<u8>);
+
 pub fn test_vm_config() -> near_parameters::vm::Config {
     let store = RuntimeConfigStore::test();
     let config = store.get_config(PROTOCOL_VERSION).wasm_config.as_ref().to_owned();
@@ -59,6 +80,7 @@ where
 
 struct LogicFixture {
     ext: Box<MockedExternal>,
+    config: Arc<near_parameters::vm::Config>,
     fees_config: Arc<RuntimeFeesConfig>,
     context: Box<near_vm_runner::logic::VMContext>,
     memory: Box<dyn MemoryLike>,
@@ -78,6 +100,8 @@ where
         memory: Option<Memory>,
     ) -> Self {
         let mut ext = Box::new(MockedExternal::new());
+        let sdk_context = context.clone();
+        let sdk_promise_results = promise_results.clone();
         let promise_results: Arc<[VmPromiseResult]> =
             promise_results.into_iter().map(Into::into).collect::<Vec<_>>().into();
         let context: Box<near_vm_runner::logic::VMContext> =
@@ -91,7 +115,8 @@ where
             ExecutionResultState::new(&context, context.make_gas_counter(&config), config.clone());
         let memory = Box::new(memory.unwrap_or_default());
 
-        let mut logic_fixture = LogicFixture { ext, context, fees_config, memory };
+        let mut logic_fixture =
+            LogicFixture { ext, config: config.clone(), context, fees_config, memory };
 
         let logic = unsafe {
             VMLogic::new(
@@ -104,15 +129,277 @@ where
         };
 
         let logic = RefCell::new(logic);
-        Self { logic, logic_fixture, _memory: PhantomData }
+        Self {
+            logic,
+            logic_fixture,
+            context: sdk_context,
+            promise_results: sdk_promise_results,
+            restored_logs: Vec::new(),
+            storage_iters: Default::default(),
+            promise_receipts: Default::default(),
+            return_data: RefCell::new(ReturnData::None),
+            yields: Default::default(),
+            gas_profiler: Default::default(),
+            _memory: PhantomData,
+        }
+    }
+
+    /// Creates a blockchain in the state captured by [`Self::snapshot`].
+    pub fn from_snapshot(snapshot: BlockchainSnapshot) -> Self {
+        let validators = snapshot.validators.into_iter().map(|(k, v)| (k.into(), v)).collect();
+        let mut blockchain = Self::new(
+            snapshot.context,
+            snapshot.config,
+            snapshot.fees_config,
+            snapshot.promise_results,
+            snapshot.storage,
+            validators,
+            None,
+        )
+        .with_yielded_receipts(snapshot.yields);
+        blockchain.logic_fixture.ext.action_log =
+            snapshot.actions.into_iter().map(Into::into).collect();
+        blockchain.restored_logs = snapshot.logs;
+        blockchain
+    }
+
+    /// Captures the current state of the blockchain, see [`BlockchainSnapshot`].
+    pub fn snapshot(&self) -> BlockchainSnapshot {
+        let mut logic = self.logic.borrow_mut();
+        let mut balance = [0u8; 16];
+        let mut locked_balance = [0u8; 16];
+        // The mocked memory reads and writes host pointers.
+        logic.account_balance(balance.as_mut_ptr() as _).unwrap();
+        logic.account_locked_balance(locked_balance.as_mut_ptr() as _).unwrap();
+        let mut context = self.context.clone();
+        // The attached deposit is added to the balance again when the snapshot is restored.
+        context.account_balance = NearToken::from_yoctonear(
+            u128::from_le_bytes(balance).saturating_sub(context.attached_deposit.as_yoctonear()),
+        );
+        context.account_locked_balance =
+            NearToken::from_yoctonear(u128::from_le_bytes(locked_balance));
+        context.storage_usage = logic.storage_usage().unwrap();
+        let ext = &self.logic_fixture.ext;
+        BlockchainSnapshot {
+            context,
+            config: self.logic_fixture.config.as_ref().clone(),
+            fees_config: self.logic_fixture.fees_config.as_ref().clone(),
+            promise_results: self.promise_results.clone(),
+            storage: ext.fake_trie.clone(),
+            validators: ext
+                .validators
+                .iter()
+                .map(|(k, v)| (k.as_str().parse().unwrap(), NearToken::from_yoctonear(*v)))
+                .collect(),
+            logs: self.restored_logs.iter().chain(logic.logs()).cloned().collect(),
+            actions: ext.action_log.iter().cloned().map(Into::into).collect(),
+            yields: self.yields.borrow().clone(),
+        }
     }
 
     pub fn take_storage(&mut self) -> HashMap<Vec<u8>, Vec<u8>> {
         std::mem::take(&mut self.logic_fixture.ext.fake_trie)
     }
 
+    /// Sets the promises yielded in the previous executions, which the contract can resume.
+    pub fn with_yielded_receipts(mut self, yields: Vec<YieldedReceipt>) -> Self {
+        // `MockedExternal` derives the data ids from a counter, which is advanced past the ones
+        // of the known promises to keep the data ids unique.
+        for _ in 0..yields.len() {
+            self.logic_fixture.ext.generate_data_id();
+        }
+        self.yields = RefCell::new(yields);
+        self
+    }
+
+    /// Returns the promises yielded, in the previous executions as well, to carry them over to
+    /// the next [`MockedBlockchain`] with [`Self::with_yielded_receipts`].
+    pub fn take_yielded_receipts(&mut self) -> Vec<YieldedReceipt> {
+        self.yields.take()
+    }
+
+    /// Returns the promises yielded, in the previous executions as well.
+    pub fn yielded_receipts(&self) -> Vec<YieldedReceipt> {
+        self.yields.borrow().clone()
+    }
+
+    /// Enables the profiling mode, in which the gas used and the storage changed by the code
+    /// executed from now on are recorded, see [`crate::test_utils::get_gas_profiles`].
+    pub fn enable_gas_profiling(&self) {
+        if self.gas_profiler.borrow().is_none() {
+            let start = self.profile_checkpoint();
+            self.gas_profiler.replace(Some(GasProfiler { profiles: Vec::new(), start }));
+        }
+    }
+
+    /// Sets the gas profiles recorded with the previous blockchains, and enables the profiling
+    /// mode if they are `Some`.
+    pub fn with_gas_profiles(self, profiles: Option<Vec<GasProfile>>) -> Self {
+        let profiler = profiles.map(|profiles| GasProfiler {
+            profiles,
+            // Nothing was executed yet, so the used gas can be known without reading it.
+            start: ProfileCheckpoint {
+                used_gas: 0,
+                storage_bytes: self.storage_bytes(),
+                cost_counts: ext_cost_counts(),
+            },
+        });
+        self.gas_profiler.replace(profiler);
+        self
+    }
+
+    /// Returns the gas profiles recorded in profiling mode, completed with the one of this
+    /// blockchain, to carry them over to the next [`MockedBlockchain`] with
+    /// [`Self::with_gas_profiles`].
+    pub fn take_gas_profiles(&mut self) -> Option<Vec<GasProfile>> {
+        let profiles = self.gas_profiles();
+        self.gas_profiler.replace(None);
+        profiles
+    }
+
+    /// Returns the gas profiles recorded in profiling mode, followed by the one of this
+    /// blockchain so far, or `None` if profiling is not enabled.
+    pub fn gas_profiles(&self) -> Option<Vec<GasProfile>> {
+        let start = self.gas_profiler.borrow().as_ref()?.start.clone();
+        let current = self.profile_since(&start);
+        let profiler = self.gas_profiler.borrow();
+        let mut profiles = profiler.as_ref()?.profiles.clone();
+        profiles.push(current);
+        Some(profiles)
+    }
+
+    /// Returns the current state of the gas counters, to profile the code executed from there.
+    pub(crate) fn profile_checkpoint(&self) -> ProfileCheckpoint {
+        let used_gas = self.used_gas_or_prepaid();
+        // Counted after reading the used gas, so that the base cost it charged is excluded.
+        ProfileCheckpoint {
+            used_gas,
+            storage_bytes: self.storage_bytes(),
+            cost_counts: ext_cost_counts(),
+        }
+    }
+
+    /// Returns the profile of the code executed since `start`.
+    pub(crate) fn profile_since(&self, start: &ProfileCheckpoint) -> GasProfile {
+        let ext_costs = &self.logic_fixture.config.ext_costs;
+        // Counted before reading the used gas, so that the base cost it charges is excluded.
+        let cost_counts = ext_cost_counts();
+        let used_gas = self.used_gas_or_prepaid();
+        let end = ProfileCheckpoint { used_gas, storage_bytes: self.storage_bytes(), cost_counts };
+        end.profile_since(start, ext_costs.gas_cost(ExtCosts::base), ext_costs)
+    }
+
+    /// Returns the used gas, which is all the prepaid gas if it ran out.
+    fn used_gas_or_prepaid(&self) -> u64 {
+        self.logic.borrow_mut().used_gas().unwrap_or(self.logic_fixture.context.prepaid_gas)
+    }
+
+    /// Returns the storage usage of the key-values, as accounted by the runtime.
+    fn storage_bytes(&self) -> u64 {
+        let extra_bytes =
+            self.logic_fixture.fees_config.storage_usage_config.num_extra_bytes_record;
+        self.logic_fixture
+            .ext
+            .fake_trie
+            .iter()
+            .map(|(key, value)| key.len() as u64 + value.len() as u64 + extra_bytes)
+            .sum()
+    }
+
+    /// Returns the promises yielded by this execution, along with the index of their receipt.
+    pub(crate) fn indexed_yields(&self) -> Vec<(ReceiptIndex, CryptoHash)> {
+        self.logic_fixture
+            .ext
+            .action_log
+            .iter()
+            .enumerate()
+            .filter_map(|(index, action)| match action {
+                LogicMockAction::YieldCreate { data_id, .. } => {
+                    Some((index as ReceiptIndex, data_id.0))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Runs `f` while the yielded promises are replaced by receipts to the current account in the
+    /// action log, as `MockedExternal` only knows the receiver of the latter, which is needed to
+    /// schedule a receipt after them.
+    fn with_yields_as_receipts<R>(&mut self, f: impl FnOnce(&mut VMLogic<'static>) -> R) -> R {
+        let action_log = &mut self.logic_fixture.ext.action_log;
+        let mut yields = Vec::new();
+        for (index, action) in action_log.iter_mut().enumerate() {
+            if let LogicMockAction::YieldCreate { receiver_id, .. } = action {
+                let receipt = LogicMockAction::CreateReceipt {
+                    receipt_indices: Vec::new(),
+                    receiver_id: receiver_id.clone(),
+                };
+                yields.push((index, std::mem::replace(action, receipt)));
+            }
+        }
+        let result = f(&mut self.logic.borrow_mut());
+        let action_log = &mut self.logic_fixture.ext.action_log;
+        for (index, action) in yields {
+            action_log[index] = action;
+        }
+        result
+    }
+
+    /// Records the promise yielded by the last action.
+    fn record_yield(&self) {
+        let action_log = &self.logic_fixture.ext.action_log;
+        let (index, data_id) = *self.indexed_yields().last().unwrap();
+        let function_call = action_log[index as usize..].iter().find_map(|action| match action {
+            LogicMockAction::FunctionCallWeight {
+                receipt_index,
+                method_name,
+                args,
+                prepaid_gas,
+                gas_weight,
+                ..
+            } if *receipt_index == index => Some(YieldedReceipt {
+                data_id,
+                receiver_id: self.logic_fixture.context.current_account_id.clone(),
+                method_name: String::from_utf8_lossy(method_name).into_owned(),
+                args: args.clone(),
+                prepaid_gas: Gas::from_gas(*prepaid_gas),
+                gas_weight: gas_weight.clone(),
+                status: YieldStatus::Pending,
+            }),
+            _ => None,
+        });
+        self.yields.borrow_mut().push(function_call.unwrap());
+    }
+
+    /// Resumes the promise yielded with `data_id` by the current account, and returns whether it
+    /// was waiting to be resumed.
+    fn resume_yield(&self, data_id: &[u8], payload: Vec<u8>) -> bool {
+        let current_account_id = &self.logic_fixture.context.current_account_id;
+        let mut yields = self.yields.borrow_mut();
+        let Some(yielded) = yields.iter_mut().find(|yielded| {
+            yielded.data_id == data_id && yielded.receiver_id == *current_account_id
+        }) else {
+            return false;
+        };
+        match yielded.status {
+            YieldStatus::Pending => {
+                yielded.status = YieldStatus::Resumed(payload);
+                true
+            }
+            // The callback receives the first payload.
+            YieldStatus::Resumed(_) => true,
+            YieldStatus::TimedOut => false,
+        }
+    }
+
     /// Returns metadata about the receipts created
     pub fn created_receipts(&self) -> Vec<Receipt> {
+        self.indexed_receipts().into_iter().map(|(_, receipt)| receipt).collect()
+    }
+
+    /// Returns the receipts created along with their index, which is how they are referred to
+    /// by [`Receipt::receipt_indices`] and [`Self::return_data`].
+    pub(crate) fn indexed_receipts(&self) -> Vec<(ReceiptIndex, Receipt)> {
         let action_log = &self.logic_fixture.ext.action_log;
         let action_log: Vec<MockAction> =
             action_log.clone().into_iter().map(<MockAction as From<_>>::from).collect();
@@ -142,22 +429,93 @@ where
                     })
                     .cloned()
                     .collect();
-                Receipt { receiver_id, actions, receipt_indices }
+                (receipt_idx as ReceiptIndex, Receipt { receiver_id, actions, receipt_indices })
             })
             .collect();
         result
     }
 
+    /// Returns what the contract returned with `value_return` or `promise_return`.
+    pub(crate) fn return_data(&self) -> ReturnData {
+        self.return_data.borrow().clone()
+    }
+
+    /// Creates an iterator over a snapshot of the key-values whose keys match `predicate`. The
+    /// host functions are deprecated in `VMLogic`, so the mock emulates them on its storage.
+    fn storage_iter_create(&self, predicate: impl Fn(&[u8]) -> bool) -> u64 {
+        let mut entries: Vec<_> = self
+            .logic_fixture
+            .ext
+            .fake_trie
+            .iter()
+            .filter(|(key, _)| predicate(key))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        // Sorted in descending order so that the next entry can be popped from the end.
+        entries.sort_unstable_by(|a, b| b.0.cmp(&a.0));
+        let mut storage_iters = self.storage_iters.borrow_mut();
+        storage_iters.push(entries);
+        (storage_iters.len() - 1) as u64
+    }
+
+    fn storage_iter_next(
+        &self,
+        iterator_id: u64,
+        key_register_id: u64,
+        value_register_id: u64,
+    ) -> u64 {
+        let entry = self
+            .storage_iters
+            .borrow_mut()
+            .get_mut(iterator_id as usize)
+            .unwrap_or_else(|| panic!("Iterator index {} does not exist", iterator_id))
+            .pop();
+        match entry {
+            Some((key, value)) => {
+                // The mocked memory reads host pointers, so the registers can be written from the
+                // entry's buffers directly.
+                let mut logic = self.logic.borrow_mut();
+                logic.write_register(key_register_id, key.len() as _, key.as_ptr() as _).unwrap();
+                logic
+                    .write_register(value_register_id, value.len() as _, value.as_ptr() as _)
+                    .unwrap();
+                1
+            }
+            None => 0,
+        }
+    }
+
+    fn read_memory(&self, len: u64, ptr: u64) -> Vec<u8> {
+        self.logic_fixture.memory.view_memory(MemSlice { ptr, len }).unwrap().into_owned()
+    }
+
+    /// Reads the content of a register, for the host functions passed a register instead of a
+    /// slice of memory.
+    fn read_register_value(&self, register_id: u64) -> Vec<u8> {
+        let mut logic = self.logic.borrow_mut();
+        let len = logic.register_len(register_id).unwrap();
+        let mut value = vec![0u8; len as usize];
+        logic.read_register(register_id, value.as_mut_ptr() as u64).unwrap();
+        value
+    }
+
     pub fn gas(&mut self, gas_amount: u32) {
         self.logic.borrow_mut().gas(gas_amount.into()).unwrap()
     }
 
     /// Returns logs created so far by the runtime.
     pub fn logs(&self) -> Vec<String> {
-        self.logic.borrow().logs().to_vec()
+        self.restored_logs.iter().chain(self.logic.borrow().logs()).cloned().collect()
     }
 }
 
+/// Returns the number of times each host function cost was charged on this thread.
+fn ext_cost_counts() -> HashMap<ExtCosts, u64> {
+    let mut counts = HashMap::new();
+    near_vm_runner::logic::with_ext_cost_counter(|counter| counts.clone_from(counter));
+    counts
+}
+
 fn sdk_context_to_vm_context(
     context: VMContext,
     promise_results: std::sync::Arc<[VmPromiseResult]>,
@@ -189,7 +547,8 @@ fn sdk_context_to_vm_context(
 
 #[cfg(not(target_arch = "wasm32"))]
 mod mock_chain {
-    use near_vm_runner::logic::{errors::VMLogicError, VMLogic};
+    use near_vm_runner::logic::types::ReceiptIndex;
+    use near_vm_runner::logic::{errors::VMLogicError, ReturnData, VMLogic};
 
     fn with_mock_interface<F, R>(f: F) -> R
     where
@@ -198,6 +557,20 @@ mod mock_chain {
         crate::mock::with_mocked_blockchain(|b| f(&mut b.logic.borrow_mut()).unwrap())
     }
 
+    /// Same as `with_mock_interface` for the functions creating a promise, which also records the
+    /// receipt created for it, if any, so that `promise_return` can be mapped to a receipt.
+    fn with_mock_promise<F>(creates_receipt: bool, f: F) -> u64
+    where
+        F: FnOnce(&mut VMLogic) -> Result<u64, VMLogicError>,
+    {
+        crate::mock::with_mocked_blockchain(|b| {
+            let receipt_index = b.logic_fixture.ext.action_log.len() as ReceiptIndex;
+            let promise_index = b.with_yields_as_receipts(f).unwrap();
+            b.promise_receipts.borrow_mut().push(creates_receipt.then_some(receipt_index));
+            promise_index
+        })
+    }
+
     #[no_mangle]
     extern "C-unwind" fn read_register(register_id: u64, ptr: u64) {
         with_mock_interface(|b| b.read_register(register_id, ptr))
@@ -318,7 +691,15 @@ mod mock_chain {
     }
     #[no_mangle]
     extern "C-unwind" fn value_return(value_len: u64, value_ptr: u64) {
-        with_mock_interface(|b| b.value_return(value_len, value_ptr))
+        crate::mock::with_mocked_blockchain(|b| {
+            b.logic.borrow_mut().value_return(value_len, value_ptr).unwrap();
+            let value = if value_len == u64::MAX {
+                b.read_register_value(value_ptr)
+            } else {
+                b.read_memory(value_len, value_ptr)
+            };
+            *b.return_data.borrow_mut() = ReturnData::Value(value);
+        })
     }
     #[no_mangle]
     extern "C-unwind" fn panic() -> ! {
@@ -349,7 +730,7 @@ mod mock_chain {
         amount_ptr: u64,
         gas: u64,
     ) -> u64 {
-        with_mock_interface(|b| {
+        with_mock_promise(true, |b| {
             b.promise_create(
                 account_id_len,
                 account_id_ptr,
@@ -374,7 +755,7 @@ mod mock_chain {
         amount_ptr: u64,
         gas: u64,
     ) -> u64 {
-        with_mock_interface(|b| {
+        with_mock_promise(true, |b| {
             b.promise_then(
                 promise_index,
                 account_id_len,
@@ -390,11 +771,11 @@ mod mock_chain {
     }
     #[no_mangle]
     extern "C-unwind" fn promise_and(promise_idx_ptr: u64, promise_idx_count: u64) -> u64 {
-        with_mock_interface(|b| b.promise_and(promise_idx_ptr, promise_idx_count))
+        with_mock_promise(false, |b| b.promise_and(promise_idx_ptr, promise_idx_count))
     }
     #[no_mangle]
     extern "C-unwind" fn promise_batch_create(account_id_len: u64, account_id_ptr: u64) -> u64 {
-        with_mock_interface(|b| b.promise_batch_create(account_id_len, account_id_ptr))
+        with_mock_promise(true, |b| b.promise_batch_create(account_id_len, account_id_ptr))
     }
     #[no_mangle]
     extern "C-unwind" fn promise_batch_then(
@@ -402,7 +783,9 @@ mod mock_chain {
         account_id_len: u64,
         account_id_ptr: u64,
     ) -> u64 {
-        with_mock_interface(|b| b.promise_batch_then(promise_index, account_id_len, account_id_ptr))
+        with_mock_promise(true, |b| {
+            b.promise_batch_then(promise_index, account_id_len, account_id_ptr)
+        })
     }
     #[no_mangle]
     extern "C-unwind" fn promise_batch_action_create_account(promise_index: u64) {
@@ -548,6 +931,49 @@ mod mock_chain {
         })
     }
     #[no_mangle]
+    extern "C-unwind" fn promise_yield_create(
+        function_name_len: u64,
+        function_name_ptr: u64,
+        arguments_len: u64,
+        arguments_ptr: u64,
+        gas: u64,
+        gas_weight: u64,
+        register_id: u64,
+    ) -> u64 {
+        let promise_index = with_mock_promise(true, |b| {
+            b.promise_yield_create(
+                function_name_len,
+                function_name_ptr,
+                arguments_len,
+                arguments_ptr,
+                gas,
+                gas_weight,
+                register_id,
+            )
+        });
+        crate::mock::with_mocked_blockchain(|b| b.record_yield());
+        promise_index
+    }
+    #[no_mangle]
+    extern "C-unwind" fn promise_yield_resume(
+        data_id_len: u64,
+        data_id_ptr: u64,
+        payload_len: u64,
+        payload_ptr: u64,
+    ) -> u32 {
+        crate::mock::with_mocked_blockchain(|b| {
+            // `VMLogic` only knows about the promises yielded by this execution, so its result is
+            // replaced by the one of the mock.
+            b.logic
+                .borrow_mut()
+                .promise_yield_resume(data_id_len, data_id_ptr, payload_len, payload_ptr)
+                .unwrap();
+            let data_id = b.read_memory(data_id_len, data_id_ptr);
+            let payload = b.read_memory(payload_len, payload_ptr);
+            b.resume_yield(&data_id, payload) as u32
+        })
+    }
+    #[no_mangle]
     extern "C-unwind" fn promise_results_count() -> u64 {
         with_mock_interface(|b| b.promise_results_count())
     }
@@ -557,7 +983,12 @@ mod mock_chain {
     }
     #[no_mangle]
     extern "C-unwind" fn promise_return(promise_id: u64) {
-        with_mock_interface(|b| b.promise_return(promise_id))
+        crate::mock::with_mocked_blockchain(|b| {
+            b.logic.borrow_mut().promise_return(promise_id).unwrap();
+            // Joint promises can't be returned, which `VMLogic` checked above.
+            let receipt_index = b.promise_receipts.borrow()[promise_id as usize].unwrap();
+            *b.return_data.borrow_mut() = ReturnData::ReceiptIndex(receipt_index);
+        })
     }
     #[no_mangle]
     extern "C-unwind" fn storage_write(
@@ -584,6 +1015,36 @@ mod mock_chain {
         with_mock_interface(|b| b.storage_has_key(key_len, key_ptr))
     }
     #[no_mangle]
+    extern "C-unwind" fn storage_iter_prefix(prefix_len: u64, prefix_ptr: u64) -> u64 {
+        crate::mock::with_mocked_blockchain(|b| {
+            let prefix = b.read_memory(prefix_len, prefix_ptr);
+            b.storage_iter_create(|key| key.starts_with(&prefix))
+        })
+    }
+    #[no_mangle]
+    extern "C-unwind" fn storage_iter_range(
+        start_len: u64,
+        start_ptr: u64,
+        end_len: u64,
+        end_ptr: u64,
+    ) -> u64 {
+        crate::mock::with_mocked_blockchain(|b| {
+            let start = b.read_memory(start_len, start_ptr);
+            let end = b.read_memory(end_len, end_ptr);
+            b.storage_iter_create(|key| start.as_slice() <= key && key < end.as_slice())
+        })
+    }
+    #[no_mangle]
+    extern "C-unwind" fn storage_iter_next(
+        iterator_id: u64,
+        key_register_id: u64,
+        value_register_id: u64,
+    ) -> u64 {
+        crate::mock::with_mocked_blockchain(|b| {
+            b.storage_iter_next(iterator_id, key_register_id, value_register_id)
+        })
+    }
+    #[no_mangle]
     extern "C-unwind" fn validator_stake(account_id_len: u64, account_id_ptr: u64, stake_ptr: u64) {
         with_mock_interface(|b| b.validator_stake(account_id_len, account_id_ptr, stake_ptr))
     }
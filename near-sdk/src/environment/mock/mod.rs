@@ -1,11 +1,13 @@
 mod mocked_blockchain;
 mod mocked_memory;
 mod receipt;
+mod snapshot;
 
 pub use mocked_blockchain::test_vm_config;
 
 pub use self::mocked_blockchain::MockedBlockchain;
-pub use self::receipt::{MockAction, Receipt};
+pub use self::receipt::{MockAction, Receipt, YieldStatus, YieldedReceipt};
+pub use self::snapshot::{restore, snapshot, BlockchainSnapshot};
 use core::cell::RefCell;
 
 thread_local! {
@@ -2,7 +2,7 @@ use near_primitives_core::types::GasWeight;
 use near_vm_runner::logic::mocks::mock_external::MockAction as LogicMockAction;
 use near_vm_runner::logic::types::ReceiptIndex;
 
-use crate::{AccountId, Gas, NearToken};
+use crate::{AccountId, CryptoHash, Gas, NearToken, PromiseResult};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[non_exhaustive]
@@ -12,11 +12,50 @@ pub struct Receipt {
     pub actions: Vec<MockAction>,
 }
 
-#[derive(serde::Serialize)]
+/// A function call yielded by a contract with [`Promise::new_yield`](crate::Promise::new_yield).
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[non_exhaustive]
+pub struct YieldedReceipt {
+    /// Resumption token of the yielded promise.
+    pub data_id: CryptoHash,
+    /// Account which yielded the promise, and on which the callback is executed.
+    pub receiver_id: AccountId,
+    pub method_name: String,
+    pub args: Vec<u8>,
+    pub prepaid_gas: Gas,
+    #[serde(with = "GasWeightSer")]
+    pub gas_weight: GasWeight,
+    pub status: YieldStatus,
+}
+
+impl YieldedReceipt {
+    /// Returns the promise result the callback is executed with, or [`None`] if the promise is
+    /// still waiting to be resumed.
+    pub fn promise_result(&self) -> Option<PromiseResult> {
+        match &self.status {
+            YieldStatus::Pending => None,
+            YieldStatus::Resumed(payload) => Some(PromiseResult::Successful(payload.clone())),
+            YieldStatus::TimedOut => Some(PromiseResult::Failed),
+        }
+    }
+}
+
+/// Status of a [`YieldedReceipt`].
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum YieldStatus {
+    /// The promise is waiting to be resumed.
+    Pending,
+    /// The promise was resumed with this payload.
+    Resumed(Vec<u8>),
+    /// The promise was not resumed in time.
+    TimedOut,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
 #[serde(remote = "GasWeight")]
 struct GasWeightSer(u64);
 
-#[derive(Debug, Clone, serde::Serialize, PartialEq, Eq)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
 pub enum MockAction {
     CreateReceipt {
         receipt_indices: Vec<ReceiptIndex>,
@@ -173,3 +212,66 @@ impl From<LogicMockAction> for MockAction {
         }
     }
 }
+
+impl From<MockAction> for LogicMockAction {
+    fn from(value: MockAction) -> Self {
+        match value {
+            MockAction::CreateReceipt { receipt_indices, receiver_id } => {
+                Self::CreateReceipt { receipt_indices, receiver_id }
+            }
+            MockAction::CreateAccount { receipt_index } => Self::CreateAccount { receipt_index },
+            MockAction::DeployContract { receipt_index, code } => {
+                Self::DeployContract { receipt_index, code }
+            }
+            MockAction::FunctionCallWeight {
+                receipt_index,
+                method_name,
+                args,
+                attached_deposit,
+                prepaid_gas,
+                gas_weight,
+            } => Self::FunctionCallWeight {
+                receipt_index,
+                method_name,
+                args,
+                attached_deposit: attached_deposit.as_yoctonear(),
+                prepaid_gas: prepaid_gas.as_gas(),
+                gas_weight,
+            },
+            MockAction::Transfer { receipt_index, deposit } => {
+                Self::Transfer { receipt_index, deposit: deposit.as_yoctonear() }
+            }
+            MockAction::Stake { receipt_index, stake, public_key } => {
+                Self::Stake { receipt_index, stake: stake.as_yoctonear(), public_key }
+            }
+            MockAction::DeleteAccount { receipt_index, beneficiary_id } => {
+                Self::DeleteAccount { receipt_index, beneficiary_id }
+            }
+            MockAction::DeleteKey { receipt_index, public_key } => {
+                Self::DeleteKey { receipt_index, public_key }
+            }
+            MockAction::AddKeyWithFunctionCall {
+                receipt_index,
+                public_key,
+                nonce,
+                allowance,
+                receiver_id,
+                method_names,
+            } => Self::AddKeyWithFunctionCall {
+                receipt_index,
+                public_key,
+                nonce,
+                allowance: allowance.map(|allowance| allowance.as_yoctonear()),
+                receiver_id,
+                method_names: method_names.into_iter().map(String::into_bytes).collect(),
+            },
+            MockAction::AddKeyWithFullAccess { receipt_index, public_key, nonce } => {
+                Self::AddKeyWithFullAccess { receipt_index, public_key, nonce }
+            }
+            MockAction::YieldCreate { data_id, receiver_id } => {
+                Self::YieldCreate { data_id, receiver_id }
+            }
+            MockAction::YieldResume { data, data_id } => Self::YieldResume { data, data_id },
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use super::{MockAction, YieldedReceipt};
+use crate::json_types::Base64VecU8;
+use crate::{test_vm_config, AccountId, NearToken, PromiseResult, VMContext};
+use near_parameters::RuntimeFeesConfig;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::path::Path;
+
+/// State of the [`MockedBlockchain`](super::MockedBlockchain) captured by [`snapshot`], which
+/// [`restore`] sets back.
+///
+/// Building the state of a large test fixture can be expensive, so a snapshot of it can be
+/// restored before each variant of a test instead of rebuilding it, and [saved](Self::save) to a
+/// file to be [loaded](Self::load) by other test binaries.
+///
+/// A snapshot holds the storage, the context with the balances and storage usage at the time of
+/// the snapshot, the validators, the promise results, the logs, the receipts created and the
+/// promises yielded. The gas used is not captured, so a restored blockchain starts with all the
+/// prepaid gas of the context. The VM and fees configurations are not saved to files, and the
+/// test ones are used by a loaded snapshot.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BlockchainSnapshot {
+    pub(crate) context: VMContext,
+    #[serde(skip, default = "test_vm_config")]
+    pub(crate) config: near_parameters::vm::Config,
+    #[serde(skip, default = "RuntimeFeesConfig::test")]
+    pub(crate) fees_config: RuntimeFeesConfig,
+    #[serde(with = "promise_results")]
+    pub(crate) promise_results: Vec<PromiseResult>,
+    #[serde(with = "storage")]
+    pub(crate) storage: HashMap<Vec<u8>, Vec<u8>>,
+    pub(crate) validators: HashMap<AccountId, NearToken>,
+    pub(crate) logs: Vec<String>,
+    /// Actions logged by `MockedExternal`, from which the receipts are derived.
+    pub(crate) actions: Vec<MockAction>,
+    pub(crate) yields: Vec<YieldedReceipt>,
+}
+
+impl BlockchainSnapshot {
+    /// Returns the context the restored blockchain executes with.
+    pub fn context(&self) -> &VMContext {
+        &self.context
+    }
+
+    /// Returns the key-values of the storage.
+    pub fn storage(&self) -> &HashMap<Vec<u8>, Vec<u8>> {
+        &self.storage
+    }
+
+    /// Writes the snapshot to the file at `path`, as JSON.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let file = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(file, self).map_err(io::Error::from)
+    }
+
+    /// Reads a snapshot written by [`Self::save`] from the file at `path`.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = BufReader::new(File::open(path)?);
+        serde_json::from_reader(file).map_err(io::Error::from)
+    }
+}
+
+/// Captures the state of the mocked blockchain, to be set back with [`restore`]. Only available
+/// in unit tests.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::{env, mock};
+///
+/// env::storage_write(b"key", b"fixture");
+/// let snapshot = mock::snapshot();
+///
+/// env::storage_write(b"key", b"variant");
+/// mock::restore(snapshot.clone());
+/// assert_eq!(env::storage_read(b"key"), Some(b"fixture".to_vec()));
+/// ```
+pub fn snapshot() -> BlockchainSnapshot {
+    super::with_mocked_blockchain(|b| b.snapshot())
+}
+
+/// Replaces the mocked blockchain with one in the state captured by `snapshot`. Only available in
+/// unit tests.
+pub fn restore(snapshot: BlockchainSnapshot) {
+    crate::env::set_blockchain_interface(super::MockedBlockchain::from_snapshot(snapshot));
+}
+
+/// Serializes the storage as base64 key-values, sorted for the files to be deterministic.
+mod storage {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        storage: &HashMap<Vec<u8>, Vec<u8>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut entries: Vec<_> = storage
+            .iter()
+            .map(|(key, value)| (Base64VecU8(key.clone()), Base64VecU8(value.clone())))
+            .collect();
+        entries.sort_unstable_by(|a, b| a.0 .0.cmp(&b.0 .0));
+        entries.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<HashMap<Vec<u8>, Vec<u8>>, D::Error> {
+        let entries = Vec::<(Base64VecU8, Base64VecU8)>::deserialize(deserializer)?;
+        Ok(entries.into_iter().map(|(key, value)| (key.0, value.0)).collect())
+    }
+}
+
+/// Serializes the promise results as base64 values, or `null` for the failed ones.
+mod promise_results {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        results: &[PromiseResult],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        results
+            .iter()
+            .map(|result| match result {
+                PromiseResult::Successful(value) => Some(Base64VecU8(value.clone())),
+                PromiseResult::Failed => None,
+            })
+            .collect::<Vec<_>>()
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<PromiseResult>, D::Error> {
+        let results = Vec::<Option<Base64VecU8>>::deserialize(deserializer)?;
+        Ok(results
+            .into_iter()
+            .map(|result| match result {
+                Some(value) => PromiseResult::Successful(value.0),
+                None => PromiseResult::Failed,
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{accounts, get_created_receipts, get_logs, VMContextBuilder};
+    use crate::{env, testing_env, Gas, Promise};
+
+    fn build_fixture() {
+        let context = VMContextBuilder::new()
+            .current_account_id(accounts(0))
+            .account_balance(NearToken::from_near(10))
+            .attached_deposit(NearToken::from_near(1))
+            .storage_usage(0)
+            .build();
+        testing_env!(context);
+        for i in 0..10u8 {
+            env::storage_write(&[i], &[i; 4]);
+        }
+        env::log_str("fixture");
+        Promise::new(accounts(1))
+            .transfer(NearToken::from_near(2))
+            .then(Promise::new(accounts(0)).function_call(
+                "callback".into(),
+                vec![],
+                NearToken::from_near(0),
+                Gas::from_tgas(5),
+            ))
+            .as_return();
+    }
+
+    fn assert_fixture() {
+        assert_eq!(env::storage_read(&[3]), Some(vec![3; 4]));
+        assert_eq!(env::storage_read(&[10]), None);
+        assert_eq!(env::account_balance(), NearToken::from_near(9));
+        assert_eq!(env::attached_deposit(), NearToken::from_near(1));
+        assert_eq!(env::storage_usage(), 10 * (1 + 4 + 40));
+        assert_eq!(get_logs(), vec!["fixture".to_string()]);
+        let receipts = get_created_receipts();
+        assert_eq!(receipts.len(), 2);
+        assert_eq!(receipts[1].receipt_indices, vec![0]);
+    }
+
+    #[test]
+    fn snapshot_and_restore() {
+        build_fixture();
+        let snapshot = snapshot();
+        assert_fixture();
+
+        env::storage_write(&[10], b"variant");
+        env::storage_remove(&[3]);
+        env::log_str("variant");
+        Promise::new(accounts(2)).transfer(NearToken::from_near(1));
+
+        restore(snapshot.clone());
+        assert_fixture();
+
+        // Promises created after the restore are indexed after the restored receipts.
+        Promise::new(accounts(2)).transfer(NearToken::from_near(1));
+        let receipts = get_created_receipts();
+        assert_eq!(receipts.len(), 3);
+        assert_eq!(receipts[2].receiver_id, accounts(2));
+
+        restore(snapshot);
+        assert_fixture();
+    }
+
+    #[test]
+    fn save_and_load() {
+        build_fixture();
+        let path = std::env::temp_dir().join(format!("near-sdk-snapshot-{}.json", line!()));
+        snapshot().save(&path).unwrap();
+        testing_env!(VMContextBuilder::new().build());
+
+        let snapshot = BlockchainSnapshot::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(snapshot.context().current_account_id, accounts(0));
+        restore(snapshot);
+        assert_fixture();
+    }
+}
@@ -9,8 +9,8 @@
 extern crate quickcheck;
 
 pub use near_sdk_macros::{
-    ext_contract, near, near_bindgen, BorshStorageKey, EventMetadata, FunctionError, NearSchema,
-    PanicOnDefault,
+    ext_contract, ext_contract_from_abi, near, near_bindgen, BorshStorageKey, EventMetadata,
+    FunctionError, NearSchema, PanicOnDefault,
 };
 
 pub mod store;
@@ -24,7 +24,9 @@ pub use environment::env;
 pub use near_sys as sys;
 
 mod promise;
-pub use promise::{Allowance, Promise, PromiseOrValue};
+pub use promise::{
+    Allowance, GasPlan, GasPlanner, GasStep, GasStepId, Promise, PromiseOrValue, YieldedPromise,
+};
 
 // Private types just used within macro generation, not stable to be used.
 #[doc(hidden)]
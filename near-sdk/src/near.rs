@@ -145,3 +145,208 @@ pub fn result_serializer() {}
 /// }
 /// ```
 pub fn handle_result() {}
+
+/// Callback results inner [`#[near]`](../attr.near.html) annotation.
+///
+/// An argument marked with `#[callback_result]` must be a `Result<T, PromiseError>`, and receives
+/// the result of the promise at the same position. Instead of panicking, the callback gets a
+/// [`PromiseError`](crate::PromiseError) when the promise failed, when its result is longer than
+/// `max_len` bytes, or when the result can't be deserialized into `T`. `max_len` is optional and
+/// limits how much of the result is read into memory.
+///
+/// # Examples
+///
+/// ```rust
+/// use near_sdk::{log, near, PromiseError};
+///
+/// #[near(contract_state)]
+/// #[derive(Default)]
+/// pub struct Counter {
+///     val: u64,
+/// }
+///
+/// #[near]
+/// impl Counter {
+///     #[private]
+///     pub fn on_get_value(
+///         &mut self,
+///         #[callback_result(max_len = 64)] value: Result<u64, PromiseError>,
+///     ) {
+///         match value {
+///             Ok(value) => self.val = value,
+///             Err(PromiseError::DeserializationFailed(err)) => log!("Invalid value: {}", err),
+///             Err(err) => log!("Failed to get the value: {}", err),
+///         }
+///     }
+/// }
+/// ```
+pub fn callback_result() {}
+
+/// Method guards inner [`#[near]`](../attr.near.html) annotation.
+///
+/// `#[near(guard = "path")]` calls the function at `path` before the arguments of the method are
+/// deserialized, so that unauthorized calls fail early. The guard is expected to panic if the
+/// method can't be called. It receives the contract by reference when the method has a `self`
+/// receiver, and no arguments otherwise.
+///
+/// `#[near(owner_only)]` is a shorthand for calling `Self::assert_owner(&self)`, and
+/// `#[near(role = "name")]` for calling `Self::assert_role(&self, "name")`, both of which have to
+/// be implemented by the contract. Guards are called in the order they are declared, and are
+/// listed along with the deposit and gas requirements of the method in a `near-requirements` JSON
+/// block at the end of its doc in the ABI.
+///
+/// # Examples
+///
+/// ```rust
+/// use near_sdk::{env, near, require, AccountId};
+///
+/// #[near(contract_state)]
+/// #[derive(Default)]
+/// pub struct Counter {
+///     owner_id: Option<AccountId>,
+///     paused: bool,
+///     val: u64,
+/// }
+///
+/// #[near]
+/// impl Counter {
+///     #[near(owner_only, guard = "Self::assert_not_paused")]
+///     pub fn reset(&mut self) {
+///         self.val = 0;
+///     }
+///
+///     fn assert_owner(&self) {
+///         require!(self.owner_id == Some(env::predecessor_account_id()), "Not the owner");
+///     }
+///
+///     fn assert_not_paused(&self) {
+///         require!(!self.paused, "Paused");
+///     }
+/// }
+/// ```
+pub fn guard() {}
+
+/// Deposit requirement inner [`#[near]`](../attr.near.html) annotation.
+///
+/// `#[deposit(min = "amount")]` and `#[deposit(exact = "amount")]` make the method panic when the
+/// attached deposit is respectively less than or different from `amount`, before its arguments
+/// are deserialized. The amount is a number followed by one of `yoctoNEAR` (or `yocto`),
+/// `milliNEAR` or `NEAR`. The method accepts a deposit without being marked `#[payable]`.
+///
+/// # Examples
+///
+/// ```rust
+/// use near_sdk::near;
+///
+/// #[near(contract_state)]
+/// #[derive(Default)]
+/// pub struct Counter {
+///     val: u64,
+/// }
+///
+/// #[near]
+/// impl Counter {
+///     #[deposit(exact = "1 yoctoNEAR")]
+///     pub fn increment(&mut self) {
+///         self.val += 1;
+///     }
+///
+///     #[deposit(min = "0.1 NEAR")]
+///     pub fn increment_by(&mut self, value: u64) {
+///         self.val += value;
+///     }
+/// }
+/// ```
+pub fn deposit() {}
+
+/// Gas requirement inner [`#[near]`](../attr.near.html) annotation.
+///
+/// `#[gas(min = "amount")]` makes the method panic when less than `amount` of gas is attached to
+/// the call, before its arguments are deserialized. This is useful for methods creating promises
+/// which need enough gas for their callbacks. The amount is a number followed by one of `gas`,
+/// `Ggas` or `Tgas`.
+///
+/// # Examples
+///
+/// ```rust
+/// use near_sdk::{near, AccountId, Gas, Promise};
+///
+/// #[near(contract_state)]
+/// #[derive(Default)]
+/// pub struct Counter {
+///     val: u64,
+/// }
+///
+/// #[near]
+/// impl Counter {
+///     #[gas(min = "30 Tgas")]
+///     pub fn notify(&self, account_id: AccountId) -> Promise {
+///         Promise::new(account_id).function_call(
+///             "on_notify".to_string(),
+///             vec![],
+///             near_sdk::NearToken::from_near(0),
+///             Gas::from_tgas(20),
+///         )
+///     }
+/// }
+/// ```
+pub fn gas() {}
+
+/// Callback locks inner [`#[near]`](../attr.near.html) annotation.
+///
+/// `#[near(lock = "arg")]` acquires the [`CallbackLock`](crate::CallbackLock) of the key held by
+/// the argument `arg` once the arguments are deserialized, failing if it's already locked. The
+/// method must return the `Promise` chain it launches, or a `PromiseOrValue`. The lock is then
+/// released by a private method generated along with the method, `__near_release_lock_<method>`,
+/// which is called with 5 Tgas once the chain is executed, whether its callback succeeds, gets a
+/// `PromiseError` or panics. The result of the method is still the one of the chain. When a value
+/// is returned instead of a promise, the lock is released right away.
+///
+/// The methods which must not touch the key while the chain is pending reject the calls with
+/// `#[near(reject_locked = "arg")]`.
+///
+/// # Examples
+///
+/// ```rust
+/// use near_sdk::{env, near, AccountId, Gas, NearToken, Promise, PromiseError};
+/// use std::collections::HashMap;
+///
+/// #[near(contract_state)]
+/// #[derive(Default)]
+/// pub struct Vault {
+///     balances: HashMap<AccountId, NearToken>,
+/// }
+///
+/// #[near]
+/// impl Vault {
+///     #[near(lock = "account_id")]
+///     pub fn withdraw(&mut self, account_id: AccountId) -> Promise {
+///         let amount = self.balances.remove(&account_id).unwrap_or_default();
+///         Promise::new(account_id.clone()).transfer(amount).then(
+///             Self::ext(env::current_account_id())
+///                 .with_static_gas(Gas::from_tgas(5))
+///                 .on_withdrawn(account_id, amount),
+///         )
+///     }
+///
+///     #[private]
+///     pub fn on_withdrawn(
+///         &mut self,
+///         account_id: AccountId,
+///         amount: NearToken,
+///         #[callback_result] result: Result<(), PromiseError>,
+///     ) {
+///         if result.is_err() {
+///             self.balances.insert(account_id, amount);
+///         }
+///     }
+///
+///     #[payable]
+///     #[near(reject_locked = "account_id")]
+///     pub fn deposit(&mut self, account_id: AccountId) {
+///         let balance = self.balances.entry(account_id).or_default();
+///         *balance = balance.saturating_add(env::attached_deposit());
+///     }
+/// }
+/// ```
+pub fn lock() {}
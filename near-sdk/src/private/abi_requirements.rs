@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Gas, NearToken};
+
+/// Language of the code block holding the requirements of a function in its ABI doc.
+pub const REQUIREMENTS_DOC_BLOCK: &str = "near-requirements";
+
+/// Requirements declared with `#[near(guard = "...")]`, `#[deposit(...)]`, `#[gas(...)]` and the
+/// like on a method, which a call has to meet. They are listed in the doc of the function in the
+/// ABI, see [`append_to_doc`](Self::append_to_doc).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AbiFunctionRequirements {
+    /// Requirement on the attached deposit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deposit: Option<AbiDepositRequirement>,
+    /// Minimum prepaid gas.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_gas: Option<Gas>,
+    /// Checks made before the arguments are deserialized, in the order they are made.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub guards: Vec<AbiGuard>,
+    /// Operations on the `CallbackLock` of arguments.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub locks: Vec<AbiLockOperation>,
+}
+
+impl AbiFunctionRequirements {
+    /// Appends the requirements to the doc of a function, as a JSON code block.
+    ///
+    /// The doc is the only field of a function which is kept as is when the ABI chunks are
+    /// combined, as the functions of `near_abi` reject unknown fields. The requirements can be
+    /// read back with [`from_doc`](Self::from_doc).
+    pub fn append_to_doc(&self, doc: Option<String>) -> String {
+        let requirements = serde_json::to_string(self).unwrap();
+        let block = format!("```{}\n{}\n```", REQUIREMENTS_DOC_BLOCK, requirements);
+        match doc {
+            Some(doc) => format!("{}\n\n{}", doc, block),
+            None => block,
+        }
+    }
+
+    /// Reads the requirements appended to the doc of a function by
+    /// [`append_to_doc`](Self::append_to_doc), if any.
+    pub fn from_doc(doc: &str) -> Option<Self> {
+        let start = format!("```{}\n", REQUIREMENTS_DOC_BLOCK);
+        let (_, block) = doc.rsplit_once(&start)?;
+        let (requirements, _) = block.split_once("\n```")?;
+        serde_json::from_str(requirements).ok()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AbiDepositRequirement {
+    Min(NearToken),
+    Exact(NearToken),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AbiGuard {
+    /// A function which panics if the method can't be called, by path.
+    Function(String),
+    /// The caller must be the owner.
+    Owner,
+    /// The caller must have the role.
+    Role(String),
+}
+
+/// An operation on the `CallbackLock` of the key held by an argument, by name of the argument.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AbiLockOperation {
+    Lock(String),
+    RejectLocked(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn doc_round_trip() {
+        let requirements = AbiFunctionRequirements {
+            deposit: Some(AbiDepositRequirement::Exact(NearToken::from_yoctonear(1))),
+            guards: vec![AbiGuard::Owner],
+            ..Default::default()
+        };
+        let doc = requirements.append_to_doc(Some(" Does things.".to_string()));
+        assert_eq!(
+            doc,
+            " Does things.\n\n```near-requirements\n{\"deposit\":{\"exact\":\"1\"},\"guards\":[\"owner\"]}\n```"
+        );
+        assert_eq!(AbiFunctionRequirements::from_doc(&doc), Some(requirements.clone()));
+        assert_eq!(
+            AbiFunctionRequirements::from_doc(&requirements.append_to_doc(None)),
+            Some(requirements)
+        );
+        assert_eq!(AbiFunctionRequirements::from_doc(" Does things."), None);
+    }
+}
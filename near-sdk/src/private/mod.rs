@@ -6,11 +6,21 @@ pub use near_abi::{
     AbiParameters, AbiType,
 };
 #[cfg(feature = "abi")]
+mod abi_requirements;
+#[cfg(feature = "abi")]
+pub use abi_requirements::{
+    AbiDepositRequirement, AbiFunctionRequirements, AbiGuard, AbiLockOperation,
+    REQUIREMENTS_DOC_BLOCK,
+};
+#[cfg(feature = "abi")]
 mod result_type_ext;
 
 #[cfg(feature = "abi")]
 pub use result_type_ext::ResultTypeExt;
 
+pub use crate::utils::callback_lock::{
+    acquire_lock, release_lock, ReleaseLockAfter, RELEASE_LOCK_METHOD_PREFIX,
+};
 use crate::IntoStorageKey;
 use borsh::{to_vec, BorshSerialize};
 
@@ -43,3 +53,27 @@ where
         to_vec(&self).unwrap()
     }
 }
+
+pub use crate::store::prefixes::field_path;
+use crate::store::{StoragePrefix, StoragePrefixes};
+
+/// Wraps a field of a `#[near]` struct for the derived [`StoragePrefixes`] implementation to
+/// collect the prefixes of the fields implementing it and skip the others, by method resolution
+/// through [`CollectStoragePrefixes`] before the auto-referenced [`SkipStoragePrefixes`].
+pub struct PrefixProbe<'a, T: ?Sized>(pub &'a T);
+
+pub trait CollectStoragePrefixes {
+    fn collect_storage_prefixes(&self, path: &str, prefixes: &mut Vec<StoragePrefix>);
+}
+
+impl<T: StoragePrefixes + ?Sized> CollectStoragePrefixes for PrefixProbe<'_, T> {
+    fn collect_storage_prefixes(&self, path: &str, prefixes: &mut Vec<StoragePrefix>) {
+        self.0.storage_prefixes(path, prefixes);
+    }
+}
+
+pub trait SkipStoragePrefixes {
+    fn collect_storage_prefixes(&self, _path: &str, _prefixes: &mut Vec<StoragePrefix>) {}
+}
+
+impl<T: ?Sized> SkipStoragePrefixes for &PrefixProbe<'_, T> {}
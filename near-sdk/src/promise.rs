@@ -8,7 +8,7 @@ use std::num::NonZeroU128;
 use std::rc::Rc;
 
 use crate::env::migrate_to_allowance;
-use crate::{AccountId, Gas, GasWeight, NearToken, PromiseIndex, PublicKey};
+use crate::{AccountId, CryptoHash, Gas, GasWeight, NearToken, PromiseIndex, PublicKey};
 
 /// Allow an access key to spend either an unlimited or limited amount of gas
 // This wrapper prevents incorrect construction
@@ -245,6 +245,8 @@ impl BorshSchema for Promise {
 enum PromiseSubtype {
     Single(Rc<PromiseSingle>),
     Joint(Rc<PromiseJoint>),
+    /// Yielded promises are created eagerly, as their data id is needed to resume them.
+    Yielded(PromiseIndex),
 }
 
 impl Promise {
@@ -261,12 +263,80 @@ impl Promise {
         }
     }
 
+    /// Create a promise that executes `function_name` on the current account with `arguments`,
+    /// once it is resumed with [`YieldedPromise::resume`] or times out, after 200 blocks.
+    ///
+    /// The callback receives a single promise result, which is the payload it was resumed with,
+    /// or a failure if it timed out. `gas` is attached to the callback, along with a share of the
+    /// unused gas of the default weight, see [`Promise::function_call_weight`].
+    ///
+    /// Unlike other promises, the yielded promise is created right away, and actions can't be
+    /// added to it.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use near_sdk::{env, near, Gas, Promise, PromiseError};
+    /// #[near(contract_state)]
+    /// #[derive(Default)]
+    /// struct Oracle {
+    ///     pending: Option<near_sdk::CryptoHash>,
+    /// }
+    ///
+    /// #[near]
+    /// impl Oracle {
+    ///     pub fn request(&mut self) -> Promise {
+    ///         let yielded = Promise::new_yield("on_response", vec![], Gas::from_tgas(10));
+    ///         self.pending = Some(yielded.data_id());
+    ///         yielded.into()
+    ///     }
+    ///
+    ///     pub fn respond(&mut self, price: u64) {
+    ///         let data_id = self.pending.take().expect("No pending request");
+    ///         Promise::resume(&data_id, &price);
+    ///     }
+    ///
+    ///     #[private]
+    ///     pub fn on_response(&self, #[callback_result] price: Result<u64, PromiseError>) -> u64 {
+    ///         price.unwrap_or_else(|_| env::panic_str("The request timed out"))
+    ///     }
+    /// }
+    /// ```
+    pub fn new_yield(function_name: &str, arguments: Vec<u8>, gas: Gas) -> YieldedPromise {
+        let (promise_index, data_id) = crate::env::promise_yield_create_with_data_id(
+            function_name,
+            &arguments,
+            gas,
+            GasWeight::default(),
+        );
+        YieldedPromise {
+            promise: Promise {
+                subtype: PromiseSubtype::Yielded(promise_index),
+                should_return: RefCell::new(false),
+            },
+            data_id,
+        }
+    }
+
+    /// Resumes the promise yielded with `data_id` by [`Promise::new_yield`] on the current account,
+    /// passing `value` serialized to JSON to its callback.
+    ///
+    /// Returns `false` if there is no such promise waiting to be resumed. If it is resumed several
+    /// times, the callback receives the first value.
+    pub fn resume<T: serde::Serialize>(data_id: &CryptoHash, value: &T) -> bool {
+        let value = serde_json::to_vec(value)
+            .unwrap_or_else(|_| crate::env::panic_str("Failed to serialize the value"));
+        crate::env::promise_yield_resume(data_id, &value)
+    }
+
     fn add_action(self, action: PromiseAction) -> Self {
         match &self.subtype {
             PromiseSubtype::Single(x) => x.actions.borrow_mut().push(action),
             PromiseSubtype::Joint(_) => {
                 crate::env::panic_str("Cannot add action to a joint promise.")
             }
+            PromiseSubtype::Yielded(_) => {
+                crate::env::panic_str("Cannot add action to a yielded promise.")
+            }
         }
         self
     }
@@ -312,6 +382,25 @@ impl Promise {
         })
     }
 
+    /// Same as [`Promise::function_call_weight`], calling the function of the `step` of `plan` with
+    /// its gas and weight, see [`GasPlanner`].
+    pub fn function_call_planned(
+        self,
+        plan: &GasPlan,
+        step: GasStepId,
+        arguments: Vec<u8>,
+        amount: NearToken,
+    ) -> Self {
+        let step = plan.step(step);
+        self.function_call_weight(
+            step.function_name.clone(),
+            arguments,
+            amount,
+            step.min_gas,
+            GasWeight(step.weight),
+        )
+    }
+
     /// Transfer tokens to the account that this promise acts on.
     pub fn transfer(self, amount: NearToken) -> Self {
         self.add_action(PromiseAction::Transfer { amount })
@@ -459,6 +548,7 @@ impl Promise {
                 *after = Some(self)
             }
             PromiseSubtype::Joint(_) => crate::env::panic_str("Cannot callback joint promise."),
+            PromiseSubtype::Yielded(_) => crate::env::panic_str("Cannot callback yielded promise."),
         }
         other
     }
@@ -495,10 +585,11 @@ impl Promise {
         self
     }
 
-    fn construct_recursively(&self) -> PromiseIndex {
+    pub(crate) fn construct_recursively(&self) -> PromiseIndex {
         let res = match &self.subtype {
             PromiseSubtype::Single(x) => x.construct_recursively(),
             PromiseSubtype::Joint(x) => x.construct_recursively(),
+            PromiseSubtype::Yielded(promise_index) => *promise_index,
         };
         if *self.should_return.borrow() {
             crate::env::promise_return(res);
@@ -546,6 +637,167 @@ impl schemars::JsonSchema for Promise {
     }
 }
 
+/// A promise created with [`Promise::new_yield`], waiting to be resumed.
+///
+/// It is converted into a [`Promise`] to be returned, or to schedule callbacks after it.
+pub struct YieldedPromise {
+    promise: Promise,
+    data_id: CryptoHash,
+}
+
+impl YieldedPromise {
+    /// Returns the resumption token to pass to [`Promise::resume`], which is only valid on the
+    /// current account.
+    pub fn data_id(&self) -> CryptoHash {
+        self.data_id
+    }
+
+    /// Schedules `other` after the callback of the yielded promise, see [`Promise::then`].
+    pub fn then(self, other: Promise) -> Promise {
+        self.promise.then(other)
+    }
+}
+
+impl From<YieldedPromise> for Promise {
+    fn from(yielded: YieldedPromise) -> Self {
+        yielded.promise
+    }
+}
+
+/// Declares the gas needed by each function call of a chain of promises, to check that enough gas
+/// is left to schedule all of them, instead of hardcoding the gas of each call.
+///
+/// Each step has a minimum gas, which is attached to the function call as static gas, and a
+/// weight, which decides the share of the unused gas it receives at the end of the execution.
+/// Gas the current function still needs after scheduling the promises, including the cost of
+/// creating them, can be set aside with [`GasPlanner::reserve`].
+///
+/// # Examples
+/// ```
+/// # use near_sdk::{test_utils::VMContextBuilder, testing_env};
+/// # testing_env!(VMContextBuilder::new().prepaid_gas(near_sdk::Gas::from_tgas(100)).build());
+/// use near_sdk::{Gas, GasPlanner, NearToken, Promise};
+///
+/// let mut planner = GasPlanner::new();
+/// let on_transfer = planner.step("ft_on_transfer", Gas::from_tgas(10), 1);
+/// let resolve = planner.step("ft_resolve_transfer", Gas::from_tgas(5), 0);
+/// let plan = planner.reserve(Gas::from_tgas(5)).plan();
+///
+/// Promise::new("receiver.near".parse().unwrap())
+///     .function_call_planned(&plan, on_transfer, vec![], NearToken::from_near(0))
+///     .then(Promise::new("token.near".parse().unwrap()).function_call_planned(
+///         &plan,
+///         resolve,
+///         vec![],
+///         NearToken::from_near(0),
+///     ));
+/// ```
+///
+/// A function can be planned several times, each step being identified by the [`GasStepId`]
+/// returned when it is added. The steps can also be used with the builders generated by
+/// [`ext_contract`](crate::ext_contract), with `with_static_gas(step.min_gas)` and
+/// `with_unused_gas_weight(step.weight)`.
+#[derive(Clone, Debug, Default)]
+pub struct GasPlanner {
+    steps: Vec<GasStep>,
+    reserved: Gas,
+}
+
+impl GasPlanner {
+    /// Creates a planner without any step.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a call of `function_name` which needs at least `min_gas`, and receives a share of the
+    /// unused gas proportional to `weight`. Returns the id of the step in the plan.
+    pub fn step(
+        &mut self,
+        function_name: impl Into<String>,
+        min_gas: Gas,
+        weight: u64,
+    ) -> GasStepId {
+        self.steps.push(GasStep { function_name: function_name.into(), min_gas, weight });
+        GasStepId(self.steps.len() - 1)
+    }
+
+    /// Sets aside gas for the current function after the promises are scheduled.
+    pub fn reserve(mut self, gas: Gas) -> Self {
+        self.reserved = self.reserved.saturating_add(gas);
+        self
+    }
+
+    /// Returns the gas needed by all the steps and the reserved gas.
+    pub fn required_gas(&self) -> Gas {
+        self.steps.iter().fold(self.reserved, |total, step| total.saturating_add(step.min_gas))
+    }
+
+    /// Checks that the gas left covers all the steps and the reserved gas, and returns the plan.
+    ///
+    /// # Panics
+    ///
+    /// Panics with the gas needed by each step if there is not enough gas left.
+    pub fn plan(self) -> GasPlan {
+        let available = crate::env::prepaid_gas().saturating_sub(crate::env::used_gas());
+        let required = self.required_gas();
+        if available < required {
+            let breakdown: Vec<String> = self
+                .steps
+                .iter()
+                .map(|step| format!("{}: {}", step.function_name, step.min_gas))
+                .chain(std::iter::once(format!("reserved: {}", self.reserved)))
+                .collect();
+            crate::env::panic_str(&format!(
+                "Not enough gas for the promise chain: {} required ({}), {} available",
+                required,
+                breakdown.join(", "),
+                available
+            ));
+        }
+        GasPlan { steps: self.steps }
+    }
+}
+
+/// Gas of the function calls of a chain of promises, checked by [`GasPlanner::plan`].
+#[derive(Clone, Debug)]
+pub struct GasPlan {
+    steps: Vec<GasStep>,
+}
+
+impl GasPlan {
+    /// Returns the step `id`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` was returned by a planner with fewer steps than this plan.
+    pub fn step(&self, id: GasStepId) -> &GasStep {
+        self.steps
+            .get(id.0)
+            .unwrap_or_else(|| crate::env::panic_str(&format!("Gas step {} is not declared", id.0)))
+    }
+
+    /// Returns the steps, in the order they were added.
+    pub fn steps(&self) -> &[GasStep] {
+        &self.steps
+    }
+}
+
+/// Identifies a step of a [`GasPlan`], returned by [`GasPlanner::step`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct GasStepId(usize);
+
+/// A function call of a [`GasPlan`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct GasStep {
+    /// Function called, also used to label the step when there is not enough gas.
+    pub function_name: String,
+    /// Static gas attached to the function call.
+    pub min_gas: Gas,
+    /// Weight of the function call in the distribution of the unused gas.
+    pub weight: u64,
+}
+
 /// When the method can return either a promise or a value, it can be called with `PromiseOrValue::Promise`
 /// or `PromiseOrValue::Value` to specify which one should be returned.
 /// # Example
@@ -592,6 +844,12 @@ impl<T> From<Promise> for PromiseOrValue<T> {
     }
 }
 
+impl<T> From<YieldedPromise> for PromiseOrValue<T> {
+    fn from(yielded: YieldedPromise) -> Self {
+        PromiseOrValue::Promise(yielded.into())
+    }
+}
+
 impl<T: borsh::BorshSerialize> borsh::BorshSerialize for PromiseOrValue<T> {
     fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
         match self {
@@ -617,12 +875,12 @@ impl<T: schemars::JsonSchema> schemars::JsonSchema for PromiseOrValue<T> {
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(test)]
 mod tests {
-    use crate::mock::MockAction;
-    use crate::test_utils::get_created_receipts;
+    use crate::mock::{MockAction, YieldStatus};
     use crate::test_utils::test_env::{alice, bob};
+    use crate::test_utils::{get_created_receipts, get_yielded_receipts};
     use crate::{
-        test_utils::VMContextBuilder, testing_env, AccountId, Allowance, NearToken, Promise,
-        PublicKey,
+        test_utils::VMContextBuilder, testing_env, AccountId, Allowance, Gas, GasPlanner,
+        NearToken, Promise, PromiseResult, PublicKey,
     };
 
     fn pk() -> PublicKey {
@@ -842,4 +1100,112 @@ mod tests {
         });
         assert!(has_action);
     }
+
+    #[test]
+    fn test_yield_and_resume() {
+        testing_env!(VMContextBuilder::new().current_account_id(alice()).build());
+
+        let yielded = Promise::new_yield("on_resume", b"{}".to_vec(), Gas::from_tgas(10));
+        let data_id = yielded.data_id();
+        Promise::from(yielded).then(Promise::new(bob()).transfer(NearToken::from_yoctonear(1)));
+        let yields = get_yielded_receipts();
+        assert_eq!(yields.len(), 1);
+        assert_eq!(yields[0].data_id, data_id);
+        assert_eq!(yields[0].method_name, "on_resume");
+        assert_eq!(yields[0].status, YieldStatus::Pending);
+        // The callback is not a receipt until it is resumed, but can be depended upon.
+        let receipts = get_created_receipts();
+        assert_eq!(receipts.len(), 1);
+        assert_eq!(receipts[0].receiver_id, bob());
+
+        // Only the account which yielded the promise can resume it.
+        testing_env!(VMContextBuilder::new().current_account_id(bob()).build());
+        assert!(!Promise::resume(&data_id, &42));
+        testing_env!(VMContextBuilder::new().current_account_id(alice()).build());
+        assert!(!Promise::resume(&[0; 32], &42));
+        assert!(Promise::resume(&data_id, &42));
+        assert!(Promise::resume(&data_id, &43));
+        let yields = get_yielded_receipts();
+        assert_eq!(yields[0].status, YieldStatus::Resumed(b"42".to_vec()));
+        assert_eq!(yields[0].promise_result(), Some(PromiseResult::Successful(b"42".to_vec())));
+
+        // Promises yielded later get a different data id.
+        let yielded = Promise::new_yield("on_resume", vec![], Gas::from_tgas(10));
+        assert_ne!(yielded.data_id(), data_id);
+    }
+
+    #[test]
+    fn test_gas_plan() {
+        testing_env!(VMContextBuilder::new().prepaid_gas(Gas::from_tgas(30)).build());
+
+        let mut planner = GasPlanner::new();
+        let on_transfer = planner.step("on_transfer", Gas::from_tgas(10), 2);
+        let resolve = planner.step("resolve_transfer", Gas::from_tgas(5), 1);
+        let plan = planner.reserve(Gas::from_tgas(5)).plan();
+        assert_eq!(plan.steps().len(), 2);
+        assert_eq!(plan.step(resolve).function_name, "resolve_transfer");
+
+        Promise::new(bob()).function_call_planned(
+            &plan,
+            on_transfer,
+            vec![],
+            NearToken::from_near(0),
+        );
+        let has_action = get_actions().any(|el| {
+            matches!(
+                el,
+                MockAction::FunctionCallWeight { method_name, prepaid_gas, gas_weight, .. }
+                    if method_name == b"on_transfer"
+                        && prepaid_gas == Gas::from_tgas(10)
+                        && gas_weight.0 == 2
+            )
+        });
+        assert!(has_action);
+    }
+
+    #[test]
+    fn test_gas_plan_repeated_function() {
+        testing_env!(VMContextBuilder::new().prepaid_gas(Gas::from_tgas(30)).build());
+
+        let mut planner = GasPlanner::new();
+        let first = planner.step("ft_transfer", Gas::from_tgas(10), 1);
+        let second = planner.step("ft_transfer", Gas::from_tgas(5), 3);
+        let plan = planner.plan();
+        assert_ne!(first, second);
+
+        Promise::new(bob())
+            .function_call_planned(&plan, first, vec![], NearToken::from_near(0))
+            .function_call_planned(&plan, second, vec![], NearToken::from_near(0));
+        let weights: Vec<_> = get_actions()
+            .filter_map(|el| match el {
+                MockAction::FunctionCallWeight { method_name, prepaid_gas, gas_weight, .. } => {
+                    assert_eq!(method_name, b"ft_transfer");
+                    Some((prepaid_gas, gas_weight.0))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(weights, [(Gas::from_tgas(10), 1), (Gas::from_tgas(5), 3)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Not enough gas for the promise chain: 25.0 Tgas required \
+        (on_transfer: 15.0 Tgas, resolve_transfer: 5.0 Tgas, reserved: 5.0 Tgas)")]
+    fn test_gas_plan_not_enough_gas() {
+        testing_env!(VMContextBuilder::new().prepaid_gas(Gas::from_tgas(20)).build());
+
+        let mut planner = GasPlanner::new();
+        planner.step("on_transfer", Gas::from_tgas(15), 1);
+        planner.step("resolve_transfer", Gas::from_tgas(5), 0);
+        planner.reserve(Gas::from_tgas(5)).plan();
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot add action to a yielded promise.")]
+    fn test_yield_add_action() {
+        testing_env!(VMContextBuilder::new().current_account_id(alice()).build());
+
+        Promise::from(Promise::new_yield("on_resume", vec![], Gas::from_tgas(10)))
+            .transfer(NearToken::from_yoctonear(1));
+    }
 }
@@ -0,0 +1,249 @@
+use crate::env;
+use crate::store::prefixes::StoragePrefixes;
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, BTreeSet};
+
+thread_local! {
+    /// Number of [`StorageScope`]s alive. The collections only account their writes and removals
+    /// while it is not zero, so that no host function calls are added when storage is not
+    /// accounted.
+    static ACTIVE_SCOPES: Cell<u32> = const { Cell::new(0) };
+    /// Total bytes written and freed by the collections while a scope was alive, by prefix of the
+    /// collection.
+    static TOTALS: RefCell<BTreeMap<Box<[u8]>, StorageDelta>> = const { RefCell::new(BTreeMap::new()) };
+}
+
+/// Bytes of storage written and freed by some operations.
+///
+/// The bytes of a record include its key, its value and the fixed overhead of a record, which is
+/// 40 bytes in the current runtime configuration. Overwriting a value with a longer one writes
+/// the difference of their lengths, and with a shorter one frees it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StorageDelta {
+    /// Bytes of storage added.
+    pub written: u64,
+    /// Bytes of storage released.
+    pub freed: u64,
+}
+
+impl StorageDelta {
+    /// Returns the change of the storage usage of the account, in bytes.
+    pub fn net(&self) -> i64 {
+        self.written as i64 - self.freed as i64
+    }
+
+    fn add(self, other: Self) -> Self {
+        Self { written: self.written + other.written, freed: self.freed + other.freed }
+    }
+
+    fn sub(self, other: Self) -> Self {
+        Self { written: self.written - other.written, freed: self.freed - other.freed }
+    }
+}
+
+/// Scope accounting the storage written and freed by the collections of this module from its
+/// creation until it is dropped.
+///
+/// The writes and removals of the collections are accounted by prefix of the collection, so the
+/// storage of a single collection can be read with [`delta_of`](Self::delta_of). Values written
+/// directly with [`env::storage_write`] are not accounted. The collections cache their changes and
+/// only write them when flushed or dropped, so they must be flushed before the delta of the scope
+/// is read for their changes to be included.
+///
+/// Scopes can be nested, each accounting all the operations made during its lifetime.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::store::{LookupMap, StorageScope};
+///
+/// let mut map: LookupMap<String, String> = LookupMap::new(b"m");
+/// let scope = StorageScope::start();
+/// map.insert("key".to_string(), "value".to_string());
+/// map.flush();
+/// assert!(scope.delta().written > 0);
+/// assert_eq!(scope.delta().freed, 0);
+/// assert_eq!(scope.delta_of(&map), scope.delta());
+/// ```
+#[must_use = "storage is only accounted while the scope is alive"]
+pub struct StorageScope {
+    start: BTreeMap<Box<[u8]>, StorageDelta>,
+}
+
+impl StorageScope {
+    /// Starts accounting the storage written and freed.
+    pub fn start() -> Self {
+        ACTIVE_SCOPES.with(|scopes| scopes.set(scopes.get() + 1));
+        Self { start: TOTALS.with(|totals| totals.borrow().clone()) }
+    }
+
+    /// Returns the storage written and freed by all collections since the scope started.
+    pub fn delta(&self) -> StorageDelta {
+        self.delta_matching(|_| true)
+    }
+
+    /// Returns the storage written and freed since the scope started by `collection`, which can
+    /// also be a struct of collections. This includes the collections it is made of, such as the
+    /// values of an [`IterableMap`](crate::store::IterableMap), as reported by
+    /// [`StoragePrefixes`]. Only the exact prefixes reported are matched, so other collections
+    /// whose prefix merely starts with one of them are not included.
+    pub fn delta_of<C: StoragePrefixes + ?Sized>(&self, collection: &C) -> StorageDelta {
+        let mut prefixes = Vec::new();
+        collection.storage_prefixes("", &mut prefixes);
+        let prefixes: BTreeSet<Vec<u8>> = prefixes.into_iter().map(|p| p.prefix).collect();
+        self.delta_matching(|key| prefixes.contains(key))
+    }
+
+    fn delta_matching(&self, matches: impl Fn(&[u8]) -> bool) -> StorageDelta {
+        TOTALS.with(|totals| {
+            totals
+                .borrow()
+                .iter()
+                .filter(|(prefix, _)| matches(prefix))
+                .map(|(prefix, total)| {
+                    total.sub(self.start.get(prefix).copied().unwrap_or_default())
+                })
+                .fold(StorageDelta::default(), StorageDelta::add)
+        })
+    }
+}
+
+impl Drop for StorageScope {
+    fn drop(&mut self) {
+        ACTIVE_SCOPES.with(|scopes| scopes.set(scopes.get() - 1));
+    }
+}
+
+/// Runs `f` and returns its result along with the storage written and freed by the collections
+/// of this module. See [`StorageScope`] for the operations accounted.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::store::{storage_delta, LookupMap};
+///
+/// let mut map: LookupMap<u8, String> = LookupMap::new(b"m");
+/// map.insert(1, "a".to_string());
+/// map.flush();
+/// let (_, delta) = storage_delta(|| {
+///     map.insert(1, "bb".to_string());
+///     map.flush();
+///     map.remove(&1);
+///     map.flush();
+/// });
+/// assert_eq!(delta.written, 1);
+/// assert_eq!(delta.net(), -(1 + 1 + 4 + 1 + 40));
+/// ```
+pub fn storage_delta<R>(f: impl FnOnce() -> R) -> (R, StorageDelta) {
+    let scope = StorageScope::start();
+    let result = f();
+    (result, scope.delta())
+}
+
+/// Writes `value` under `key` for the collection using `prefix`, accounting the change of the
+/// storage usage if a [`StorageScope`] is alive.
+pub(crate) fn storage_write(prefix: &[u8], key: &[u8], value: &[u8]) -> bool {
+    track(prefix, || env::storage_write(key, value))
+}
+
+/// Removes the value under `key` for the collection using `prefix`, accounting the change of the
+/// storage usage if a [`StorageScope`] is alive.
+pub(crate) fn storage_remove(prefix: &[u8], key: &[u8]) -> bool {
+    track(prefix, || env::storage_remove(key))
+}
+
+fn track<R>(prefix: &[u8], f: impl FnOnce() -> R) -> R {
+    if ACTIVE_SCOPES.with(Cell::get) == 0 {
+        return f();
+    }
+    let before = env::storage_usage();
+    let result = f();
+    let after = env::storage_usage();
+    let delta = if after >= before {
+        StorageDelta { written: after - before, freed: 0 }
+    } else {
+        StorageDelta { written: 0, freed: before - after }
+    };
+    TOTALS.with(|totals| {
+        let mut totals = totals.borrow_mut();
+        match totals.get_mut(prefix) {
+            Some(total) => *total = total.add(delta),
+            None => {
+                totals.insert(prefix.into(), delta);
+            }
+        }
+    });
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{IterableMap, LookupMap, LookupSet, Vector};
+
+    #[test]
+    fn nested_scopes() {
+        let mut set = LookupSet::new(b"s");
+        let outer = StorageScope::start();
+        set.insert(1u8);
+        let (_, inner) = storage_delta(|| {
+            set.insert(2u8);
+            set.remove(&1);
+        });
+        assert_eq!(inner, StorageDelta { written: 2 + 40, freed: 2 + 40 });
+        assert_eq!(outer.delta(), StorageDelta { written: 2 * (2 + 40), freed: 2 + 40 });
+        assert_eq!(outer.delta().net(), 2 + 40);
+        drop(outer);
+
+        // Nothing is accounted without a scope.
+        let before = TOTALS.with(|totals| totals.borrow().clone());
+        set.remove(&2);
+        assert_eq!(TOTALS.with(|totals| totals.borrow().clone()), before);
+        assert_eq!(ACTIVE_SCOPES.with(Cell::get), 0);
+    }
+
+    #[test]
+    fn collections() {
+        let mut map = IterableMap::new(b"m");
+        let mut vec = Vector::new(b"v");
+        let scope = StorageScope::start();
+        map.insert(1u8, 2u8);
+        vec.push(3u8);
+        map.flush();
+        vec.flush();
+        let inserted = scope.delta();
+        assert!(inserted.written > 0);
+        assert_eq!(inserted.freed, 0);
+        assert_eq!(scope.delta_of(&vec), StorageDelta { written: 1 + 4 + 1 + 40, freed: 0 });
+        assert_eq!(scope.delta_of(&map).add(scope.delta_of(&vec)), inserted);
+        assert_eq!(scope.delta_of(&LookupSet::<u8>::new(b"x")), StorageDelta::default());
+        drop(scope);
+
+        let (_, removed) = storage_delta(|| {
+            map.remove(&1);
+            vec.pop();
+            map.flush();
+            vec.flush();
+        });
+        assert_eq!(removed.freed, inserted.written);
+    }
+
+    #[test]
+    fn sibling_prefixes() {
+        let mut map = LookupMap::new(b"m");
+        let mut sibling = LookupMap::new(b"ma".to_vec());
+        let scope = StorageScope::start();
+        map.insert(1u8, 2u8);
+        sibling.insert(3u8, 4u8);
+        map.flush();
+        sibling.flush();
+        assert_eq!(scope.delta_of(&map), StorageDelta { written: 1 + 1 + 1 + 40, freed: 0 });
+        assert_eq!(scope.delta_of(&sibling), StorageDelta { written: 2 + 1 + 1 + 40, freed: 0 });
+    }
+
+    #[test]
+    fn direct_writes_not_accounted() {
+        let (_, delta) = storage_delta(|| env::storage_write(b"a", b"1"));
+        assert_eq!(delta, StorageDelta::default());
+    }
+}
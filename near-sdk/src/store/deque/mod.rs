@@ -0,0 +1,610 @@
+//! A double-ended queue with values persisted to storage and lazily loaded.
+//!
+//! Values in the [`Deque`] are kept in an in-memory cache and are only persisted on [`Drop`].
+//!
+//! Elements are stored in a ring of `u32` indices, so pushing and popping at either end is O(1)
+//! and never moves other elements in storage.
+//!
+//! # Examples
+//!
+//! ```
+//! use near_sdk::store::Deque;
+//!
+//! let mut jobs: Deque<String> = Deque::new(b"j");
+//! jobs.push_back("second".to_string());
+//! jobs.push_front("first".to_string());
+//!
+//! assert_eq!(jobs.pop_front(), Some("first".to_string()));
+//! assert_eq!(jobs.back(), Some(&"second".to_string()));
+//! ```
+
+mod impls;
+mod iter;
+
+use std::fmt;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk_macros::near;
+
+pub use self::iter::{Iter, IterMut};
+use super::IndexMap;
+use crate::store::prefixes::{StoragePrefix, StoragePrefixes};
+use crate::{env, IntoStorageKey};
+
+const ERR_INDEX_OUT_OF_BOUNDS: &str = "Index out of bounds";
+
+/// A double-ended queue that stores its content on the trie. This implementation will load and
+/// store values in the underlying storage lazily.
+///
+/// Uses the following map: index -> element, where the indices of the elements are consecutive
+/// (wrapping around) starting at the front of the queue. Pushing to the front decrements the
+/// index of the front and pushing to the back increments the index of the back, which makes both
+/// operations O(1).
+///
+/// This implementation will cache all changes and loads and only updates values that are changed
+/// in storage after it's dropped through it's [`Drop`] implementation. These changes can be updated
+/// in storage before the variable is dropped by using [`Deque::flush`].
+///
+/// This type should be a drop in replacement for [`VecDeque`](std::collections::VecDeque) in most
+/// cases.
+///
+/// # Examples
+/// ```
+/// use near_sdk::store::Deque;
+///
+/// let mut deque = Deque::new(b"d");
+/// assert!(deque.is_empty());
+///
+/// deque.push_back(2);
+/// deque.push_back(3);
+/// deque.push_front(1);
+///
+/// assert_eq!(deque.len(), 3);
+/// assert_eq!(deque[0], 1);
+///
+/// assert_eq!(deque.pop_back(), Some(3));
+/// assert_eq!(deque.pop_front(), Some(1));
+/// assert_eq!(deque.len(), 1);
+///
+/// deque.extend([4, 5]);
+/// assert!(Iterator::eq(deque.iter(), [2, 4, 5].iter()));
+/// ```
+#[near(inside_nearsdk)]
+pub struct Deque<T>
+where
+    T: BorshSerialize,
+{
+    head: u32,
+    len: u32,
+    // ser/de is independent of `T` ser/de, `BorshSerialize`/`BorshDeserialize`/`BorshSchema` bounds removed
+    #[cfg_attr(not(feature = "abi"), borsh(bound(serialize = "", deserialize = "")))]
+    #[cfg_attr(
+        feature = "abi",
+        borsh(bound(serialize = "", deserialize = ""), schema(params = ""))
+    )]
+    values: IndexMap<T>,
+}
+
+impl<T> Deque<T>
+where
+    T: BorshSerialize,
+{
+    /// Create new deque with zero elements. Prefixes storage access with the prefix provided.
+    ///
+    /// This prefix can be anything that implements [`IntoStorageKey`]. The prefix is used when
+    /// storing and looking up values in storage to ensure no collisions with other collections.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Deque;
+    ///
+    /// let mut deque: Deque<u8> = Deque::new(b"d");
+    /// ```
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self { head: 0, len: 0, values: IndexMap::new(prefix) }
+    }
+
+    /// Returns the number of elements in the deque.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Deque;
+    ///
+    /// let mut deque = Deque::new(b"d");
+    /// deque.push_back(1);
+    /// deque.push_front(0);
+    /// assert_eq!(deque.len(), 2);
+    /// ```
+    pub fn len(&self) -> u32 {
+        self.len
+    }
+
+    /// Returns `true` if the deque contains no elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Deque;
+    ///
+    /// let mut deque = Deque::new(b"d");
+    /// assert!(deque.is_empty());
+    ///
+    /// deque.push_back(1);
+    /// assert!(!deque.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Removes all elements from the collection. This will remove all storage values for the
+    /// length of the [`Deque`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Deque;
+    ///
+    /// let mut deque = Deque::new(b"d");
+    /// deque.push_back(1);
+    ///
+    /// deque.clear();
+    ///
+    /// assert!(deque.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        for i in 0..self.len {
+            self.values.set(self.index_of(i), None);
+        }
+        self.head = 0;
+        self.len = 0;
+    }
+
+    /// Flushes the cache and writes all modified values to storage.
+    ///
+    /// This operation is performed on [`Drop`], but this method can be called to persist
+    /// intermediate writes in cases where [`Drop`] is not called or to identify storage changes.
+    pub fn flush(&mut self) {
+        self.values.flush();
+    }
+
+    /// Appends an element to the back of the deque.
+    ///
+    /// # Panics
+    ///
+    /// Panics if new length exceeds `u32::MAX`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Deque;
+    ///
+    /// let mut deque = Deque::new(b"d");
+    /// deque.push_back(1);
+    /// deque.push_back(3);
+    /// assert_eq!(deque.back(), Some(&3));
+    /// ```
+    pub fn push_back(&mut self, element: T) {
+        let index = self.index_of(self.len);
+        self.len =
+            self.len.checked_add(1).unwrap_or_else(|| env::panic_str(ERR_INDEX_OUT_OF_BOUNDS));
+        self.values.set(index, Some(element));
+    }
+
+    /// Prepends an element to the front of the deque.
+    ///
+    /// # Panics
+    ///
+    /// Panics if new length exceeds `u32::MAX`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Deque;
+    ///
+    /// let mut deque = Deque::new(b"d");
+    /// deque.push_front(1);
+    /// deque.push_front(2);
+    /// assert_eq!(deque.front(), Some(&2));
+    /// ```
+    pub fn push_front(&mut self, element: T) {
+        self.len =
+            self.len.checked_add(1).unwrap_or_else(|| env::panic_str(ERR_INDEX_OUT_OF_BOUNDS));
+        self.head = self.head.wrapping_sub(1);
+        self.values.set(self.head, Some(element));
+    }
+
+    /// Storage index of the element at position `index` from the front.
+    fn index_of(&self, index: u32) -> u32 {
+        self.head.wrapping_add(index)
+    }
+}
+
+impl<T> Deque<T>
+where
+    T: BorshSerialize + BorshDeserialize,
+{
+    /// Returns the element at position `index` from the front, or `None` if it is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Deque;
+    ///
+    /// let mut deque = Deque::new(b"d");
+    /// deque.push_back(3);
+    /// deque.push_front(2);
+    ///
+    /// assert_eq!(deque.get(0), Some(&2));
+    /// assert_eq!(deque.get(2), None);
+    /// ```
+    pub fn get(&self, index: u32) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+        self.values.get(self.index_of(index))
+    }
+
+    /// Returns a mutable reference to the element at position `index` from the front, or `None`
+    /// if it is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Deque;
+    ///
+    /// let mut deque = Deque::new(b"d");
+    /// deque.extend([1, 2, 3]);
+    ///
+    /// if let Some(elem) = deque.get_mut(1) {
+    ///     *elem = 42;
+    /// }
+    /// assert_eq!(deque[1], 42);
+    /// ```
+    pub fn get_mut(&mut self, index: u32) -> Option<&mut T> {
+        if index >= self.len {
+            return None;
+        }
+        self.values.get_mut(self.index_of(index))
+    }
+
+    /// Returns the front element, or `None` if the deque is empty.
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    /// Returns a mutable reference to the front element, or `None` if the deque is empty.
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(0)
+    }
+
+    /// Returns the back element, or `None` if the deque is empty.
+    pub fn back(&self) -> Option<&T> {
+        self.get(self.len.checked_sub(1)?)
+    }
+
+    /// Returns a mutable reference to the back element, or `None` if the deque is empty.
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(self.len.checked_sub(1)?)
+    }
+
+    /// Removes the first element and returns it, or [`None`] if the deque is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Deque;
+    ///
+    /// let mut deque = Deque::new(b"d");
+    /// deque.extend([1, 2]);
+    ///
+    /// assert_eq!(deque.pop_front(), Some(1));
+    /// assert_eq!(deque.pop_front(), Some(2));
+    /// assert_eq!(deque.pop_front(), None);
+    /// ```
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.len = self.len.checked_sub(1)?;
+        let prev = self.values.remove(self.head);
+        self.head = self.head.wrapping_add(1);
+        prev
+    }
+
+    /// Removes the last element and returns it, or [`None`] if the deque is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Deque;
+    ///
+    /// let mut deque = Deque::new(b"d");
+    /// deque.extend([1, 2]);
+    ///
+    /// assert_eq!(deque.pop_back(), Some(2));
+    /// assert_eq!(deque.pop_back(), Some(1));
+    /// assert_eq!(deque.pop_back(), None);
+    /// ```
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.len = self.len.checked_sub(1)?;
+        self.values.remove(self.index_of(self.len))
+    }
+
+    /// Returns a front-to-back iterator over the deque. This iterator will lazily load any values
+    /// iterated over from storage.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Deque;
+    ///
+    /// let mut deque = Deque::new(b"d");
+    /// deque.push_back(2);
+    /// deque.push_front(1);
+    /// let mut iterator = deque.iter();
+    ///
+    /// assert_eq!(iterator.next(), Some(&1));
+    /// assert_eq!(iterator.next(), Some(&2));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter::new(self)
+    }
+
+    /// Returns a front-to-back iterator over the [`Deque`] that allows modifying each value. This
+    /// iterator will lazily load any values iterated over from storage.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Deque;
+    ///
+    /// let mut deque = Deque::new(b"d");
+    /// deque.extend([1u32, 2, 4]);
+    ///
+    /// for elem in deque.iter_mut() {
+    ///     *elem += 2;
+    /// }
+    /// assert_eq!(deque.iter().copied().collect::<Vec<_>>(), &[3u32, 4, 6]);
+    /// ```
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut::new(self)
+    }
+}
+
+impl<T> fmt::Debug for Deque<T>
+where
+    T: BorshSerialize + BorshDeserialize + fmt::Debug,
+{
+    #[cfg(feature = "expensive-debug")]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.iter().collect::<Vec<_>>(), f)
+    }
+
+    #[cfg(not(feature = "expensive-debug"))]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Deque")
+            .field("head", &self.head)
+            .field("len", &self.len)
+            .field("prefix", &self.values.prefix)
+            .finish()
+    }
+}
+
+impl<T> StoragePrefixes for Deque<T>
+where
+    T: BorshSerialize,
+{
+    fn storage_prefixes(&self, path: &str, prefixes: &mut Vec<StoragePrefix>) {
+        self.values.storage_prefixes(path, prefixes);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use arbitrary::{Arbitrary, Unstructured};
+    use borsh::{to_vec, BorshDeserialize};
+    use rand::{Rng, RngCore, SeedableRng};
+    use std::collections::VecDeque;
+
+    use super::Deque;
+    use crate::test_utils::test_env::setup_free;
+
+    #[test]
+    fn test_push_pop_both_ends() {
+        let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(0);
+        let mut deque = Deque::new(b"d".to_vec());
+        let mut baseline = VecDeque::new();
+        for _ in 0..500 {
+            let value = rng.gen::<u64>();
+            if rng.gen::<bool>() {
+                deque.push_back(value);
+                baseline.push_back(value);
+            } else {
+                deque.push_front(value);
+                baseline.push_front(value);
+            }
+        }
+        assert!(Iterator::eq(deque.iter(), baseline.iter()));
+        for _ in 0..501 {
+            if rng.gen::<bool>() {
+                assert_eq!(baseline.pop_back(), deque.pop_back());
+            } else {
+                assert_eq!(baseline.pop_front(), deque.pop_front());
+            }
+        }
+        assert!(deque.is_empty());
+    }
+
+    #[test]
+    fn test_wrapping_indices() {
+        let mut deque = Deque::new(b"d");
+        deque.push_front(1u8);
+        deque.push_back(2);
+        assert_eq!(deque.head, u32::MAX);
+        assert_eq!(deque.iter().copied().collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(deque.pop_front(), Some(1));
+        assert_eq!(deque.head, 0);
+        assert_eq!(deque.front(), Some(&2));
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut deque = Deque::new(b"d");
+        deque.extend([1u8, 2, 3]);
+        deque.push_front(0);
+        deque.clear();
+        deque.flush();
+        assert!(deque.is_empty());
+        crate::mock::with_mocked_blockchain(|m| assert!(m.take_storage().is_empty()));
+    }
+
+    #[test]
+    pub fn iterator_checks() {
+        let mut deque = Deque::new(b"d");
+        let mut baseline = VecDeque::new();
+        for i in 0..10 {
+            deque.push_front(i);
+            baseline.push_front(i);
+        }
+
+        let mut deque_iter = deque.iter();
+        let mut bl_iter = baseline.iter();
+        assert_eq!(deque_iter.next(), bl_iter.next());
+        assert_eq!(deque_iter.next_back(), bl_iter.next_back());
+        assert_eq!(deque_iter.nth(3), bl_iter.nth(3));
+        assert_eq!(deque_iter.nth_back(2), bl_iter.nth_back(2));
+
+        // Check to make sure indexing overflow is handled correctly
+        assert!(deque_iter.nth(5).is_none());
+        assert!(bl_iter.nth(5).is_none());
+
+        // Count check
+        assert_eq!(deque.iter().count(), baseline.len());
+
+        for (a, b) in deque.iter_mut().zip(baseline.iter_mut()) {
+            *a += 1;
+            *b += 1;
+        }
+        assert!(Iterator::eq(deque.iter().rev(), baseline.iter().rev()));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_index_panic() {
+        let deque: Deque<bool> = Deque::new(b"d");
+        let _ = deque[0];
+    }
+
+    #[derive(Arbitrary, Debug)]
+    enum Op {
+        PushBack(u8),
+        PushFront(u8),
+        PopBack,
+        PopFront,
+        Set(u32, u8),
+        Flush,
+        Reset,
+        Get(u32),
+    }
+
+    #[test]
+    fn arbitrary() {
+        setup_free();
+
+        let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(0);
+        let mut buf = vec![0; 4096];
+        for _ in 0..1024 {
+            // Clear storage in-between runs
+            crate::mock::with_mocked_blockchain(|b| b.take_storage());
+            rng.fill_bytes(&mut buf);
+
+            let mut sd = Deque::new(b"d");
+            let mut md = VecDeque::new();
+            let u = Unstructured::new(&buf);
+            if let Ok(ops) = Vec::<Op>::arbitrary_take_rest(u) {
+                for op in ops {
+                    match op {
+                        Op::PushBack(v) => {
+                            sd.push_back(v);
+                            md.push_back(v);
+                        }
+                        Op::PushFront(v) => {
+                            sd.push_front(v);
+                            md.push_front(v);
+                        }
+                        Op::PopBack => {
+                            assert_eq!(sd.pop_back(), md.pop_back());
+                        }
+                        Op::PopFront => {
+                            assert_eq!(sd.pop_front(), md.pop_front());
+                        }
+                        Op::Set(k, v) => {
+                            if sd.is_empty() {
+                                continue;
+                            }
+                            let k = k % sd.len();
+
+                            sd[k] = v;
+                            md[k as usize] = v;
+                        }
+                        Op::Flush => {
+                            sd.flush();
+                        }
+                        Op::Reset => {
+                            let serialized = to_vec(&sd).unwrap();
+                            sd = Deque::deserialize(&mut serialized.as_slice()).unwrap();
+                        }
+                        Op::Get(k) => {
+                            assert_eq!(sd.get(k), md.get(k as usize));
+                        }
+                    }
+                    assert_eq!(sd.len() as usize, md.len());
+                }
+            }
+
+            // After all operations, compare both deques
+            assert!(Iterator::eq(sd.iter(), md.iter()));
+        }
+    }
+
+    #[test]
+    fn serialized_bytes() {
+        use borsh::BorshSerialize;
+
+        let mut deque = Deque::new(b"d".to_vec());
+        deque.push_front("Some data");
+        let serialized = to_vec(&deque).unwrap();
+
+        // Expected to serialize head, len then prefix
+        let mut expected_buf = Vec::new();
+        u32::MAX.serialize(&mut expected_buf).unwrap();
+        1u32.serialize(&mut expected_buf).unwrap();
+        (b"d".to_vec()).serialize(&mut expected_buf).unwrap();
+
+        assert_eq!(serialized, expected_buf);
+        drop(deque);
+        let deque = Deque::<String>::deserialize(&mut serialized.as_slice()).unwrap();
+        assert_eq!(deque[0], "Some data");
+    }
+
+    #[cfg(feature = "abi")]
+    #[test]
+    fn test_borsh_schema() {
+        #[derive(
+            borsh::BorshSerialize, borsh::BorshDeserialize, PartialEq, Eq, PartialOrd, Ord,
+        )]
+        struct NoSchemaStruct;
+
+        assert_eq!(
+            "Deque".to_string(),
+            <Deque<NoSchemaStruct> as borsh::BorshSchema>::declaration()
+        );
+        let mut defs = Default::default();
+        <Deque<NoSchemaStruct> as borsh::BorshSchema>::add_definitions_recursively(&mut defs);
+        insta::assert_snapshot!(format!("{:#?}", defs));
+    }
+}
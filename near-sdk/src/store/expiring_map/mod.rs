@@ -0,0 +1,412 @@
+use std::borrow::Borrow;
+use std::fmt;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use near_sdk_macros::near;
+
+use crate::store::key::{Identity, ToKey};
+use crate::store::prefixes::{field_path, StoragePrefix, StoragePrefixes};
+use crate::store::{Deque, LookupMap};
+use crate::{env, Duration, IntoStorageKey, Timestamp};
+
+/// A [`LookupMap`] whose entries expire a fixed duration after they are inserted.
+///
+/// Each insert records an expiry of [`env::block_timestamp`] plus the time to live of the map.
+/// Expired entries are hidden from [`get`], [`contains_key`] and the other accessors, and their
+/// storage is reclaimed by [`evict_expired`], which removes a bounded number of expired entries
+/// per call so that its gas cost stays predictable. It can be called at the start of the methods
+/// inserting entries, to reclaim at least as much storage as they use.
+///
+/// The keys are also recorded in a [`Deque`] in the order of their expiry, which costs an extra
+/// storage write per insert. As every entry lives for the same duration, the entries expiring
+/// first are always at the front of the queue.
+///
+/// The default hash function is [`Identity`], like for [`LookupMap`].
+///
+/// # Examples
+/// ```
+/// use near_sdk::store::ExpiringMap;
+/// use near_sdk::test_utils::VMContextBuilder;
+/// use near_sdk::testing_env;
+///
+/// // Nonces expire after one second.
+/// let mut nonces = ExpiringMap::new(b"n", 1_000_000_000);
+/// testing_env!(VMContextBuilder::new().block_timestamp(0).build());
+/// nonces.insert(7u64, ());
+/// assert!(nonces.contains_key(&7));
+///
+/// testing_env!(VMContextBuilder::new().block_timestamp(1_000_000_000).build());
+/// assert!(!nonces.contains_key(&7));
+/// assert_eq!(nonces.evict_expired(10), 1);
+/// ```
+///
+/// [`get`]: Self::get
+/// [`contains_key`]: Self::contains_key
+/// [`evict_expired`]: Self::evict_expired
+#[near(inside_nearsdk)]
+pub struct ExpiringMap<K, V, H = Identity>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    /// Time to live of the entries, in nanoseconds.
+    ttl: Duration,
+    // ser/de is independent of `K`, `V`, `H` ser/de, `BorshSerialize`/`BorshDeserialize`/`BorshSchema` bounds removed
+    #[cfg_attr(not(feature = "abi"), borsh(bound(serialize = "", deserialize = "")))]
+    #[cfg_attr(
+        feature = "abi",
+        borsh(bound(serialize = "", deserialize = ""), schema(params = ""))
+    )]
+    entries: LookupMap<K, ValueAndExpiry<V>, H>,
+    /// Expiry and key of each insert, in the order of the expiries. Records of keys removed or
+    /// inserted again are left in the queue, and skipped when evicting.
+    // ser/de is independent of `K` ser/de, `BorshSerialize`/`BorshDeserialize`/`BorshSchema` bounds removed
+    #[cfg_attr(not(feature = "abi"), borsh(bound(serialize = "", deserialize = "")))]
+    #[cfg_attr(
+        feature = "abi",
+        borsh(bound(serialize = "", deserialize = ""), schema(params = ""))
+    )]
+    expiries: Deque<(Timestamp, K)>,
+}
+
+#[near(inside_nearsdk)]
+struct ValueAndExpiry<V> {
+    value: V,
+    expires_at: Timestamp,
+}
+
+impl<V> ValueAndExpiry<V> {
+    fn is_live(&self) -> bool {
+        env::block_timestamp() < self.expires_at
+    }
+}
+
+impl<K, V, H> fmt::Debug for ExpiringMap<K, V, H>
+where
+    K: BorshSerialize + Ord + BorshDeserialize + fmt::Debug,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExpiringMap")
+            .field("ttl", &self.ttl)
+            .field("entries", &self.entries)
+            .field("expiries", &self.expiries)
+            .finish()
+    }
+}
+
+impl<K, V> ExpiringMap<K, V, Identity>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+{
+    /// Create a new [`ExpiringMap`] with the prefix provided, whose entries expire `ttl`
+    /// nanoseconds after they are inserted.
+    ///
+    /// This prefix can be anything that implements [`IntoStorageKey`]. The prefix is used when
+    /// storing and looking up values in storage to ensure no collisions with other collections.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::ExpiringMap;
+    ///
+    /// // Sessions expire after a day.
+    /// let map: ExpiringMap<String, String> = ExpiringMap::new(b"s", 24 * 60 * 60 * 1_000_000_000);
+    /// ```
+    #[inline]
+    pub fn new<S>(prefix: S, ttl: Duration) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self::with_hasher(prefix, ttl)
+    }
+}
+
+impl<K, V, H> ExpiringMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    /// Initialize an [`ExpiringMap`] with a custom hash function.
+    ///
+    /// # Example
+    /// ```
+    /// use near_sdk::store::{ExpiringMap, key::Keccak256};
+    ///
+    /// let map = ExpiringMap::<String, String, Keccak256>::with_hasher(b"s", 1_000_000_000);
+    /// ```
+    pub fn with_hasher<S>(prefix: S, ttl: Duration) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        let mut queue_key = prefix.into_storage_key();
+        let map_key = [queue_key.as_slice(), b"m"].concat();
+        queue_key.push(b'q');
+        Self { ttl, entries: LookupMap::with_hasher(map_key), expiries: Deque::new(queue_key) }
+    }
+
+    /// Returns the time to live of the entries, in nanoseconds.
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Returns the number of inserts recorded for eviction, including the ones of entries that
+    /// expired, were removed or were inserted again since.
+    pub fn pending_evictions(&self) -> u32 {
+        self.expiries.len()
+    }
+
+    /// Flushes the intermediate values of the map before this is called when the structure is
+    /// [`Drop`]ed. This will write all modified values to storage but keep all cached values
+    /// in memory.
+    pub fn flush(&mut self) {
+        self.entries.flush();
+        self.expiries.flush();
+    }
+}
+
+impl<K, V, H> ExpiringMap<K, V, H>
+where
+    K: BorshSerialize + Ord + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+    /// Returns a reference to the value corresponding to the key, or `None` if there is no value
+    /// or it expired.
+    ///
+    /// # Example
+    /// ```
+    /// use near_sdk::store::ExpiringMap;
+    ///
+    /// let mut map: ExpiringMap<u32, String> = ExpiringMap::new(b"s", 1_000_000_000);
+    /// map.insert(1, "a".to_string());
+    /// assert_eq!(map.get(&1), Some(&"a".to_string()));
+    /// assert_eq!(map.get(&2), None);
+    /// ```
+    pub fn get<Q: ?Sized>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K>,
+    {
+        self.entries.get(k).filter(|entry| entry.is_live()).map(|entry| &entry.value)
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key, or `None` if there is
+    /// no value or it expired. Modifying the value doesn't change its expiry.
+    pub fn get_mut<Q: ?Sized>(&mut self, k: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K>,
+    {
+        self.entries.get_mut(k).filter(|entry| entry.is_live()).map(|entry| &mut entry.value)
+    }
+
+    /// Returns the timestamp, in nanoseconds, at which the value corresponding to the key expires,
+    /// or `None` if there is no value or it expired.
+    pub fn expires_at<Q: ?Sized>(&self, k: &Q) -> Option<Timestamp>
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K>,
+    {
+        self.entries.get(k).filter(|entry| entry.is_live()).map(|entry| entry.expires_at)
+    }
+
+    /// Returns `true` if the map contains a value for the specified key which hasn't expired.
+    pub fn contains_key<Q: ?Sized>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K>,
+    {
+        self.get(k).is_some()
+    }
+
+    /// Inserts a key-value pair into the map, expiring after the time to live of the map from the
+    /// current block timestamp. Returns the previous value if it hadn't expired.
+    ///
+    /// Inserting a key again resets its expiry.
+    ///
+    /// # Example
+    /// ```
+    /// use near_sdk::store::ExpiringMap;
+    ///
+    /// let mut map = ExpiringMap::new(b"s", 1_000_000_000);
+    /// assert_eq!(map.insert(1u8, "a".to_string()), None);
+    /// assert_eq!(map.insert(1u8, "b".to_string()), Some("a".to_string()));
+    /// ```
+    pub fn insert(&mut self, k: K, value: V) -> Option<V>
+    where
+        K: BorshDeserialize,
+    {
+        let expires_at = env::block_timestamp().saturating_add(self.ttl);
+        self.expiries.push_back((expires_at, k.clone()));
+        self.entries
+            .insert(k, ValueAndExpiry { value, expires_at })
+            .filter(ValueAndExpiry::is_live)
+            .map(|entry| entry.value)
+    }
+
+    /// Removes a key from the map, returning the value at the key if it hadn't expired.
+    pub fn remove<Q: ?Sized>(&mut self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K>,
+    {
+        self.entries.remove(k).filter(ValueAndExpiry::is_live).map(|entry| entry.value)
+    }
+
+    /// Removes expired entries from storage, processing at most `limit` recorded inserts, and
+    /// returns the number of entries removed.
+    ///
+    /// Inserts are processed from the one expiring first, and processing stops at the first one
+    /// which hasn't expired, so the gas used is bounded by `limit`. Records of keys which were
+    /// removed or inserted again since count towards `limit` without removing anything.
+    ///
+    /// # Example
+    /// ```
+    /// use near_sdk::store::ExpiringMap;
+    /// use near_sdk::test_utils::VMContextBuilder;
+    /// use near_sdk::testing_env;
+    ///
+    /// let mut map = ExpiringMap::new(b"s", 10);
+    /// testing_env!(VMContextBuilder::new().block_timestamp(0).build());
+    /// map.insert(1u8, ());
+    /// map.insert(2u8, ());
+    /// testing_env!(VMContextBuilder::new().block_timestamp(5).build());
+    /// map.insert(3u8, ());
+    ///
+    /// testing_env!(VMContextBuilder::new().block_timestamp(10).build());
+    /// assert_eq!(map.evict_expired(1), 1);
+    /// assert_eq!(map.evict_expired(10), 1);
+    /// assert_eq!(map.pending_evictions(), 1);
+    /// ```
+    pub fn evict_expired(&mut self, limit: u32) -> u32
+    where
+        K: BorshDeserialize,
+    {
+        let now = env::block_timestamp();
+        let mut evicted = 0;
+        for _ in 0..limit {
+            match self.expiries.front() {
+                Some((expires_at, _)) if *expires_at <= now => {}
+                _ => break,
+            }
+            let (expires_at, key) = self.expiries.pop_front().unwrap_or_else(|| env::abort());
+            // The entry may have been removed, or inserted again with a later expiry.
+            let current = self.entries.get(&key).map(|entry| entry.expires_at);
+            if current == Some(expires_at) {
+                self.entries.remove(&key);
+                evicted += 1;
+            }
+        }
+        evicted
+    }
+}
+
+impl<K, V, H> StoragePrefixes for ExpiringMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    fn storage_prefixes(&self, path: &str, prefixes: &mut Vec<StoragePrefix>) {
+        self.entries.storage_prefixes(&field_path(path, "entries"), prefixes);
+        self.expiries.storage_prefixes(&field_path(path, "expiries"), prefixes);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::ExpiringMap;
+    use crate::test_utils::VMContextBuilder;
+    use crate::{env, testing_env, Timestamp};
+
+    const TTL: u64 = 100;
+
+    fn set_block_timestamp(timestamp: Timestamp) {
+        testing_env!(VMContextBuilder::new().block_timestamp(timestamp).build());
+    }
+
+    #[test]
+    fn entries_expire() {
+        set_block_timestamp(1_000);
+        let mut map = ExpiringMap::new(b"e", TTL);
+        assert_eq!(map.insert(1u32, "a".to_string()), None);
+        assert_eq!(map.expires_at(&1), Some(1_000 + TTL));
+
+        set_block_timestamp(1_000 + TTL - 1);
+        assert_eq!(map.get(&1), Some(&"a".to_string()));
+        *map.get_mut(&1).unwrap() = "b".to_string();
+
+        set_block_timestamp(1_000 + TTL);
+        assert_eq!(map.get(&1), None);
+        assert_eq!(map.get_mut(&1), None);
+        assert!(!map.contains_key(&1));
+        assert_eq!(map.expires_at(&1), None);
+        // Expired values are not returned as previous values.
+        assert_eq!(map.insert(1u32, "c".to_string()), None);
+        assert_eq!(map.expires_at(&1), Some(1_000 + 2 * TTL));
+
+        set_block_timestamp(1_000 + 2 * TTL);
+        assert_eq!(map.remove(&1), None);
+    }
+
+    #[test]
+    fn evict_skips_reinserted_and_removed_keys() {
+        set_block_timestamp(0);
+        let mut map = ExpiringMap::new(b"e", TTL);
+        map.insert(1u8, 1u8);
+        map.insert(2u8, 2u8);
+        map.insert(3u8, 3u8);
+        map.remove(&2);
+
+        set_block_timestamp(50);
+        map.insert(1u8, 4u8);
+        assert_eq!(map.pending_evictions(), 4);
+
+        set_block_timestamp(TTL);
+        // Only the record of key 3 evicts an entry, as key 1 was inserted again.
+        assert_eq!(map.evict_expired(10), 1);
+        assert_eq!(map.pending_evictions(), 1);
+        assert_eq!(map.get(&1), Some(&4));
+
+        set_block_timestamp(50 + TTL);
+        assert_eq!(map.evict_expired(10), 1);
+        assert_eq!(map.pending_evictions(), 0);
+        assert_eq!(map.evict_expired(10), 0);
+    }
+
+    #[test]
+    fn evict_across_instances_reclaims_storage() {
+        set_block_timestamp(0);
+        let mut map = ExpiringMap::new(b"e", TTL);
+        for i in 0..25u32 {
+            map.insert(i, i.to_string());
+        }
+        // Persist the map metadata as a contract would at the end of a transaction.
+        env::state_write(&map);
+        drop(map);
+
+        set_block_timestamp(TTL);
+        let mut calls = 0;
+        loop {
+            let mut map: ExpiringMap<u32, String> = env::state_read().unwrap();
+            let evicted = map.evict_expired(10);
+            env::state_write(&map);
+            calls += 1;
+            if evicted == 0 {
+                break;
+            }
+        }
+        assert_eq!(calls, 4);
+
+        // Only the contract state is left in storage.
+        let storage = crate::mock::with_mocked_blockchain(|b| b.take_storage());
+        assert_eq!(storage.keys().collect::<Vec<_>>(), [&b"STATE".to_vec()]);
+    }
+}
@@ -1,12 +1,13 @@
 mod iter;
 pub use self::iter::{Drain, Iter, IterMut};
 
-use super::{Vector, ERR_INCONSISTENT_STATE};
+use super::{IndexMap, Vector, ERR_INCONSISTENT_STATE};
 use crate::{env, IntoStorageKey};
 use near_sdk_macros::near;
 
 use borsh::{BorshDeserialize, BorshSerialize};
 
+use crate::store::prefixes::{StoragePrefix, StoragePrefixes};
 use std::{fmt, mem};
 
 /// Index for value within a bucket.
@@ -101,6 +102,28 @@ where
         self.elements.flush()
     }
 
+    /// Returns a list over the same storage which reads and writes values as `U`. This is used
+    /// to migrate values written with a previous layout of `T`. Cached changes are flushed first.
+    pub(crate) fn with_layout<U>(&mut self) -> FreeList<U>
+    where
+        U: BorshSerialize,
+    {
+        self.flush();
+        FreeList {
+            first_free: self.first_free,
+            occupied_count: self.occupied_count,
+            elements: Vector {
+                len: self.elements.len,
+                values: IndexMap::new(self.elements.values.prefix.to_vec()),
+            },
+        }
+    }
+
+    /// Overwrites the value of an occupied cell without loading the existing value from storage.
+    pub(crate) fn set_occupied(&mut self, index: FreeListIndex, value: T) {
+        self.elements.set(index.0, Slot::Occupied(value));
+    }
+
     /// Clears the bucket, removing all values (including removed entries).
     #[cfg(test)]
     fn clear(&mut self) {
@@ -291,6 +314,15 @@ where
     }
 }
 
+impl<T> StoragePrefixes for FreeList<T>
+where
+    T: BorshSerialize,
+{
+    fn storage_prefixes(&self, path: &str, prefixes: &mut Vec<StoragePrefix>) {
+        self.elements.storage_prefixes(path, prefixes);
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(test)]
 mod tests {
@@ -4,6 +4,8 @@ use borsh::{BorshDeserialize, BorshSerialize};
 use near_sdk_macros::near;
 use once_cell::unsync::OnceCell;
 
+use crate::store::accounting;
+use crate::store::prefixes::{StoragePrefix, StoragePrefixes};
 use crate::utils::StableMap;
 use crate::{env, CacheEntry, EntryState, IntoStorageKey};
 
@@ -61,11 +63,11 @@ where
                             buf.clear();
                             BorshSerialize::serialize(modified, &mut buf)
                                 .unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_SERIALIZATION));
-                            env::storage_write(&key_buf, &buf);
+                            accounting::storage_write(&self.prefix, &key_buf, &buf);
                         }
                         None => {
                             // Element was removed, clear the storage for the value
-                            env::storage_remove(&key_buf);
+                            accounting::storage_remove(&self.prefix, &key_buf);
                         }
                     }
 
@@ -88,6 +90,51 @@ where
             }
         }
     }
+
+    /// Returns the serialized value of a modified entry of the cache: `None` if the index is not
+    /// cached or unmodified, and `Some(None)` if the value was removed.
+    fn modified_raw(&self, index: u32) -> Option<Option<Vec<u8>>> {
+        self.cache
+            .map_value_ref(&index, |v| {
+                v.get().filter(|entry| entry.is_modified()).map(|entry| {
+                    entry.value().as_ref().map(|value| {
+                        borsh::to_vec(value)
+                            .unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_SERIALIZATION))
+                    })
+                })
+            })
+            .flatten()
+    }
+
+    /// Returns the serialized bytes of the element at `index`, without deserializing or caching
+    /// it, or `None` if it is not present.
+    pub fn get_raw(&self, index: u32) -> Option<Vec<u8>> {
+        match self.modified_raw(index) {
+            Some(value) => value,
+            None => {
+                let mut key = Vec::with_capacity(self.prefix.len() + 4);
+                Self::index_to_lookup_key(&self.prefix, index, &mut key);
+                env::storage_read(&key)
+            }
+        }
+    }
+
+    /// Sets the serialized bytes of the element at `index` as the return value, and returns
+    /// `false` if it is not present.
+    pub fn return_raw(&self, index: u32) -> bool {
+        match self.modified_raw(index) {
+            Some(Some(value)) => {
+                env::value_return(&value);
+                true
+            }
+            Some(None) => false,
+            None => {
+                let mut key = Vec::with_capacity(self.prefix.len() + 4);
+                Self::index_to_lookup_key(&self.prefix, index, &mut key);
+                env::value_return_from_storage(&key)
+            }
+        }
+    }
 }
 
 impl<T> IndexMap<T>
@@ -163,6 +210,15 @@ where
     }
 }
 
+impl<T> StoragePrefixes for IndexMap<T>
+where
+    T: BorshSerialize,
+{
+    fn storage_prefixes(&self, path: &str, prefixes: &mut Vec<StoragePrefix>) {
+        prefixes.push(StoragePrefix::new(path, &self.prefix));
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(test)]
 mod tests {
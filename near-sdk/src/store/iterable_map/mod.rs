@@ -20,6 +20,7 @@ pub use entry::{Entry, OccupiedEntry, VacantEntry};
 
 pub use self::iter::{Drain, Iter, IterMut, Keys, Values, ValuesMut};
 use super::{LookupMap, ERR_INCONSISTENT_STATE, ERR_NOT_EXIST};
+use crate::store::prefixes::{field_path, StoragePrefix, StoragePrefixes};
 
 /// A lazily loaded storage map that stores its content directly on the storage trie.
 /// This structure is similar to [`near_sdk::store::LookupMap`](crate::store::LookupMap), except
@@ -247,6 +248,45 @@ where
         }
     }
 
+    /// Removes at most `limit` key-value pairs from the map and returns whether the map is empty
+    /// afterwards. This allows clearing a large map across multiple transactions, each call
+    /// continuing where the previous one stopped.
+    ///
+    /// Pairs are removed from the end of the vector of keys, whose length is persisted with the
+    /// map, so clearing can be safely resumed in a later transaction. This is also how a map
+    /// which needs to be retired can be cleared, which isn't possible with a [`LookupMap`] as its
+    /// keys are not persisted.
+    ///
+    /// [`LookupMap`]: crate::store::LookupMap
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::IterableMap;
+    ///
+    /// let mut map: IterableMap<String, u8> = IterableMap::new(b"b");
+    /// map.insert("a".to_string(), 1);
+    /// map.insert("b".to_string(), 2);
+    ///
+    /// assert!(!map.clear_chunk(1));
+    /// assert_eq!(map.len(), 1);
+    /// assert!(map.clear_chunk(1));
+    /// ```
+    pub fn clear_chunk(&mut self, limit: u32) -> bool
+    where
+        K: BorshDeserialize + Clone,
+        V: BorshDeserialize,
+    {
+        for _ in 0..limit {
+            match self.keys.pop() {
+                // Set instead of remove to avoid loading the value from storage.
+                Some(k) => self.values.set(k, None),
+                None => break,
+            }
+        }
+        self.keys.is_empty()
+    }
+
     /// An iterator visiting all key-value pairs in arbitrary order.
     /// The iterator element type is `(&'a K, &'a V)`.
     ///
@@ -662,10 +702,23 @@ where
     }
 }
 
+impl<K, V, H> StoragePrefixes for IterableMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    fn storage_prefixes(&self, path: &str, prefixes: &mut Vec<StoragePrefix>) {
+        self.keys.storage_prefixes(&field_path(path, "keys"), prefixes);
+        self.values.storage_prefixes(&field_path(path, "values"), prefixes);
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(test)]
 mod tests {
     use super::IterableMap;
+    use crate::env;
     use crate::test_utils::test_env::setup_free;
     use arbitrary::{Arbitrary, Unstructured};
     use borsh::{to_vec, BorshDeserialize};
@@ -673,6 +726,35 @@ mod tests {
     use rand::SeedableRng;
     use std::collections::HashMap;
 
+    #[test]
+    fn clear_chunk_across_instances() {
+        setup_free();
+        let mut map = IterableMap::new(b"j");
+        for i in 0..25u32 {
+            map.insert(i, i.to_string());
+        }
+        map.remove(&3);
+        // Persist the map metadata as a contract would at the end of a transaction.
+        env::state_write(&map);
+        drop(map);
+
+        let mut cleared_chunks = 0;
+        loop {
+            let mut map: IterableMap<u32, String> = env::state_read().unwrap();
+            let done = map.clear_chunk(10);
+            env::state_write(&map);
+            cleared_chunks += 1;
+            if done {
+                break;
+            }
+        }
+        assert_eq!(cleared_chunks, 3);
+
+        // Only the contract state is left in storage.
+        let storage = crate::mock::with_mocked_blockchain(|b| b.take_storage());
+        assert_eq!(storage.keys().collect::<Vec<_>>(), [&b"STATE".to_vec()]);
+    }
+
     #[test]
     fn basic_functionality() {
         let mut map = IterableMap::new(b"b");
@@ -7,6 +7,7 @@ mod iter;
 pub use self::iter::{Difference, Drain, Intersection, Iter, SymmetricDifference, Union};
 use super::{LookupMap, ERR_INCONSISTENT_STATE};
 use crate::store::key::{Sha256, ToKey};
+use crate::store::prefixes::{field_path, StoragePrefix, StoragePrefixes};
 use crate::store::Vector;
 use crate::{env, IntoStorageKey};
 use borsh::{BorshDeserialize, BorshSerialize};
@@ -198,6 +199,43 @@ where
         }
     }
 
+    /// Removes at most `limit` values from the set and returns whether the set is empty
+    /// afterwards. This allows clearing a large set across multiple transactions, each call
+    /// continuing where the previous one stopped.
+    ///
+    /// Values are removed from the end of the vector of values, whose length is persisted with the
+    /// set, so clearing can be safely resumed in a later transaction. This is also how a set
+    /// which needs to be retired can be cleared, which isn't possible with a [`LookupSet`] as its
+    /// values are not persisted.
+    ///
+    /// [`LookupSet`]: crate::store::LookupSet
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::IterableSet;
+    ///
+    /// let mut set: IterableSet<String> = IterableSet::new(b"s");
+    /// set.insert("a".to_string());
+    /// set.insert("b".to_string());
+    ///
+    /// assert!(!set.clear_chunk(1));
+    /// assert_eq!(set.len(), 1);
+    /// assert!(set.clear_chunk(1));
+    /// ```
+    pub fn clear_chunk(&mut self, limit: u32) -> bool
+    where
+        T: BorshDeserialize + Clone,
+    {
+        for _ in 0..limit {
+            match self.elements.pop() {
+                Some(e) => self.index.set(e, None),
+                None => break,
+            }
+        }
+        self.elements.is_empty()
+    }
+
     /// Visits the values representing the difference, i.e., the values that are in `self` but not
     /// in `other`.
     ///
@@ -543,9 +581,21 @@ where
     }
 }
 
+impl<T, H> StoragePrefixes for IterableSet<T, H>
+where
+    T: BorshSerialize + Ord,
+    H: ToKey,
+{
+    fn storage_prefixes(&self, path: &str, prefixes: &mut Vec<StoragePrefix>) {
+        self.elements.storage_prefixes(&field_path(path, "elements"), prefixes);
+        self.index.storage_prefixes(&field_path(path, "index"), prefixes);
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(test)]
 mod tests {
+    use crate::env;
     use crate::store::IterableSet;
     use crate::test_utils::test_env::setup_free;
     use arbitrary::{Arbitrary, Unstructured};
@@ -554,6 +604,35 @@ mod tests {
     use rand::SeedableRng;
     use std::collections::HashSet;
 
+    #[test]
+    fn clear_chunk_across_instances() {
+        setup_free();
+        let mut set = IterableSet::new(b"j");
+        for i in 0..25u32 {
+            set.insert(i);
+        }
+        set.remove(&3);
+        // Persist the set metadata as a contract would at the end of a transaction.
+        env::state_write(&set);
+        drop(set);
+
+        let mut cleared_chunks = 0;
+        loop {
+            let mut set: IterableSet<u32> = env::state_read().unwrap();
+            let done = set.clear_chunk(10);
+            env::state_write(&set);
+            cleared_chunks += 1;
+            if done {
+                break;
+            }
+        }
+        assert_eq!(cleared_chunks, 3);
+
+        // Only the contract state is left in storage.
+        let storage = crate::mock::with_mocked_blockchain(|b| b.take_storage());
+        assert_eq!(storage.keys().collect::<Vec<_>>(), [&b"STATE".to_vec()]);
+    }
+
     #[test]
     fn basic_functionality() {
         let mut set = IterableSet::new(b"b");
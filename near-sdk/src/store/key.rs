@@ -2,20 +2,46 @@ use borsh::BorshSerialize;
 
 use crate::env;
 
-mod private {
-    /// Seal `ToKey` implementations to limit usage to the builtin implementations
-    pub trait Sealed {}
-
-    impl Sealed for super::Sha256 {}
-    impl Sealed for super::Keccak256 {}
-    impl Sealed for super::Identity {}
-}
-
 /// Trait used to generate keys to store data based on a serializable structure.
-pub trait ToKey: self::private::Sealed {
+///
+/// Besides the builtin [`Sha256`], [`Sha256Truncated`], [`Keccak256`] and [`Identity`], it can be
+/// implemented to read state written with another key scheme, such as the one of an older
+/// contract. The implementation must be deterministic, and map different keys to different
+/// storage keys, which collections don't check.
+///
+/// # Example
+/// ```
+/// use near_sdk::borsh::BorshSerialize;
+/// use near_sdk::env;
+/// use near_sdk::store::key::ToKey;
+/// use near_sdk::store::LookupMap;
+///
+/// /// Hashes the keys along with a domain separator, as the keys of a legacy contract were.
+/// pub enum LegacyHasher {}
+///
+/// impl ToKey for LegacyHasher {
+///     type KeyType = [u8; 32];
+///
+///     fn to_key<Q: ?Sized>(prefix: &[u8], key: &Q, buffer: &mut Vec<u8>) -> Self::KeyType
+///     where
+///         Q: BorshSerialize,
+///     {
+///         buffer.extend(b"legacy:");
+///         buffer.extend(prefix);
+///         key.serialize(buffer).unwrap_or_else(|_| env::abort());
+///         env::sha256_array(buffer)
+///     }
+/// }
+///
+/// let mut map = LookupMap::<String, u64, LegacyHasher>::with_hasher(b"m");
+/// map.insert("key".to_string(), 1);
+/// ```
+pub trait ToKey {
     /// Output type for the generated lookup key.
     type KeyType: AsRef<[u8]>;
 
+    /// Returns the storage key of `key` in the collection with the given `prefix`. `buffer` is an
+    /// empty buffer which can be used to serialize them, so that it's reused across calls.
     fn to_key<Q: ?Sized>(prefix: &[u8], key: &Q, buffer: &mut Vec<u8>) -> Self::KeyType
     where
         Q: BorshSerialize;
@@ -40,6 +66,53 @@ impl ToKey for Sha256 {
     }
 }
 
+/// Sha256 hash helper which keeps only the first `N` bytes of the hash, to reduce the size of the
+/// keys and so the storage staked by the collections. `N` must be between 8 and 32.
+///
+/// Two keys which collide share the same entry, so the value of one silently overwrites the other.
+/// With `N` bytes, a collision between random keys becomes likely after about `2^(4 * N)` keys,
+/// which is about 4 billion keys for the minimum of 8 bytes. The keys of a collection can however
+/// be chosen by the callers of the contract, who can search for colliding keys offline with about
+/// as much work, so `N` shouldn't be much lower than 16 in this case.
+///
+/// # Example
+/// ```
+/// use near_sdk::store::{key::Sha256Truncated, LookupMap};
+///
+/// let map = LookupMap::<String, String, Sha256Truncated<20>>::with_hasher(b"m");
+/// ```
+///
+/// Hashes shorter than 8 bytes are rejected at compile time:
+/// ```compile_fail
+/// use near_sdk::store::{key::Sha256Truncated, LookupMap};
+///
+/// let map = LookupMap::<String, String, Sha256Truncated<4>>::with_hasher(b"m");
+/// map.get("key");
+/// ```
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Sha256Truncated<const N: usize> {}
+
+impl<const N: usize> Sha256Truncated<N> {
+    const VALID_LENGTH: () =
+        assert!(N >= 8 && N <= 32, "Sha256Truncated must be between 8 and 32 bytes long");
+}
+
+impl<const N: usize> ToKey for Sha256Truncated<N> {
+    type KeyType = [u8; N];
+
+    fn to_key<Q: ?Sized>(prefix: &[u8], key: &Q, buffer: &mut Vec<u8>) -> Self::KeyType
+    where
+        Q: BorshSerialize,
+    {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::VALID_LENGTH;
+        let hash = Sha256::to_key(prefix, key, buffer);
+        let mut truncated = [0; N];
+        truncated.copy_from_slice(&hash[..N]);
+        truncated
+    }
+}
+
 /// Keccak256 hash helper which hashes through a syscall. This type satisfies the [`ToKey`] trait.
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum Keccak256 {}
@@ -76,3 +149,44 @@ impl ToKey for Identity {
         buffer.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[allow(deprecated)]
+    use crate::store::{IterableMap, LookupMap, TreeMap, UnorderedMap};
+
+    #[test]
+    fn truncated_sha256() {
+        let full = Sha256::to_key(b"m", "key", &mut Vec::new());
+        let truncated = Sha256Truncated::<12>::to_key(b"m", "key", &mut Vec::new());
+        assert_eq!(truncated, full[..12]);
+        assert_eq!(Sha256Truncated::<32>::to_key(b"m", "key", &mut Vec::new()), full);
+    }
+
+    #[test]
+    fn truncated_keys_in_collections() {
+        let mut lookup_map = LookupMap::<u32, u32, Sha256Truncated<8>>::with_hasher(b"l");
+        let mut iterable_map = IterableMap::<u32, u32, Sha256Truncated<8>>::with_hasher(b"i");
+        let mut tree_map = TreeMap::<u32, u32, Sha256Truncated<8>>::with_hasher(b"t");
+        #[allow(deprecated)]
+        let mut unordered_map = UnorderedMap::<u32, u32, Sha256Truncated<8>>::with_hasher(b"u");
+        for i in 0..10 {
+            lookup_map.insert(i, i);
+            iterable_map.insert(i, i);
+            tree_map.insert(i, i);
+            unordered_map.insert(i, i);
+        }
+        lookup_map.flush();
+        iterable_map.flush();
+        tree_map.flush();
+        unordered_map.flush();
+
+        let key = Sha256Truncated::<8>::to_key(b"l", &3u32, &mut Vec::new());
+        assert_eq!(env::storage_read(&key), Some(borsh::to_vec(&3u32).unwrap()));
+        assert_eq!(iterable_map.get(&3), Some(&3));
+        assert_eq!(tree_map.get(&3), Some(&3));
+        assert_eq!(unordered_map.get(&3), Some(&3));
+        assert_eq!(tree_map.range(2..5).count(), 3);
+    }
+}
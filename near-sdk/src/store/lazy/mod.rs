@@ -12,6 +12,8 @@ use once_cell::unsync::OnceCell;
 use near_sdk_macros::near;
 
 use crate::env;
+use crate::store::accounting;
+use crate::store::prefixes::{StoragePrefix, StoragePrefixes};
 use crate::store::ERR_INCONSISTENT_STATE;
 use crate::utils::{CacheEntry, EntryState};
 use crate::IntoStorageKey;
@@ -43,7 +45,7 @@ where
     T: BorshSerialize,
 {
     let serialized = to_vec(value).unwrap_or_else(|_| env::panic_str(ERR_VALUE_SERIALIZATION));
-    env::storage_write(key, &serialized);
+    accounting::storage_write(key, key, &serialized);
 }
 
 /// An persistent lazily loaded value, that stores a value in the storage.
@@ -122,7 +124,7 @@ where
 
     /// Removes the underlying storage item. Useful for deprecating the obsolete [`Lazy`] values.
     pub fn remove(&mut self) -> bool {
-        env::storage_remove(&self.storage_key)
+        accounting::storage_remove(&self.storage_key, &self.storage_key)
     }
 }
 
@@ -154,6 +156,15 @@ where
     }
 }
 
+impl<T> StoragePrefixes for Lazy<T>
+where
+    T: BorshSerialize,
+{
+    fn storage_prefixes(&self, path: &str, prefixes: &mut Vec<StoragePrefix>) {
+        prefixes.push(StoragePrefix::new(path, &self.storage_key));
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(test)]
 mod tests {
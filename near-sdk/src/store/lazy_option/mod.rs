@@ -5,7 +5,9 @@ use near_sdk_macros::near;
 use once_cell::unsync::OnceCell;
 
 use crate::env;
+use crate::store::accounting;
 use crate::store::lazy::{load_and_deserialize, serialize_and_store};
+use crate::store::prefixes::{StoragePrefix, StoragePrefixes};
 use crate::utils::{CacheEntry, EntryState};
 use crate::IntoStorageKey;
 
@@ -89,7 +91,7 @@ where
             match v.value().as_ref() {
                 Some(value) => serialize_and_store(&self.prefix, value),
                 None => {
-                    env::storage_remove(&self.prefix);
+                    accounting::storage_remove(&self.prefix, &self.prefix);
                 }
             }
 
@@ -122,6 +124,15 @@ where
     }
 }
 
+impl<T> StoragePrefixes for LazyOption<T>
+where
+    T: BorshSerialize,
+{
+    fn storage_prefixes(&self, path: &str, prefixes: &mut Vec<StoragePrefix>) {
+        prefixes.push(StoragePrefix::new(path, &self.prefix));
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(test)]
 mod tests {
@@ -9,7 +9,9 @@ use near_sdk_macros::near;
 use once_cell::unsync::OnceCell;
 
 use super::ERR_NOT_EXIST;
+use crate::store::accounting;
 use crate::store::key::{Identity, ToKey};
+use crate::store::prefixes::{StoragePrefix, StoragePrefixes};
 use crate::utils::{EntryState, StableMap};
 use crate::{env, CacheEntry, IntoStorageKey};
 
@@ -201,6 +203,100 @@ where
             }
         }
     }
+
+    /// Returns the serialized value of a modified entry of the cache: `None` if the key is not
+    /// cached or unmodified, in which case the value in storage is up to date, and `Some(None)` if
+    /// the value was removed.
+    fn modified_raw<Q: ?Sized>(&self, k: &Q) -> Option<Option<Vec<u8>>>
+    where
+        K: Borrow<Q>,
+        Q: Ord,
+    {
+        self.cache
+            .map_value_ref(k, |v| {
+                v.value.get().filter(|entry| entry.is_modified()).map(|entry| {
+                    entry.value().as_ref().map(|value| {
+                        borsh::to_vec(value)
+                            .unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_SERIALIZATION))
+                    })
+                })
+            })
+            .flatten()
+    }
+
+    /// Returns the serialized bytes of the value corresponding to the key.
+    ///
+    /// Unlike [`LookupMap::get`], the value is neither deserialized nor loaded in the cache, which
+    /// avoids a deserialization when the bytes are only passed on, such as in a view method
+    /// returning a large value. A value modified since it was loaded is serialized again.
+    ///
+    /// The key may be any borrowed form of the map's key type, but
+    /// [`BorshSerialize`] on the borrowed form *must* match that of the key type.
+    ///
+    /// # Example
+    /// ```
+    /// use near_sdk::store::LookupMap;
+    ///
+    /// let mut map: LookupMap<u32, Vec<u8>> = LookupMap::new(b"m");
+    ///
+    /// map.insert(1, vec![1, 2, 3]);
+    /// assert_eq!(map.get_raw(&1), Some(borsh::to_vec(&vec![1u8, 2, 3]).unwrap()));
+    /// assert_eq!(map.get_raw(&2), None);
+    /// ```
+    pub fn get_raw<Q: ?Sized>(&self, k: &Q) -> Option<Vec<u8>>
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + Ord,
+    {
+        match self.modified_raw(k) {
+            Some(value) => value,
+            None => env::storage_read(H::to_key(&self.prefix, k, &mut Vec::new()).as_ref()),
+        }
+    }
+
+    /// Sets the serialized bytes of the value corresponding to the key as the return value of the
+    /// method, and returns `false` without setting it if there is no value for the key.
+    ///
+    /// The bytes are returned directly from storage with [`env::value_return_from_storage`],
+    /// without being deserialized nor copied into the memory of the contract, unless the value was
+    /// modified since it was loaded. As the bytes are the Borsh serialization of the value, this
+    /// is meant for methods returning Borsh, which must not also return a value themselves.
+    ///
+    /// # Example
+    /// ```
+    /// use near_sdk::store::LookupMap;
+    /// use near_sdk::{near, PanicOnDefault};
+    ///
+    /// #[near(contract_state)]
+    /// #[derive(PanicOnDefault)]
+    /// pub struct Contract {
+    ///     blobs: LookupMap<u32, Vec<u8>>,
+    /// }
+    ///
+    /// #[near]
+    /// impl Contract {
+    ///     /// Returns the Borsh serialized blob, or nothing if there is no blob with this id.
+    ///     pub fn get_blob(&self, id: u32) {
+    ///         self.blobs.return_raw(&id);
+    ///     }
+    /// }
+    /// ```
+    pub fn return_raw<Q: ?Sized>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + Ord,
+    {
+        match self.modified_raw(k) {
+            Some(Some(value)) => {
+                env::value_return(&value);
+                true
+            }
+            Some(None) => false,
+            None => {
+                env::value_return_from_storage(H::to_key(&self.prefix, k, &mut Vec::new()).as_ref())
+            }
+        }
+    }
 }
 
 impl<K, V, H> LookupMap<K, V, H>
@@ -443,11 +539,11 @@ where
                             buf.clear();
                             BorshSerialize::serialize(modified, &mut buf)
                                 .unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_SERIALIZATION));
-                            env::storage_write(key.as_ref(), &buf);
+                            accounting::storage_write(prefix, key.as_ref(), &buf);
                         }
                         None => {
                             // Element was removed, clear the storage for the value
-                            env::storage_remove(key.as_ref());
+                            accounting::storage_remove(prefix, key.as_ref());
                         }
                     }
 
@@ -460,12 +556,24 @@ where
     }
 }
 
+impl<K, V, H> StoragePrefixes for LookupMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    fn storage_prefixes(&self, path: &str, prefixes: &mut Vec<StoragePrefix>) {
+        prefixes.push(StoragePrefix::new(path, &self.prefix));
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(test)]
 mod tests {
     use super::LookupMap;
     use crate::env;
     use crate::store::key::{Keccak256, ToKey};
+    use crate::test_utils::get_return_value;
     use crate::test_utils::test_env::setup_free;
     use arbitrary::{Arbitrary, Unstructured};
     use rand::seq::SliceRandom;
@@ -473,6 +581,39 @@ mod tests {
     use rand::{Rng, SeedableRng};
     use std::collections::HashMap;
 
+    #[test]
+    fn raw_values() {
+        let mut map = LookupMap::new(b"m");
+        map.insert(1u8, "stored".to_string());
+        map.flush();
+        map.insert(2u8, "cached".to_string());
+
+        let stored = borsh::to_vec("stored").unwrap();
+        assert_eq!(map.get_raw(&1), Some(stored.clone()));
+        assert_eq!(map.get_raw(&2), Some(borsh::to_vec("cached").unwrap()));
+        assert_eq!(map.get_raw(&3), None);
+
+        // Raw reads don't load the values in the cache.
+        let map = LookupMap::<u8, String>::new(b"m");
+        assert_eq!(map.get_raw(&1), Some(stored.clone()));
+        assert!(map.cache.map_value_ref(&1, |_| ()).is_none());
+
+        assert!(!map.return_raw(&3));
+        assert_eq!(get_return_value(), None);
+        assert!(map.return_raw(&1));
+        assert_eq!(get_return_value(), Some(stored));
+    }
+
+    #[test]
+    fn raw_values_removed() {
+        let mut map = LookupMap::new(b"m");
+        map.insert(1u8, 2u8);
+        map.flush();
+        map.remove(&1);
+        assert_eq!(map.get_raw(&1), None);
+        assert!(!map.return_raw(&1));
+    }
+
     #[test]
     fn test_insert() {
         let mut map = LookupMap::new(b"m");
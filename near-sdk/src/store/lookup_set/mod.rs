@@ -1,5 +1,6 @@
 mod impls;
 
+use crate::store::accounting;
 use crate::store::key::{Identity, ToKey};
 use crate::{env, IntoStorageKey};
 use borsh::BorshSerialize;
@@ -7,6 +8,7 @@ use std::borrow::Borrow;
 use std::fmt;
 use std::marker::PhantomData;
 
+use crate::store::prefixes::{StoragePrefix, StoragePrefixes};
 use near_sdk_macros::near;
 
 /// A non-iterable implementation of a set that stores its content directly on the storage trie.
@@ -125,7 +127,7 @@ where
     /// If the set did have this value present, false is returned.
     pub fn insert(&mut self, value: T) -> bool {
         let lookup_key = H::to_key(&self.prefix, &value, &mut Vec::new());
-        !env::storage_write(lookup_key.as_ref(), &[])
+        !accounting::storage_write(&self.prefix, lookup_key.as_ref(), &[])
     }
 
     /// Removes a value from the set. Returns whether the value was present in the set.
@@ -138,7 +140,17 @@ where
         Q: BorshSerialize,
     {
         let lookup_key = H::to_key(&self.prefix, value, &mut Vec::new());
-        env::storage_remove(lookup_key.as_ref())
+        accounting::storage_remove(&self.prefix, lookup_key.as_ref())
+    }
+}
+
+impl<T, H> StoragePrefixes for LookupSet<T, H>
+where
+    T: BorshSerialize,
+    H: ToKey,
+{
+    fn storage_prefixes(&self, path: &str, prefixes: &mut Vec<StoragePrefix>) {
+        prefixes.push(StoragePrefix::new(path, &self.prefix));
     }
 }
 
@@ -0,0 +1,371 @@
+//! Helpers to migrate the entries of a collection into another one across multiple transactions.
+//!
+//! Migrating a large collection, e.g. from a legacy [`collections`](crate::collections) type to a
+//! [`store`](crate::store) one, or into a new value layout, cannot fit into the gas limit of one
+//! transaction. [`migrate_chunk`] copies entries from a [`MigrationSource`] into a
+//! [`MigrationDestination`] until the gas budget of the current transaction is reached, and
+//! records where it stopped in a [`MigrationCursor`], which the contract persists in its state
+//! and passes to the next call.
+//!
+//! The source is only read, so it must not be modified while the migration is in progress.
+//! Once the migration is complete, it can be cleared, e.g. with
+//! [`IterableMap::clear_chunk`](crate::store::IterableMap::clear_chunk) or
+//! [`IterableSet::clear_chunk`](crate::store::IterableSet::clear_chunk).
+//!
+//! # Examples
+//! ```
+//! use near_sdk::collections::UnorderedMap;
+//! use near_sdk::store::migrate::{migrate_chunk, MigrationCursor};
+//! use near_sdk::store::IterableMap;
+//! use near_sdk::Gas;
+//!
+//! let mut legacy: UnorderedMap<String, u64> = UnorderedMap::new(b"l");
+//! legacy.insert(&"alice".to_string(), &10);
+//! legacy.insert(&"bob".to_string(), &20);
+//!
+//! // Both would usually be fields of the contract state.
+//! let mut balances: IterableMap<String, u128> = IterableMap::new(b"b");
+//! let mut cursor = MigrationCursor::default();
+//!
+//! // Called once per transaction until the migration reports completion.
+//! let status = migrate_chunk(&legacy, &mut balances, &mut cursor, Gas::from_tgas(10), |(k, v)| {
+//!     Some((k, u128::from(v)))
+//! });
+//! assert!(status.is_complete());
+//! assert_eq!(balances.get("bob"), Some(&20));
+//! ```
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk_macros::near;
+
+#[cfg(feature = "legacy")]
+use crate::collections;
+use crate::store::key::ToKey;
+use crate::{env, store, Gas};
+
+/// Position of a migration in its source, to be persisted between transactions.
+#[near(inside_nearsdk)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MigrationCursor {
+    next_index: u64,
+}
+
+impl MigrationCursor {
+    /// Index of the next source entry to migrate, which is also the number of entries read so
+    /// far.
+    pub fn next_index(&self) -> u64 {
+        self.next_index
+    }
+}
+
+/// Outcome of a call to [`migrate_chunk`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationStatus {
+    /// The gas budget was reached before the end of the source. Call [`migrate_chunk`] again
+    /// with the same cursor in another transaction.
+    InProgress {
+        /// Number of entries read from the source by this call.
+        migrated: u64,
+        /// Number of entries left in the source.
+        remaining: u64,
+    },
+    /// All entries of the source were migrated.
+    Complete {
+        /// Number of entries read from the source by this call.
+        migrated: u64,
+    },
+}
+
+impl MigrationStatus {
+    /// Returns `true` if all entries of the source were migrated.
+    pub fn is_complete(&self) -> bool {
+        matches!(self, Self::Complete { .. })
+    }
+}
+
+/// Collection which entries can be read by index for a migration.
+pub trait MigrationSource {
+    type Item;
+
+    /// Returns the number of entries in the collection.
+    fn migration_len(&self) -> u64;
+
+    /// Returns the entry at `index`, which is lower than [`migration_len`](Self::migration_len).
+    fn migration_item(&self, index: u64) -> Self::Item;
+}
+
+/// Collection which migrated entries can be inserted into.
+pub trait MigrationDestination {
+    type Item;
+
+    /// Inserts a migrated entry.
+    fn migration_insert(&mut self, item: Self::Item);
+}
+
+/// Migrates entries from `source` into `destination`, starting at `cursor`, until either all
+/// entries are migrated or the gas left in the transaction gets below `reserved_gas` plus the
+/// gas the most expensive entry took so far. `transform` converts each entry, and returning
+/// [`None`] skips it.
+///
+/// `reserved_gas` must cover everything the transaction does after this call, including the
+/// flush of `destination` when it is written to storage.
+pub fn migrate_chunk<S, D, F>(
+    source: &S,
+    destination: &mut D,
+    cursor: &mut MigrationCursor,
+    reserved_gas: Gas,
+    mut transform: F,
+) -> MigrationStatus
+where
+    S: MigrationSource,
+    D: MigrationDestination,
+    F: FnMut(S::Item) -> Option<D::Item>,
+{
+    let len = source.migration_len();
+    let prepaid_gas = env::prepaid_gas();
+    let mut max_entry_gas = Gas::from_gas(0);
+    let mut migrated = 0;
+    while cursor.next_index < len {
+        let used_gas = env::used_gas();
+        if used_gas.saturating_add(max_entry_gas).saturating_add(reserved_gas) > prepaid_gas {
+            return MigrationStatus::InProgress { migrated, remaining: len - cursor.next_index };
+        }
+        if let Some(item) = transform(source.migration_item(cursor.next_index)) {
+            destination.migration_insert(item);
+        }
+        cursor.next_index += 1;
+        migrated += 1;
+        max_entry_gas = max_entry_gas.max(env::used_gas().saturating_sub(used_gas));
+    }
+    MigrationStatus::Complete { migrated }
+}
+
+fn expect_item<T>(item: Option<T>) -> T {
+    item.unwrap_or_else(|| env::panic_str(super::ERR_INCONSISTENT_STATE))
+}
+
+impl<T> MigrationSource for store::Vector<T>
+where
+    T: BorshSerialize + BorshDeserialize + Clone,
+{
+    type Item = T;
+
+    fn migration_len(&self) -> u64 {
+        self.len().into()
+    }
+
+    fn migration_item(&self, index: u64) -> T {
+        expect_item(self.get(index as u32)).clone()
+    }
+}
+
+impl<K, V, H> MigrationSource for store::IterableMap<K, V, H>
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone,
+    V: BorshSerialize + BorshDeserialize + Clone,
+    H: ToKey,
+{
+    type Item = (K, V);
+
+    fn migration_len(&self) -> u64 {
+        self.len().into()
+    }
+
+    fn migration_item(&self, index: u64) -> (K, V) {
+        let (k, v) = expect_item(self.iter().nth(index as usize));
+        (k.clone(), v.clone())
+    }
+}
+
+impl<T, H> MigrationSource for store::IterableSet<T, H>
+where
+    T: BorshSerialize + BorshDeserialize + Ord + Clone,
+    H: ToKey,
+{
+    type Item = T;
+
+    fn migration_len(&self) -> u64 {
+        self.len().into()
+    }
+
+    fn migration_item(&self, index: u64) -> T {
+        expect_item(self.iter().nth(index as usize)).clone()
+    }
+}
+
+#[cfg(feature = "legacy")]
+impl<T> MigrationSource for collections::Vector<T>
+where
+    T: BorshDeserialize,
+{
+    type Item = T;
+
+    fn migration_len(&self) -> u64 {
+        self.len()
+    }
+
+    fn migration_item(&self, index: u64) -> T {
+        expect_item(self.get(index))
+    }
+}
+
+#[cfg(feature = "legacy")]
+impl<K, V> MigrationSource for collections::UnorderedMap<K, V>
+where
+    K: BorshSerialize + BorshDeserialize,
+    V: BorshSerialize + BorshDeserialize,
+{
+    type Item = (K, V);
+
+    fn migration_len(&self) -> u64 {
+        self.len()
+    }
+
+    fn migration_item(&self, index: u64) -> (K, V) {
+        (
+            expect_item(self.keys_as_vector().get(index)),
+            expect_item(self.values_as_vector().get(index)),
+        )
+    }
+}
+
+#[cfg(feature = "legacy")]
+impl<T> MigrationSource for collections::UnorderedSet<T>
+where
+    T: BorshSerialize + BorshDeserialize,
+{
+    type Item = T;
+
+    fn migration_len(&self) -> u64 {
+        self.len()
+    }
+
+    fn migration_item(&self, index: u64) -> T {
+        expect_item(self.as_vector().get(index))
+    }
+}
+
+impl<T> MigrationDestination for store::Vector<T>
+where
+    T: BorshSerialize,
+{
+    type Item = T;
+
+    fn migration_insert(&mut self, item: T) {
+        self.push(item);
+    }
+}
+
+impl<K, V, H> MigrationDestination for store::LookupMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    type Item = (K, V);
+
+    fn migration_insert(&mut self, (k, v): (K, V)) {
+        // Set instead of insert to avoid loading the previous value from storage.
+        self.set(k, Some(v));
+    }
+}
+
+impl<K, V, H> MigrationDestination for store::IterableMap<K, V, H>
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+    type Item = (K, V);
+
+    fn migration_insert(&mut self, (k, v): (K, V)) {
+        self.insert(k, v);
+    }
+}
+
+impl<T, H> MigrationDestination for store::LookupSet<T, H>
+where
+    T: BorshSerialize,
+    H: ToKey,
+{
+    type Item = T;
+
+    fn migration_insert(&mut self, item: T) {
+        self.insert(item);
+    }
+}
+
+impl<T, H> MigrationDestination for store::IterableSet<T, H>
+where
+    T: BorshSerialize + BorshDeserialize + Ord + Clone,
+    H: ToKey,
+{
+    type Item = T;
+
+    fn migration_insert(&mut self, item: T) {
+        self.insert(item);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::VMContextBuilder;
+    use crate::testing_env;
+
+    #[test]
+    fn migrate_across_transactions() {
+        let mut source = store::Vector::new(b"s");
+        source.extend(0..200u32);
+        source.flush();
+
+        let mut destination = store::IterableMap::new(b"d");
+        let mut cursor = MigrationCursor::default();
+        let mut calls = 0;
+        loop {
+            // Every call runs in a fresh transaction with a small amount of gas.
+            testing_env!(VMContextBuilder::new().prepaid_gas(Gas::from_tgas(20)).build());
+            calls += 1;
+            let status =
+                migrate_chunk(&source, &mut destination, &mut cursor, Gas::from_tgas(15), |v| {
+                    // Only keep even values, keyed by their half.
+                    (v % 2 == 0).then_some((v / 2, v))
+                });
+            assert!(env::used_gas() <= Gas::from_tgas(15));
+            destination.flush();
+            match status {
+                MigrationStatus::InProgress { migrated, remaining } => {
+                    assert!(migrated > 0);
+                    assert_eq!(remaining, 200 - cursor.next_index());
+                }
+                MigrationStatus::Complete { .. } => break,
+            }
+        }
+        assert!(calls > 1);
+        assert_eq!(cursor.next_index(), 200);
+        assert_eq!(destination.len(), 100);
+        assert_eq!(destination.get(&50), Some(&100));
+    }
+
+    #[cfg(feature = "legacy")]
+    #[test]
+    fn migrate_legacy_unordered_set() {
+        let mut source = collections::UnorderedSet::new(b"s");
+        for v in ["a", "b", "c"] {
+            source.insert(&v.to_string());
+        }
+        let mut destination = store::IterableSet::new(b"d");
+        let mut cursor = MigrationCursor::default();
+
+        let status =
+            migrate_chunk(&source, &mut destination, &mut cursor, Gas::from_tgas(10), Some);
+        assert_eq!(status, MigrationStatus::Complete { migrated: 3 });
+        assert!(destination.contains("b"));
+
+        // Migrating again with a completed cursor is a no-op.
+        let status =
+            migrate_chunk(&source, &mut destination, &mut cursor, Gas::from_tgas(10), Some);
+        assert_eq!(status, MigrationStatus::Complete { migrated: 0 });
+    }
+}
@@ -43,22 +43,40 @@
 //!
 //! - [`Vector`]: Analogous to [`Vec`] but not contiguous and persisted to storage.
 //!
+//! - [`Deque`]: Analogous to [`std::collections::VecDeque`], with O(1) pushes and pops at both
+//!   ends.
+//!
+//! - [`PriorityQueue`]: Analogous to [`std::collections::BinaryHeap`], pops the greatest element
+//!   first.
+//!
 //! Maps:
 //!
 //! - [`LookupMap`]: Wrapper around key-value storage interactions, similar to
 //!   [`UnorderedMap`]/[`std::collections::HashMap`] except that keys are not persisted and cannot be
 //!   iterated over.
 //!
+//! - [`IterableMap`]: [`LookupMap`] which also persists its keys in a [`Vector`], so that it can
+//!   be iterated over and cleared in chunks across multiple transactions with
+//!   [`IterableMap::clear_chunk`].
+//!
+//! - [`ExpiringMap`]: [`LookupMap`] whose entries expire a fixed duration after they are
+//!   inserted, based on the block timestamp, and can be evicted in bounded batches.
+//!
 //! - [`UnorderedMap`]: Storage version of [`std::collections::HashMap`]. No ordering
 //!   guarantees.
 //!
-//! - [`TreeMap`](TreeMap) (`unstable`): Storage version of [`std::collections::BTreeMap`]. Ordered by key,
-//!   which comes at the cost of more expensive lookups and iteration.
+//! - [`TreeMap`](TreeMap): Storage version of [`std::collections::BTreeMap`]. Ordered by key,
+//!   which comes at the cost of more expensive lookups and iteration. Also supports
+//!   order-statistic queries such as the rank of a key or the key at an index.
 //!
 //! Sets:
 //!
 //! - [`LookupSet`]: Non-iterable storage version of [`std::collections::HashSet`].
 //!
+//! - [`IterableSet`]: [`LookupSet`] which also persists its values in a [`Vector`], so that it can
+//!   be iterated over and cleared in chunks across multiple transactions with
+//!   [`IterableSet::clear_chunk`].
+//!
 //! - [`UnorderedSet`]: Analogous to [`std::collections::HashSet`], and is an iterable
 //!   version of [`LookupSet`] and persisted to storage.
 //!
@@ -70,6 +88,48 @@
 //! - [`LazyOption<T>`](LazyOption): Lazily loaded, optional type that can be used in
 //!   place of a type [`Option<T>`](Option). Will only be loaded when interacted with and will
 //!   persist on [`Drop`].
+//!
+//! The keys of a [`LookupMap`] and the values of a [`LookupSet`] are not persisted, so the entries
+//! of one which is retired can't be found to be removed. Collections which may have to be cleared
+//! should be an [`IterableMap`] or an [`IterableSet`] instead. An existing [`LookupMap`] can be
+//! moved into an [`IterableMap`] with [`migrate::migrate_chunk`] when its keys are known from
+//! another iterable collection of the contract, removing each entry from the [`LookupMap`] as it's
+//! migrated so that its storage is released:
+//!
+//! ```
+//! use near_sdk::store::migrate::{migrate_chunk, MigrationCursor};
+//! use near_sdk::store::{IterableMap, IterableSet, LookupMap};
+//! use near_sdk::{AccountId, Gas};
+//!
+//! let alice: AccountId = "alice.near".parse().unwrap();
+//! let mut accounts: IterableSet<AccountId> = IterableSet::new(b"a");
+//! let mut balances: LookupMap<AccountId, u128> = LookupMap::new(b"b");
+//! accounts.insert(alice.clone());
+//! balances.insert(alice.clone(), 10);
+//!
+//! // Both would usually be fields of the contract state.
+//! let mut migrated: IterableMap<AccountId, u128> = IterableMap::new(b"m");
+//! let mut cursor = MigrationCursor::default();
+//!
+//! // Called once per transaction until the migration reports completion.
+//! let status = migrate_chunk(&accounts, &mut migrated, &mut cursor, Gas::from_tgas(10), |id| {
+//!     balances.remove(&id).map(|balance| (id, balance))
+//! });
+//! assert!(status.is_complete());
+//! assert_eq!(migrated.get(&alice), Some(&10));
+//! assert!(!balances.contains_key(&alice));
+//!
+//! // Unlike the `LookupMap`, the new map can be cleared in chunks once it is retired.
+//! assert!(migrated.clear_chunk(100));
+//! ```
+//!
+//! The storage written and freed by the collections can be accounted with a [`StorageScope`] or
+//! [`storage_delta`], for example to charge the account which caused it.
+//!
+//! Each collection must use a storage prefix which is not equal to, nor a prefix of, the prefix
+//! of another collection of the contract. `#[near(auto_prefix)]` derives such prefixes from the
+//! fields of the contract state, and [`check_storage_prefixes`] detects overlapping prefixes in
+//! unit tests.
 
 #[cfg(feature = "unstable")]
 mod lazy;
@@ -84,9 +144,18 @@ pub use lazy_option::LazyOption;
 pub mod vec;
 pub use vec::Vector;
 
+pub mod deque;
+pub use deque::Deque;
+
+pub mod priority_queue;
+pub use priority_queue::PriorityQueue;
+
 pub mod lookup_map;
 pub use self::lookup_map::LookupMap;
 
+pub mod expiring_map;
+pub use self::expiring_map::ExpiringMap;
+
 mod lookup_set;
 pub use self::lookup_set::LookupSet;
 
@@ -102,9 +171,7 @@ pub mod unordered_set;
 #[allow(deprecated)]
 pub use self::unordered_set::UnorderedSet;
 
-#[cfg(feature = "unstable")]
 pub mod tree_map;
-#[cfg(feature = "unstable")]
 pub use self::tree_map::TreeMap;
 
 mod index_map;
@@ -116,6 +183,16 @@ pub(crate) use self::free_list::FreeList;
 /// Storage key hash function types and trait to override map hash functions.
 pub mod key;
 
+pub(crate) mod prefixes;
+pub use self::prefixes::{
+    check_storage_prefixes, storage_prefixes, PrefixOverlap, StoragePrefix, StoragePrefixes,
+};
+
+pub(crate) mod accounting;
+pub use self::accounting::{storage_delta, StorageDelta, StorageScope};
+
+pub mod migrate;
+
 pub(crate) const ERR_INCONSISTENT_STATE: &str =
     "The collection is in an inconsistent state. Did previous smart \
         contract execution terminate unexpectedly?";
@@ -0,0 +1,198 @@
+use std::fmt;
+
+/// Key under which the state of the contract is stored.
+const STATE_KEY: &[u8] = b"STATE";
+
+/// Types which store values under storage prefixes, such as the collections, to check that the
+/// prefixes of all collections in a contract state are unique with [`check_storage_prefixes`].
+///
+/// It is implemented for the collections of this module and of [`collections`](crate::collections),
+/// and derived by `#[near]` for the structs serialized with Borsh, which report the prefixes of
+/// their fields. Fields of other types, such as numbers or strings, don't report any prefix.
+pub trait StoragePrefixes {
+    /// Adds the storage prefixes used by `self` to `prefixes`, where `path` is the path of `self`
+    /// in the contract state, such as `token.accounts`.
+    fn storage_prefixes(&self, path: &str, prefixes: &mut Vec<StoragePrefix>);
+}
+
+/// Storage prefix used by a collection, reported by [`StoragePrefixes`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StoragePrefix {
+    /// Path of the collection in the contract state, such as `token.accounts`. Collections which
+    /// use several prefixes append the name of their inner collection, such as `tokens.keys`.
+    pub path: String,
+    /// Bytes prefixed to the keys of the values stored by the collection.
+    pub prefix: Vec<u8>,
+}
+
+impl StoragePrefix {
+    pub(crate) fn new(path: &str, prefix: &[u8]) -> Self {
+        Self { path: path.to_string(), prefix: prefix.to_vec() }
+    }
+}
+
+impl fmt::Display for StoragePrefix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` (b\"{}\")", self.path, self.prefix.escape_ascii())
+    }
+}
+
+/// Overlap between two storage prefixes found by [`check_storage_prefixes`]: `prefix` is equal to
+/// or a prefix of `overlapping`, so the keys of their collections can collide.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PrefixOverlap {
+    /// Shorter of the overlapping prefixes.
+    pub prefix: StoragePrefix,
+    /// Prefix starting with `prefix`. Its path is `STATE` when `prefix` overlaps with the key of
+    /// the contract state.
+    pub overlapping: StoragePrefix,
+}
+
+impl fmt::Display for PrefixOverlap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.prefix.prefix == self.overlapping.prefix {
+            write!(f, "storage prefix of {} is also used by {}", self.prefix, self.overlapping)
+        } else {
+            write!(f, "storage prefix of {} is a prefix of {}", self.prefix, self.overlapping)
+        }
+    }
+}
+
+impl std::error::Error for PrefixOverlap {}
+
+/// Returns the storage prefixes of all collections in `state`, including the ones of nested
+/// structs, ordered by prefix.
+pub fn storage_prefixes<T: StoragePrefixes + ?Sized>(state: &T) -> Vec<StoragePrefix> {
+    let mut prefixes = Vec::new();
+    state.storage_prefixes("", &mut prefixes);
+    prefixes.sort_by(|a, b| a.prefix.cmp(&b.prefix));
+    prefixes
+}
+
+/// Checks that no two collections in `state` share a storage prefix, including the collections
+/// of nested structs, and that no prefix is a prefix of another one, or of the key under which the
+/// contract state is stored. Any of these makes the keys of different values collide, which
+/// silently corrupts the state.
+///
+/// The collections of a contract only exist once it's initialized, so this is meant to be called
+/// in unit tests on the state returned by the initialization methods.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::store::{check_storage_prefixes, IterableMap, LookupMap};
+/// use near_sdk::near;
+///
+/// #[near(contract_state)]
+/// pub struct Contract {
+///     balances: LookupMap<String, u128>,
+///     tokens: IterableMap<u32, String>,
+/// }
+///
+/// let contract = Contract { balances: LookupMap::new(b"t"), tokens: IterableMap::new(b"t") };
+/// let overlap = check_storage_prefixes(&contract).unwrap_err();
+/// assert_eq!(overlap.prefix.path, "balances");
+/// assert_eq!(overlap.overlapping.path, "tokens.values");
+///
+/// let contract = Contract { balances: LookupMap::new(b"b"), tokens: IterableMap::new(b"t") };
+/// assert!(check_storage_prefixes(&contract).is_ok());
+/// ```
+pub fn check_storage_prefixes<T: StoragePrefixes + ?Sized>(state: &T) -> Result<(), PrefixOverlap> {
+    let prefixes = storage_prefixes(state);
+    if let Some(prefix) = prefixes.iter().find(|p| STATE_KEY.starts_with(&p.prefix)) {
+        return Err(PrefixOverlap {
+            prefix: prefix.clone(),
+            overlapping: StoragePrefix::new("STATE", STATE_KEY),
+        });
+    }
+    // In sorted order, the prefixes starting with a prefix directly follow it.
+    for pair in prefixes.windows(2) {
+        if pair[1].prefix.starts_with(&pair[0].prefix) {
+            return Err(PrefixOverlap { prefix: pair[0].clone(), overlapping: pair[1].clone() });
+        }
+    }
+    Ok(())
+}
+
+/// Returns the path of the field `name` of the value at `path`.
+pub fn field_path(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", path, name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{TreeMap, Vector};
+    use crate::{collections, near};
+
+    #[near(inside_nearsdk)]
+    struct Nested {
+        accounts: collections::LookupMap<String, u128>,
+    }
+
+    #[near(inside_nearsdk)]
+    struct State {
+        nested: Nested,
+        tree: TreeMap<u32, u32>,
+        list: Vector<u8>,
+    }
+
+    impl StoragePrefixes for Nested {
+        fn storage_prefixes(&self, path: &str, prefixes: &mut Vec<StoragePrefix>) {
+            self.accounts.storage_prefixes(&field_path(path, "accounts"), prefixes);
+        }
+    }
+
+    impl StoragePrefixes for State {
+        fn storage_prefixes(&self, path: &str, prefixes: &mut Vec<StoragePrefix>) {
+            self.nested.storage_prefixes(&field_path(path, "nested"), prefixes);
+            self.tree.storage_prefixes(&field_path(path, "tree"), prefixes);
+            self.list.storage_prefixes(&field_path(path, "list"), prefixes);
+        }
+    }
+
+    fn state(accounts: &[u8], tree: &[u8], list: &[u8]) -> State {
+        State {
+            nested: Nested { accounts: collections::LookupMap::new(accounts) },
+            tree: TreeMap::new(tree),
+            list: Vector::new(list),
+        }
+    }
+
+    #[test]
+    fn nested_prefixes() {
+        let state = state(b"a", b"t", b"l");
+        let prefixes = storage_prefixes(&state);
+        let paths: Vec<_> = prefixes.iter().map(|p| p.path.as_str()).collect();
+        assert_eq!(paths, ["nested.accounts", "list", "tree.nodes", "tree.values"]);
+        assert_eq!(prefixes[2].prefix, b"tn");
+        assert!(check_storage_prefixes(&state).is_ok());
+    }
+
+    #[test]
+    fn overlaps() {
+        let overlap = check_storage_prefixes(&state(b"a", b"t", b"a")).unwrap_err();
+        assert_eq!(overlap.prefix.path, "nested.accounts");
+        assert_eq!(overlap.overlapping.path, "list");
+        assert_eq!(
+            overlap.to_string(),
+            "storage prefix of `nested.accounts` (b\"a\") is also used by `list` (b\"a\")"
+        );
+
+        let overlap = check_storage_prefixes(&state(b"a", b"t", b"t")).unwrap_err();
+        assert_eq!(overlap.prefix.path, "list");
+        assert_eq!(overlap.overlapping.path, "tree.nodes");
+        assert_eq!(
+            overlap.to_string(),
+            "storage prefix of `list` (b\"t\") is a prefix of `tree.nodes` (b\"tn\")"
+        );
+
+        let overlap = check_storage_prefixes(&state(b"ST", b"t", b"l")).unwrap_err();
+        assert_eq!(overlap.prefix.path, "nested.accounts");
+        assert_eq!(overlap.overlapping, StoragePrefix::new("STATE", STATE_KEY));
+    }
+}
@@ -0,0 +1,476 @@
+//! A priority queue with values persisted to storage and lazily loaded.
+//!
+//! Values in the [`PriorityQueue`] are kept in an in-memory cache and are only persisted on
+//! [`Drop`].
+//!
+//! # Examples
+//!
+//! The greatest element is popped first. To pop the least element first, reverse the ordering
+//! of the element type:
+//!
+//! ```
+//! use near_sdk::near;
+//! use near_sdk::store::PriorityQueue;
+//! use std::cmp::Ordering;
+//!
+//! #[near]
+//! #[derive(PartialEq, Eq, Debug)]
+//! pub struct Deadline(u64);
+//!
+//! impl Ord for Deadline {
+//!     fn cmp(&self, other: &Self) -> Ordering {
+//!         other.0.cmp(&self.0)
+//!     }
+//! }
+//!
+//! impl PartialOrd for Deadline {
+//!     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+//!         Some(self.cmp(other))
+//!     }
+//! }
+//!
+//! let mut deadlines: PriorityQueue<Deadline> = PriorityQueue::new(b"p");
+//! deadlines.push(Deadline(30));
+//! deadlines.push(Deadline(10));
+//! deadlines.push(Deadline(20));
+//!
+//! assert_eq!(deadlines.pop(), Some(Deadline(10)));
+//! assert_eq!(deadlines.peek(), Some(&Deadline(20)));
+//! ```
+
+use std::fmt;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk_macros::near;
+
+use super::vec::Iter;
+use super::{Vector, ERR_INCONSISTENT_STATE};
+use crate::store::prefixes::{StoragePrefix, StoragePrefixes};
+use crate::{env, IntoStorageKey};
+
+fn expect_consistent_state<T>(val: Option<T>) -> T {
+    val.unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE))
+}
+
+/// A priority queue implemented as a binary max-heap that stores its content on the trie. This
+/// implementation will load and store values in the underlying storage lazily.
+///
+/// Elements are stored in a [`Vector`] in heap order, so [`push`](PriorityQueue::push) and
+/// [`pop`](PriorityQueue::pop) only load and write O(log(N)) elements, and
+/// [`peek`](PriorityQueue::peek) only loads the greatest element.
+///
+/// This implementation will cache all changes and loads and only updates values that are changed
+/// in storage after it's dropped. These changes can be updated in storage before the variable is
+/// dropped by using [`PriorityQueue::flush`].
+///
+/// This type should be a drop in replacement for [`BinaryHeap`](std::collections::BinaryHeap) in
+/// most cases.
+///
+/// # Examples
+/// ```
+/// use near_sdk::store::PriorityQueue;
+///
+/// let mut bids = PriorityQueue::new(b"b");
+/// assert!(bids.is_empty());
+///
+/// bids.push(5);
+/// bids.push(12);
+/// bids.push(7);
+///
+/// assert_eq!(bids.len(), 3);
+/// assert_eq!(bids.peek(), Some(&12));
+///
+/// assert_eq!(bids.pop(), Some(12));
+/// assert_eq!(bids.pop(), Some(7));
+/// assert_eq!(bids.len(), 1);
+/// ```
+#[near(inside_nearsdk)]
+pub struct PriorityQueue<T>
+where
+    T: BorshSerialize,
+{
+    // ser/de is independent of `T` ser/de, `BorshSerialize`/`BorshDeserialize`/`BorshSchema` bounds removed
+    #[cfg_attr(not(feature = "abi"), borsh(bound(serialize = "", deserialize = "")))]
+    #[cfg_attr(
+        feature = "abi",
+        borsh(bound(serialize = "", deserialize = ""), schema(params = ""))
+    )]
+    elements: Vector<T>,
+}
+
+impl<T> PriorityQueue<T>
+where
+    T: BorshSerialize,
+{
+    /// Create new priority queue with zero elements. Prefixes storage access with the prefix
+    /// provided.
+    ///
+    /// This prefix can be anything that implements [`IntoStorageKey`]. The prefix is used when
+    /// storing and looking up values in storage to ensure no collisions with other collections.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::PriorityQueue;
+    ///
+    /// let mut queue: PriorityQueue<u8> = PriorityQueue::new(b"p");
+    /// ```
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self { elements: Vector::new(prefix) }
+    }
+
+    /// Returns the number of elements in the queue.
+    pub fn len(&self) -> u32 {
+        self.elements.len()
+    }
+
+    /// Returns `true` if the queue contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// Removes all elements from the collection. This will remove all storage values for the
+    /// length of the [`PriorityQueue`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::PriorityQueue;
+    ///
+    /// let mut queue = PriorityQueue::new(b"p");
+    /// queue.push(1);
+    ///
+    /// queue.clear();
+    ///
+    /// assert!(queue.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        self.elements.clear();
+    }
+
+    /// Flushes the cache and writes all modified values to storage.
+    ///
+    /// This operation is performed on [`Drop`], but this method can be called to persist
+    /// intermediate writes in cases where [`Drop`] is not called or to identify storage changes.
+    pub fn flush(&mut self) {
+        self.elements.flush();
+    }
+}
+
+impl<T> PriorityQueue<T>
+where
+    T: BorshSerialize + BorshDeserialize + Ord,
+{
+    /// Returns the greatest element in the queue, or `None` if it is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::PriorityQueue;
+    ///
+    /// let mut queue = PriorityQueue::new(b"p");
+    /// assert_eq!(queue.peek(), None);
+    ///
+    /// queue.extend([1, 5, 2]);
+    /// assert_eq!(queue.peek(), Some(&5));
+    /// ```
+    pub fn peek(&self) -> Option<&T> {
+        self.elements.get(0)
+    }
+
+    /// Pushes an element onto the queue.
+    ///
+    /// # Panics
+    ///
+    /// Panics if new length exceeds `u32::MAX`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::PriorityQueue;
+    ///
+    /// let mut queue = PriorityQueue::new(b"p");
+    /// queue.push(3);
+    /// queue.push(5);
+    /// queue.push(1);
+    ///
+    /// assert_eq!(queue.peek(), Some(&5));
+    /// ```
+    pub fn push(&mut self, element: T) {
+        self.elements.push(element);
+        self.sift_up(self.elements.len() - 1);
+    }
+
+    /// Removes the greatest element from the queue and returns it, or [`None`] if it is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::PriorityQueue;
+    ///
+    /// let mut queue = PriorityQueue::new(b"p");
+    /// queue.extend([1, 3]);
+    ///
+    /// assert_eq!(queue.pop(), Some(3));
+    /// assert_eq!(queue.pop(), Some(1));
+    /// assert_eq!(queue.pop(), None);
+    /// ```
+    pub fn pop(&mut self) -> Option<T> {
+        if self.elements.is_empty() {
+            return None;
+        }
+        let greatest = self.elements.swap_remove(0);
+        if !self.elements.is_empty() {
+            self.sift_down(0);
+        }
+        Some(greatest)
+    }
+
+    /// Returns an iterator visiting all elements in arbitrary order. This iterator will lazily
+    /// load any values iterated over from storage.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::PriorityQueue;
+    ///
+    /// let mut queue = PriorityQueue::new(b"p");
+    /// queue.extend([1, 2, 3]);
+    ///
+    /// let mut elements: Vec<_> = queue.iter().copied().collect();
+    /// elements.sort();
+    /// assert_eq!(elements, [1, 2, 3]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.elements.iter()
+    }
+
+    fn element(&self, index: u32) -> &T {
+        expect_consistent_state(self.elements.get(index))
+    }
+
+    // Move the element at `index` up until its parent is not less than it.
+    fn sift_up(&mut self, mut index: u32) {
+        while index > 0 {
+            let parent = (index - 1) / 2;
+            if self.element(index) <= self.element(parent) {
+                break;
+            }
+            self.elements.swap(index, parent);
+            index = parent;
+        }
+    }
+
+    // Move the element at `index` down until none of its children are greater than it.
+    fn sift_down(&mut self, mut index: u32) {
+        let len = self.elements.len();
+        while let Some(left) = index.checked_mul(2).and_then(|i| i.checked_add(1)) {
+            if left >= len {
+                break;
+            }
+            let right = left + 1;
+            let child =
+                if right < len && self.element(right) > self.element(left) { right } else { left };
+            if self.element(child) <= self.element(index) {
+                break;
+            }
+            self.elements.swap(index, child);
+            index = child;
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a PriorityQueue<T>
+where
+    T: BorshSerialize + BorshDeserialize + Ord,
+{
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T> Extend<T> for PriorityQueue<T>
+where
+    T: BorshSerialize + BorshDeserialize + Ord,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for item in iter {
+            self.push(item)
+        }
+    }
+}
+
+impl<T> fmt::Debug for PriorityQueue<T>
+where
+    T: BorshSerialize + BorshDeserialize + fmt::Debug,
+{
+    #[cfg(feature = "expensive-debug")]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.elements, f)
+    }
+
+    #[cfg(not(feature = "expensive-debug"))]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PriorityQueue")
+            .field("len", &self.elements.len())
+            .field("prefix", &self.elements.values.prefix)
+            .finish()
+    }
+}
+
+impl<T> StoragePrefixes for PriorityQueue<T>
+where
+    T: BorshSerialize,
+{
+    fn storage_prefixes(&self, path: &str, prefixes: &mut Vec<StoragePrefix>) {
+        self.elements.storage_prefixes(path, prefixes);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use arbitrary::{Arbitrary, Unstructured};
+    use borsh::{to_vec, BorshDeserialize};
+    use near_sdk_macros::near;
+    use rand::{Rng, RngCore, SeedableRng};
+    use std::cmp::Ordering;
+    use std::collections::BinaryHeap;
+
+    use super::PriorityQueue;
+    use crate::test_utils::test_env::setup_free;
+
+    #[test]
+    fn test_push_pop() {
+        let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(0);
+        let mut queue = PriorityQueue::new(b"p".to_vec());
+        let mut baseline = BinaryHeap::new();
+        for _ in 0..500 {
+            let value = rng.gen::<u64>();
+            queue.push(value);
+            baseline.push(value);
+            assert_eq!(queue.peek(), baseline.peek());
+        }
+        for _ in 0..501 {
+            assert_eq!(baseline.pop(), queue.pop());
+        }
+        assert!(queue.is_empty());
+    }
+
+    #[near(inside_nearsdk)]
+    #[derive(PartialEq, Eq, Debug)]
+    struct Min(u8);
+
+    impl Ord for Min {
+        fn cmp(&self, other: &Self) -> Ordering {
+            other.0.cmp(&self.0)
+        }
+    }
+
+    impl PartialOrd for Min {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    #[test]
+    fn test_min_queue_with_duplicates() {
+        let mut queue = PriorityQueue::new(b"p");
+        queue.extend([3, 1, 2, 1, 3].map(Min));
+        let popped: Vec<u8> = core::iter::from_fn(|| queue.pop()).map(|Min(v)| v).collect();
+        assert_eq!(popped, [1, 1, 2, 3, 3]);
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut queue = PriorityQueue::new(b"p");
+        queue.extend([1u8, 2, 3]);
+        queue.clear();
+        queue.flush();
+        assert!(queue.is_empty());
+        crate::mock::with_mocked_blockchain(|m| assert!(m.take_storage().is_empty()));
+    }
+
+    #[derive(Arbitrary, Debug)]
+    enum Op {
+        Push(u8),
+        Pop,
+        Peek,
+        Flush,
+        Reset,
+    }
+
+    #[test]
+    fn arbitrary() {
+        setup_free();
+
+        let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(0);
+        let mut buf = vec![0; 4096];
+        for _ in 0..1024 {
+            // Clear storage in-between runs
+            crate::mock::with_mocked_blockchain(|b| b.take_storage());
+            rng.fill_bytes(&mut buf);
+
+            let mut sq = PriorityQueue::new(b"p");
+            let mut mq = BinaryHeap::new();
+            let u = Unstructured::new(&buf);
+            if let Ok(ops) = Vec::<Op>::arbitrary_take_rest(u) {
+                for op in ops {
+                    match op {
+                        Op::Push(v) => {
+                            sq.push(v);
+                            mq.push(v);
+                        }
+                        Op::Pop => {
+                            assert_eq!(sq.pop(), mq.pop());
+                        }
+                        Op::Peek => {
+                            assert_eq!(sq.peek(), mq.peek());
+                        }
+                        Op::Flush => {
+                            sq.flush();
+                        }
+                        Op::Reset => {
+                            let serialized = to_vec(&sq).unwrap();
+                            sq = PriorityQueue::deserialize(&mut serialized.as_slice()).unwrap();
+                        }
+                    }
+                    assert_eq!(sq.len() as usize, mq.len());
+                }
+            }
+
+            // After all operations, compare both queues
+            let mut elements: Vec<u8> = sq.iter().copied().collect();
+            elements.sort();
+            assert_eq!(elements, mq.into_sorted_vec());
+        }
+    }
+
+    #[cfg(feature = "abi")]
+    #[test]
+    fn test_borsh_schema() {
+        #[derive(
+            borsh::BorshSerialize, borsh::BorshDeserialize, PartialEq, Eq, PartialOrd, Ord,
+        )]
+        struct NoSchemaStruct;
+
+        assert_eq!(
+            "PriorityQueue".to_string(),
+            <PriorityQueue<NoSchemaStruct> as borsh::BorshSchema>::declaration()
+        );
+        let mut defs = Default::default();
+        <PriorityQueue<NoSchemaStruct> as borsh::BorshSchema>::add_definitions_recursively(
+            &mut defs,
+        );
+        insta::assert_snapshot!(format!("{:#?}", defs));
+    }
+}
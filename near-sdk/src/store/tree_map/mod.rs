@@ -5,19 +5,26 @@ mod iter;
 use super::lookup_map as lm;
 use crate::store::free_list::{FreeList, FreeListIndex};
 use crate::store::key::{Sha256, ToKey};
+use crate::store::migrate::MigrationStatus;
+use crate::store::prefixes::{field_path, StoragePrefix, StoragePrefixes};
 use crate::store::LookupMap;
-use crate::{env, IntoStorageKey};
+use crate::{env, Gas, IntoStorageKey};
 use borsh::{BorshDeserialize, BorshSerialize};
 pub use entry::Entry;
 pub use iter::{Iter, IterMut, Keys, Range, RangeMut, Values, ValuesMut};
 use std::borrow::Borrow;
+use std::cmp::Ordering;
 use std::fmt;
-use std::ops::RangeBounds;
+use std::iter::Take;
+use std::ops::{Bound, RangeBounds};
 
 use near_sdk_macros::near;
 
 type NodeAndIndex<'a, K> = (FreeListIndex, &'a Node<K>);
 
+const ERR_NODE_WITHOUT_SIZE: &str =
+    "TreeMap node has no subtree size, migrate it with TreeMap::migrate_subtree_sizes";
+
 fn expect<T>(val: Option<T>) -> T {
     val.unwrap_or_else(|| env::abort())
 }
@@ -30,6 +37,8 @@ fn expect<T>(val: Option<T>) -> T {
 /// - `min`/`max`:              O(log(N))
 /// - `above`/`below`:          O(log(N))
 /// - `range` of K elements:    O(Klog(N))
+/// - `rank`/`nth`:             O(log(N))
+/// - `iter_from_index` of K:   O(Klog(N))
 #[near(inside_nearsdk)]
 pub struct TreeMap<K, V, H = Sha256>
 where
@@ -112,6 +121,50 @@ struct Node<K> {
     lft: Option<FreeListIndex>, // left link of a node
     rgt: Option<FreeListIndex>, // right link of a node
     ht: u32,                    // height of a subtree at a node
+    #[borsh(deserialize_with = "deserialize_size")]
+    sz: u32, // number of nodes in a subtree at a node
+}
+
+// Nodes written before subtree sizes were tracked end right after their height. Reading one
+// panics with a clear message instead of a generic deserialization error.
+fn deserialize_size<R: borsh::io::Read>(reader: &mut R) -> borsh::io::Result<u32> {
+    u32::deserialize_reader(reader).or_else(|_| env::panic_str(ERR_NODE_WITHOUT_SIZE))
+}
+
+/// Layout of [`Node`] before subtree sizes were tracked, only read by
+/// [`TreeMap::migrate_subtree_sizes`].
+#[near(inside_nearsdk)]
+#[derive(Clone, Debug)]
+struct NodeWithoutSize<K> {
+    key: K,
+    lft: Option<FreeListIndex>,
+    rgt: Option<FreeListIndex>,
+    ht: u32,
+}
+
+/// Progress of [`TreeMap::migrate_subtree_sizes`], to be persisted between transactions.
+#[near(inside_nearsdk)]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SubtreeSizeCursor {
+    // Path from the root to the node being visited. Nodes are rewritten once both of their
+    // subtrees are, so the root is rewritten last.
+    path: Vec<(FreeListIndex, Visit)>,
+    migrated: u32,
+}
+
+impl SubtreeSizeCursor {
+    /// Number of nodes rewritten so far.
+    pub fn migrated(&self) -> u32 {
+        self.migrated
+    }
+}
+
+#[near(inside_nearsdk)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Visit {
+    Left,
+    Right,
+    Node,
 }
 
 impl<K> Node<K>
@@ -119,7 +172,7 @@ where
     K: BorshSerialize + BorshDeserialize,
 {
     fn of(key: K) -> Self {
-        Self { key, lft: None, rgt: None, ht: 1 }
+        Self { key, lft: None, rgt: None, ht: 1, sz: 1 }
     }
 
     fn left<'a>(&self, list: &'a FreeList<Node<K>>) -> Option<(FreeListIndex, &'a Node<K>)> {
@@ -154,6 +207,14 @@ where
     V: BorshSerialize,
     H: ToKey,
 {
+    /// Initialize a [`TreeMap`] with a custom hash function.
+    ///
+    /// # Example
+    /// ```
+    /// use near_sdk::store::{key::Sha256Truncated, TreeMap};
+    ///
+    /// let map = TreeMap::<String, String, Sha256Truncated<20>>::with_hasher(b"m");
+    /// ```
     pub fn with_hasher<S>(prefix: S) -> Self
     where
         S: IntoStorageKey,
@@ -358,6 +419,47 @@ where
         }
     }
 
+    fn size(&self, id: Option<FreeListIndex>) -> u32 {
+        id.and_then(|id| self.node(id).map(|n| n.sz)).unwrap_or_default()
+    }
+
+    /// Returns the number of keys that are strictly less than key given as the parameter
+    fn rank<Q>(&self, key: &Q) -> u32
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        let mut rank = 0;
+        let mut at = self.root;
+        while let Some(node) = at.and_then(|id| self.node(id)) {
+            let k: &Q = node.key.borrow();
+            if k.lt(key) {
+                rank += self.size(node.lft) + 1;
+                at = node.rgt;
+            } else {
+                at = node.lft;
+            }
+        }
+        rank
+    }
+
+    /// Returns the key at position `index` in ascending order
+    fn nth_key(&self, mut index: u32) -> Option<&K> {
+        let mut at = self.root;
+        while let Some(node) = at.and_then(|id| self.node(id)) {
+            let lsz = self.size(node.lft);
+            match index.cmp(&lsz) {
+                Ordering::Less => at = node.lft,
+                Ordering::Equal => return Some(&node.key),
+                Ordering::Greater => {
+                    index -= lsz + 1;
+                    at = node.rgt;
+                }
+            }
+        }
+        None
+    }
+
     /// Returns (node, parent node) of left-most lower (min) node starting from given node `at`.
     fn min_at(&self, mut at: FreeListIndex) -> Option<(NodeAndIndex<K>, Option<NodeAndIndex<K>>)> {
         let mut parent: Option<NodeAndIndex<K>> = None;
@@ -520,6 +622,34 @@ where
         }
     }
 
+    // Take one step of the post-order walk of `cursor`: descend into a subtree of the node at the
+    // end of its path, or rewrite the node from `legacy` with its size once both subtrees are.
+    fn migrate_size_step(
+        &mut self,
+        legacy: &FreeList<NodeWithoutSize<K>>,
+        cursor: &mut SubtreeSizeCursor,
+    ) {
+        let (id, visit) = *expect(cursor.path.last());
+        let node = expect(legacy.get(id));
+        match visit {
+            Visit::Left => {
+                expect(cursor.path.last_mut()).1 = Visit::Right;
+                cursor.path.extend(node.lft.map(|lft| (lft, Visit::Left)));
+            }
+            Visit::Right => {
+                expect(cursor.path.last_mut()).1 = Visit::Node;
+                cursor.path.extend(node.rgt.map(|rgt| (rgt, Visit::Left)));
+            }
+            Visit::Node => {
+                let NodeWithoutSize { key, lft, rgt, ht } = node.clone();
+                let sz = 1 + self.size(lft) + self.size(rgt);
+                self.nodes.set_occupied(id, Node { key, lft, rgt, ht, sz });
+                cursor.path.pop();
+                cursor.migrated += 1;
+            }
+        }
+    }
+
     fn insert_at(&mut self, mut node: Node<K>, id: FreeListIndex, key: K) -> FreeListIndex {
         if key.eq(&node.key) {
             // This branch should not be hit, because we check for existence in insert.
@@ -544,13 +674,17 @@ where
         }
     }
 
-    // Calculate and save the height of a subtree at node `at`:
+    // Calculate and save the height and size of a subtree at node `at`:
     // height[at] = 1 + max(height[at.L], height[at.R])
+    // size[at] = 1 + size[at.L] + size[at.R]
     fn update_height(&mut self, node: &mut Node<K>, id: FreeListIndex) {
-        let lft = node.lft.and_then(|id| self.node(id).map(|n| n.ht)).unwrap_or_default();
-        let rgt = node.rgt.and_then(|id| self.node(id).map(|n| n.ht)).unwrap_or_default();
+        let (lht, lsz) =
+            node.lft.and_then(|id| self.node(id).map(|n| (n.ht, n.sz))).unwrap_or_default();
+        let (rht, rsz) =
+            node.rgt.and_then(|id| self.node(id).map(|n| (n.ht, n.sz))).unwrap_or_default();
 
-        node.ht = 1 + std::cmp::max(lft, rgt);
+        node.ht = 1 + std::cmp::max(lht, rht);
+        node.sz = 1 + lsz + rsz;
         // This side effect isn't great, but a lot of logic depends on values in storage/cache to be
         // up to date. Until changes and the tree are kept all in a single data structure, this
         // will be necessary.
@@ -916,6 +1050,145 @@ where
     }
 }
 
+impl<K, V, H> TreeMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+    /// Returns the number of keys in the map that are strictly less than the given key, which
+    /// is the index the key has, or would have, in ascending order.
+    ///
+    /// The key may be any borrowed form of the map's key type, but the ordering
+    /// on the borrowed form *must* match the ordering on the key type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::TreeMap;
+    ///
+    /// let mut map = TreeMap::new(b"t");
+    /// map.extend([(10, 1), (20, 2), (30, 3)]);
+    /// assert_eq!(map.rank(&20), 1);
+    /// assert_eq!(map.rank(&25), 2);
+    /// assert_eq!(map.rank(&5), 0);
+    /// ```
+    pub fn rank<Q>(&self, key: &Q) -> u32
+    where
+        K: BorshDeserialize + Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        self.tree.rank(key)
+    }
+
+    /// Returns the key-value pair at position `index` in ascending order of keys, or [`None`]
+    /// if `index` is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::TreeMap;
+    ///
+    /// let mut map = TreeMap::new(b"t");
+    /// map.extend([(10, 1), (20, 2), (30, 3)]);
+    /// assert_eq!(map.nth(1), Some((&20, &2)));
+    /// assert_eq!(map.nth(3), None);
+    /// ```
+    pub fn nth(&self, index: u32) -> Option<(&K, &V)>
+    where
+        K: BorshDeserialize + Clone,
+    {
+        self.tree.nth_key(index).map(|key| (key, expect(self.values.get(key))))
+    }
+
+    /// Returns an iterator over at most `limit` key-value pairs in ascending order of keys,
+    /// starting at position `offset`. Locating the first pair is O(log(N)), which makes this
+    /// suitable for pagination.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::TreeMap;
+    ///
+    /// let mut map = TreeMap::new(b"t");
+    /// map.extend((0..10).map(|i| (i, i * 10)));
+    /// let page: Vec<_> = map.iter_from_index(4, 3).map(|(k, _)| *k).collect();
+    /// assert_eq!(page, [4, 5, 6]);
+    /// assert_eq!(map.iter_from_index(10, 3).next(), None);
+    /// ```
+    pub fn iter_from_index(&self, offset: u32, limit: u32) -> Take<Range<'_, K, V, H>>
+    where
+        K: BorshDeserialize + Clone,
+    {
+        let (start, limit) = match self.tree.nth_key(offset) {
+            Some(key) => (Bound::Included(key), limit),
+            None => (Bound::Unbounded, 0),
+        };
+        Range::new(self, (start, Bound::Unbounded)).take(limit as usize)
+    }
+
+    /// Recomputes the subtree sizes of a map whose nodes were written by a version of this
+    /// collection which did not track them. Subtree sizes back [`rank`](Self::rank),
+    /// [`nth`](Self::nth) and [`iter_from_index`](Self::iter_from_index).
+    ///
+    /// Nodes are rewritten starting at `cursor` until either all of them are or the gas left in
+    /// the transaction gets below `reserved_gas` plus the gas the most expensive step took so far,
+    /// the same way as [`migrate_chunk`](crate::store::migrate::migrate_chunk). The contract
+    /// persists `cursor` in its state and calls this again in other transactions until the
+    /// migration reports completion. `reserved_gas` must cover everything the transaction does
+    /// after this call, including the flush of the map.
+    ///
+    /// Until the migration is complete, [`get`](Self::get) and
+    /// [`contains_key`](Self::contains_key) keep working, while the methods reading the tree
+    /// panic as they reach a node without subtree size. The map must not be modified while the
+    /// migration is in progress.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::tree_map::SubtreeSizeCursor;
+    /// use near_sdk::store::TreeMap;
+    /// use near_sdk::Gas;
+    ///
+    /// // Both would usually be fields of the contract state.
+    /// let mut map: TreeMap<u32, u32> = TreeMap::new(b"t");
+    /// let mut cursor = SubtreeSizeCursor::default();
+    ///
+    /// // Called once per transaction until the migration reports completion.
+    /// let status = map.migrate_subtree_sizes(&mut cursor, Gas::from_tgas(10));
+    /// assert!(status.is_complete());
+    /// ```
+    pub fn migrate_subtree_sizes(
+        &mut self,
+        cursor: &mut SubtreeSizeCursor,
+        reserved_gas: Gas,
+    ) -> MigrationStatus
+    where
+        K: BorshDeserialize + Clone,
+    {
+        let len = self.tree.nodes.len();
+        if cursor.path.is_empty() && cursor.migrated == 0 {
+            cursor.path.extend(self.tree.root.map(|root| (root, Visit::Left)));
+        }
+        let legacy = self.tree.nodes.with_layout::<NodeWithoutSize<K>>();
+        let prepaid_gas = env::prepaid_gas();
+        let mut max_step_gas = Gas::from_gas(0);
+        let start = cursor.migrated;
+        while !cursor.path.is_empty() {
+            let used_gas = env::used_gas();
+            if used_gas.saturating_add(max_step_gas).saturating_add(reserved_gas) > prepaid_gas {
+                return MigrationStatus::InProgress {
+                    migrated: (cursor.migrated - start).into(),
+                    remaining: (len - cursor.migrated).into(),
+                };
+            }
+            self.tree.migrate_size_step(&legacy, cursor);
+            max_step_gas = max_step_gas.max(env::used_gas().saturating_sub(used_gas));
+        }
+        MigrationStatus::Complete { migrated: (cursor.migrated - start).into() }
+    }
+}
+
 impl<K, V, H> TreeMap<K, V, H>
 where
     K: BorshSerialize + Ord,
@@ -989,12 +1262,25 @@ where
     }
 }
 
+impl<K, V, H> StoragePrefixes for TreeMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    fn storage_prefixes(&self, path: &str, prefixes: &mut Vec<StoragePrefix>) {
+        self.values.storage_prefixes(&field_path(path, "values"), prefixes);
+        self.tree.nodes.storage_prefixes(&field_path(path, "nodes"), prefixes);
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::test_utils::test_env::setup_free;
-    use crate::test_utils::{next_trie_id, test_env};
+    use crate::test_utils::{next_trie_id, test_env, VMContextBuilder};
+    use crate::testing_env;
 
     use arbitrary::{Arbitrary, Unstructured};
     use quickcheck::QuickCheck;
@@ -1883,6 +2169,115 @@ mod tests {
             .quickcheck(prop as fn(std::vec::Vec<(u32, u32)>, std::vec::Vec<u32>) -> bool);
     }
 
+    #[test]
+    fn prop_avl_vs_rb_order_statistics() {
+        fn prop(insert: Vec<(u32, u32)>, remove: Vec<u32>, key: u32) -> bool {
+            let a = avl(&insert, &remove);
+            let b = rb(&insert, &remove);
+            let root_size = a.tree.size(a.tree.root);
+            let rank = b.range(..key).count() as u32;
+            let all_nth =
+                (0..=b.len() as u32).map(|i| a.nth(i)).eq(b.iter().map(Some).chain([None]));
+            root_size == a.len() && a.rank(&key) == rank && all_nth
+        }
+
+        QuickCheck::new()
+            .tests(300)
+            .quickcheck(prop as fn(std::vec::Vec<(u32, u32)>, std::vec::Vec<u32>, u32) -> bool);
+    }
+
+    #[test]
+    fn test_iter_from_index() {
+        let mut map: TreeMap<u32, u32> = TreeMap::new(next_trie_id());
+        map.extend((0..20).rev().map(|i| (i * 2, i)));
+        for i in (0..20).step_by(3) {
+            map.remove(&(i * 2));
+        }
+
+        let keys: Vec<u32> = map.keys().copied().collect();
+        for offset in 0..=keys.len() as u32 + 1 {
+            for limit in 0..4 {
+                let page: Vec<u32> = map.iter_from_index(offset, limit).map(|(k, _)| *k).collect();
+                let expected: Vec<u32> =
+                    keys.iter().skip(offset as usize).take(limit as usize).copied().collect();
+                assert_eq!(page, expected);
+            }
+        }
+        assert_eq!(map.rank(&keys[5]), 5);
+        assert_eq!(map.nth(5), Some((&keys[5], &(keys[5] / 2))));
+    }
+
+    // Returns the state of a map of `n` keys but `removed`, with its nodes written in the layout
+    // without subtree sizes.
+    fn legacy_map(n: u32, removed: u32) -> Vec<u8> {
+        testing_env!(VMContextBuilder::new().prepaid_gas(Gas::from_gas(u64::MAX)).build());
+        let mut map: TreeMap<u32, u32> = TreeMap::new(b"m");
+        map.extend((0..n).map(|i| (i, i)));
+        map.remove(&removed);
+        map.flush();
+
+        let mut legacy = map.tree.nodes.with_layout::<NodeWithoutSize<u32>>();
+        for (id, node) in
+            (0..n).filter_map(|i| map.tree.nodes.get(FreeListIndex(i)).map(|n| (i, n)))
+        {
+            let Node { key, lft, rgt, ht, .. } = node.clone();
+            legacy.set_occupied(FreeListIndex(id), NodeWithoutSize { key, lft, rgt, ht });
+        }
+        legacy.flush();
+        borsh::to_vec(&map).unwrap()
+    }
+
+    #[test]
+    fn test_migrate_subtree_sizes() {
+        let state = legacy_map(500, 7);
+
+        let mut cursor = SubtreeSizeCursor::default();
+        let mut calls = 0;
+        loop {
+            // Every call runs in a fresh transaction with a small amount of gas.
+            testing_env!(VMContextBuilder::new().prepaid_gas(Gas::from_tgas(20)).build());
+            calls += 1;
+            let mut map: TreeMap<u32, u32> = borsh::from_slice(&state).unwrap();
+            let status = map.migrate_subtree_sizes(&mut cursor, Gas::from_tgas(15));
+            assert!(env::used_gas() <= Gas::from_tgas(15));
+            map.flush();
+            match status {
+                MigrationStatus::InProgress { migrated, remaining } => {
+                    assert!(migrated > 0);
+                    assert_eq!(remaining, u64::from(499 - cursor.migrated()));
+                    // Values are still read while the tree is migrated.
+                    assert_eq!(map.get(&20), Some(&20));
+                }
+                MigrationStatus::Complete { .. } => break,
+            }
+        }
+        assert!(calls > 1);
+        assert_eq!(cursor.migrated(), 499);
+
+        testing_env!(VMContextBuilder::new().prepaid_gas(Gas::from_gas(u64::MAX)).build());
+        let mut map: TreeMap<u32, u32> = borsh::from_slice(&state).unwrap();
+        assert_eq!(map.tree.size(map.tree.root), 499);
+        assert_eq!(map.rank(&20), 19);
+        assert_eq!(map.nth(7), Some((&8, &8)));
+        map.insert(7, 7);
+        assert_eq!(map.nth(7), Some((&7, &7)));
+        assert!(is_balanced(&map, map.tree.root.unwrap()));
+
+        // Migrating again with a completed cursor is a no-op.
+        let status = map.migrate_subtree_sizes(&mut cursor, Gas::from_tgas(15));
+        assert_eq!(status, MigrationStatus::Complete { migrated: 0 });
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "TreeMap node has no subtree size, migrate it with TreeMap::migrate_subtree_sizes"
+    )]
+    fn test_read_before_subtree_size_migration() {
+        let state = legacy_map(50, 7);
+        let map: TreeMap<u32, u32> = borsh::from_slice(&state).unwrap();
+        map.nth(0);
+    }
+
     #[test]
     fn insert_delete_insert() {
         let mut map = TreeMap::new(b"t");
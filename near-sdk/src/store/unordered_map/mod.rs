@@ -20,6 +20,7 @@ pub use entry::{Entry, OccupiedEntry, VacantEntry};
 pub use self::iter::{Drain, Iter, IterMut, Keys, Values, ValuesMut};
 use super::free_list::FreeListIndex;
 use super::{FreeList, LookupMap, ERR_INCONSISTENT_STATE, ERR_NOT_EXIST};
+use crate::store::prefixes::{field_path, StoragePrefix, StoragePrefixes};
 
 /// A lazily loaded storage map that stores its content directly on the storage trie.
 /// This structure is similar to [`near_sdk::store::LookupMap`](crate::store::LookupMap), except
@@ -690,6 +691,18 @@ where
     }
 }
 
+impl<K, V, H> StoragePrefixes for UnorderedMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    fn storage_prefixes(&self, path: &str, prefixes: &mut Vec<StoragePrefix>) {
+        self.keys.storage_prefixes(&field_path(path, "keys"), prefixes);
+        self.values.storage_prefixes(&field_path(path, "values"), prefixes);
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(test)]
 mod tests {
@@ -8,6 +8,7 @@ pub use self::iter::{Difference, Drain, Intersection, Iter, SymmetricDifference,
 use super::{FreeList, LookupMap, ERR_INCONSISTENT_STATE};
 use crate::store::free_list::FreeListIndex;
 use crate::store::key::{Sha256, ToKey};
+use crate::store::prefixes::{field_path, StoragePrefix, StoragePrefixes};
 use crate::{env, IntoStorageKey};
 use borsh::{BorshDeserialize, BorshSerialize};
 use near_sdk_macros::near;
@@ -580,6 +581,17 @@ where
     }
 }
 
+impl<T, H> StoragePrefixes for UnorderedSet<T, H>
+where
+    T: BorshSerialize + Ord,
+    H: ToKey,
+{
+    fn storage_prefixes(&self, path: &str, prefixes: &mut Vec<StoragePrefix>) {
+        self.elements.storage_prefixes(&field_path(path, "elements"), prefixes);
+        self.index.storage_prefixes(&field_path(path, "index"), prefixes);
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(test)]
 mod tests {
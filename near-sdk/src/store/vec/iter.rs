@@ -72,6 +72,75 @@ where
     }
 }
 
+/// An iterator over the serialized bytes of each element in the stored vector, created by
+/// [`Vector::iter_raw`].
+#[derive(Clone)]
+pub struct RawIter<'a, T>
+where
+    T: BorshSerialize,
+{
+    /// Underlying vector to iterate through
+    vec: &'a Vector<T>,
+    /// Range of indices to iterate.
+    range: Range<u32>,
+}
+
+impl<'a, T> RawIter<'a, T>
+where
+    T: BorshSerialize,
+{
+    pub(super) fn new(vec: &'a Vector<T>) -> Self {
+        Self { vec, range: Range { start: 0, end: vec.len() } }
+    }
+
+    /// Returns number of elements left to iterate.
+    fn remaining(&self) -> usize {
+        self.range.len()
+    }
+}
+
+impl<'a, T> Iterator for RawIter<'a, T>
+where
+    T: BorshSerialize,
+{
+    type Item = Vec<u8>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        <Self as Iterator>::nth(self, 0)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.remaining();
+        (remaining, Some(remaining))
+    }
+
+    fn count(self) -> usize {
+        self.remaining()
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        let idx = self.range.nth(n)?;
+        Some(self.vec.get_raw(idx).unwrap_or_else(|| env::panic_str(ERR_INDEX_OUT_OF_BOUNDS)))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for RawIter<'a, T> where T: BorshSerialize {}
+impl<'a, T> FusedIterator for RawIter<'a, T> where T: BorshSerialize {}
+
+impl<'a, T> DoubleEndedIterator for RawIter<'a, T>
+where
+    T: BorshSerialize,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        <Self as DoubleEndedIterator>::nth_back(self, 0)
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        let idx = self.range.nth_back(n)?;
+        Some(self.vec.get_raw(idx).unwrap_or_else(|| env::panic_str(ERR_INDEX_OUT_OF_BOUNDS)))
+    }
+}
+
 /// An iterator over exclusive references to each element of a stored vector.
 #[derive(Debug)]
 pub struct IterMut<'a, T>
@@ -63,11 +63,12 @@ use std::{
 use borsh::{BorshDeserialize, BorshSerialize};
 use near_sdk_macros::near;
 
-pub use self::iter::{Drain, Iter, IterMut};
+pub use self::iter::{Drain, Iter, IterMut, RawIter};
 use super::ERR_INCONSISTENT_STATE;
 use crate::{env, IntoStorageKey};
 
 use super::IndexMap;
+use crate::store::prefixes::{StoragePrefix, StoragePrefixes};
 
 const ERR_INDEX_OUT_OF_BOUNDS: &str = "Index out of bounds";
 
@@ -278,6 +279,85 @@ where
             self.len.checked_add(1).unwrap_or_else(|| env::panic_str(ERR_INDEX_OUT_OF_BOUNDS));
         self.set(last_idx, element)
     }
+
+    /// Returns the serialized bytes of the element at `index`, or `None` if it is not present.
+    ///
+    /// Unlike [`Vector::get`], the element is neither deserialized nor loaded in the cache, which
+    /// avoids a deserialization when the bytes are only passed on, such as in a view method
+    /// returning a large element. An element modified since it was loaded is serialized again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Vector;
+    ///
+    /// let mut vec = Vector::new(b"v");
+    /// vec.push("test".to_string());
+    ///
+    /// assert_eq!(vec.get_raw(0), Some(borsh::to_vec("test").unwrap()));
+    /// assert_eq!(vec.get_raw(3), None);
+    /// ```
+    pub fn get_raw(&self, index: u32) -> Option<Vec<u8>> {
+        if index >= self.len() {
+            return None;
+        }
+        self.values.get_raw(index)
+    }
+
+    /// Sets the serialized bytes of the element at `index` as the return value of the method, and
+    /// returns `false` without setting it if the element is not present.
+    ///
+    /// The bytes are returned directly from storage with [`env::value_return_from_storage`],
+    /// without being deserialized nor copied into the memory of the contract, unless the element
+    /// was modified since it was loaded. As the bytes are the Borsh serialization of the element,
+    /// this is meant for methods returning Borsh, which must not also return a value themselves.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Vector;
+    /// use near_sdk::{near, PanicOnDefault};
+    ///
+    /// #[near(contract_state)]
+    /// #[derive(PanicOnDefault)]
+    /// pub struct Contract {
+    ///     blobs: Vector<Vec<u8>>,
+    /// }
+    ///
+    /// #[near]
+    /// impl Contract {
+    ///     /// Returns the Borsh serialized blob, or nothing if there is no blob at this index.
+    ///     pub fn get_blob(&self, index: u32) {
+    ///         self.blobs.return_raw(index);
+    ///     }
+    /// }
+    /// ```
+    pub fn return_raw(&self, index: u32) -> bool {
+        if index >= self.len() {
+            return false;
+        }
+        self.values.return_raw(index)
+    }
+
+    /// Returns an iterator over the serialized bytes of the elements of the vector, which are
+    /// read from storage without being deserialized or cached. See [`Vector::get_raw`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Vector;
+    ///
+    /// let mut vec = Vector::new(b"v");
+    /// vec.extend([1u8, 2]);
+    /// let mut iterator = vec.iter_raw();
+    ///
+    /// assert_eq!(iterator.next(), Some(vec![1]));
+    /// assert_eq!(iterator.next(), Some(vec![2]));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    pub fn iter_raw(&self) -> RawIter<'_, T> {
+        RawIter::new(self)
+    }
 }
 
 impl<T> Vector<T>
@@ -524,6 +604,15 @@ where
     }
 }
 
+impl<T> StoragePrefixes for Vector<T>
+where
+    T: BorshSerialize,
+{
+    fn storage_prefixes(&self, path: &str, prefixes: &mut Vec<StoragePrefix>) {
+        self.values.storage_prefixes(path, prefixes);
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(test)]
 mod tests {
@@ -533,8 +622,30 @@ mod tests {
     use std::ops::{Bound, IndexMut};
 
     use super::Vector;
+    use crate::test_utils::get_return_value;
     use crate::{store::IndexMap, test_utils::test_env::setup_free};
 
+    #[test]
+    fn raw_elements() {
+        let mut vec = Vector::new(b"v");
+        vec.extend(["a".to_string(), "b".to_string()]);
+        vec.flush();
+        vec[1] = "c".to_string();
+
+        let raw: Vec<Vec<u8>> = vec.iter_raw().collect();
+        assert_eq!(raw, vec![to_vec("a").unwrap(), to_vec("c").unwrap()]);
+        assert_eq!(vec.iter_raw().next_back(), Some(to_vec("c").unwrap()));
+        assert_eq!(vec.get_raw(2), None);
+        assert!(!vec.return_raw(2));
+        vec.flush();
+
+        // Raw reads of a vector loaded from its state don't load the elements in the cache.
+        let vec = Vector::<String>::try_from_slice(&to_vec(&vec).unwrap()).unwrap();
+        assert!(vec.return_raw(1));
+        assert_eq!(get_return_value(), Some(to_vec("c").unwrap()));
+        assert!(vec.values.cache.map_value_ref(&1, |_| ()).is_none());
+    }
+
     #[test]
     fn test_push_pop() {
         let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(0);
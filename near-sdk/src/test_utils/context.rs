@@ -23,7 +23,7 @@ impl Default for VMContextBuilder {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
 /// Context for the contract execution.
 pub struct VMContext {
     /// The account id of the current contract that we are executing.
@@ -188,17 +188,21 @@ impl VMContextBuilder {
 /// Initializes the [`MockedBlockchain`] with a single promise result during execution.
 #[deprecated(since = "4.0.0", note = "Use `testing_env!` macro to initialize with promise results")]
 pub fn testing_env_with_promise_results(context: VMContext, promise_result: PromiseResult) {
-    let storage = crate::mock::with_mocked_blockchain(|b| b.take_storage());
+    let (storage, yields) =
+        crate::mock::with_mocked_blockchain(|b| (b.take_storage(), b.take_yielded_receipts()));
 
     //? This probably shouldn't need to replace the existing mocked blockchain altogether?
     //? Might be a good time to remove this utility function altogether
-    crate::env::set_blockchain_interface(MockedBlockchain::new(
-        context,
-        test_vm_config(),
-        RuntimeFeesConfig::test(),
-        vec![promise_result],
-        storage,
-        Default::default(),
-        None,
-    ));
+    crate::env::set_blockchain_interface(
+        MockedBlockchain::new(
+            context,
+            test_vm_config(),
+            RuntimeFeesConfig::test(),
+            vec![promise_result],
+            storage,
+            Default::default(),
+            None,
+        )
+        .with_yielded_receipts(yields),
+    );
 }
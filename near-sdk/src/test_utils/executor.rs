@@ -0,0 +1,700 @@
+//! In-process execution of the receipts created by contracts.
+//!
+//! [`ReceiptExecutor`] keeps a set of accounts, some of which have a [`NativeContract`]
+//! deployed, and executes a function call transaction on them along with all the receipts it
+//! creates, until there are none left. Function calls run the methods generated by `#[near]`
+//! natively on the [`MockedBlockchain`], the value or promise they return is forwarded to the
+//! callbacks depending on them as a [`PromiseResult`], and transfers, account creations and
+//! deletions are applied to the accounts.
+//!
+//! This makes it possible to test cross-contract calls, e.g. an `ft_transfer_call` round-trip,
+//! from unit tests and integration tests. The native methods are generated by `#[near]` for the
+//! impl blocks marked with `#[near(native_dispatch)]`.
+//!
+//! The execution is simplified compared to the protocol: gas is not paid for, deployed Wasm
+//! code is not executed, access keys and stakes are ignored, and receipts are executed in the
+//! order they are created, once their dependencies are resolved.
+//!
+//! Promises yielded with [`Promise::new_yield`](crate::Promise::new_yield) wait until a later
+//! call resumes them, which executes their callback as part of that call, or until they are
+//! timed out with [`ReceiptExecutor::time_out`].
+//!
+//! # Examples
+//! ```ignore
+//! use near_sdk::test_utils::{native_contract, ReceiptExecutor};
+//! use near_sdk::{Gas, NearToken};
+//!
+//! let mut executor = ReceiptExecutor::new();
+//! executor.create_account("alice.near".parse().unwrap(), NearToken::from_near(10));
+//! executor.create_account("status.near".parse().unwrap(), NearToken::from_near(10));
+//! executor.deploy(
+//!     &"status.near".parse().unwrap(),
+//!     native_contract!(StatusMessage { set_status, get_status }),
+//! );
+//!
+//! let outcome = executor.call(
+//!     "alice.near".parse().unwrap(),
+//!     "status.near".parse().unwrap(),
+//!     "set_status",
+//!     br#"{"message":"hello"}"#.to_vec(),
+//!     NearToken::from_near(0),
+//!     Gas::from_tgas(30),
+//! );
+//! assert!(outcome.is_success());
+//! ```
+
+use std::any::Any;
+use std::collections::{HashMap, VecDeque};
+use std::panic::{self, AssertUnwindSafe};
+
+use near_parameters::RuntimeFeesConfig;
+use near_primitives_core::types::GasWeight;
+use near_vm_runner::logic::ReturnData;
+use serde::de::DeserializeOwned;
+
+use crate::mock::{MockAction, MockedBlockchain, YieldStatus, YieldedReceipt};
+use crate::test_utils::{GasProfile, VMContextBuilder};
+use crate::{
+    env, test_vm_config, AccountId, CryptoHash, Gas, NearToken, PromiseResult, StorageUsage,
+};
+
+/// Result of a transaction which depends on a yielded promise that was not resumed yet.
+const WAITING_FOR_YIELD: &str = "The transaction is waiting for a yielded promise";
+
+/// Storage usage of an account without any data, which is what the protocol charges for the
+/// account record itself.
+const ACCOUNT_STORAGE_USAGE: StorageUsage = 100;
+
+/// Methods of a contract which can be executed by a [`ReceiptExecutor`], by their name.
+///
+/// Use [`native_contract!`](crate::test_utils::native_contract) to list the methods of a contract
+/// type.
+#[derive(Clone, Default)]
+pub struct NativeContract {
+    methods: HashMap<String, fn()>,
+}
+
+impl NativeContract {
+    /// Creates a contract without any method.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a method which is executed by calling `wrapper`, the function generated by `#[near]`
+    /// which reads its input and state from the environment.
+    pub fn with_method(mut self, name: impl Into<String>, wrapper: fn()) -> Self {
+        self.methods.insert(name.into(), wrapper);
+        self
+    }
+}
+
+impl std::fmt::Debug for NativeContract {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut methods: Vec<_> = self.methods.keys().collect();
+        methods.sort();
+        f.debug_struct("NativeContract").field("methods", &methods).finish()
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Account {
+    balance: NearToken,
+    storage: HashMap<Vec<u8>, Vec<u8>>,
+    storage_usage: StorageUsage,
+    contract: Option<NativeContract>,
+}
+
+impl Account {
+    fn new(balance: NearToken) -> Self {
+        Self {
+            balance,
+            storage: HashMap::new(),
+            storage_usage: ACCOUNT_STORAGE_USAGE,
+            contract: None,
+        }
+    }
+}
+
+/// Outcome of one receipt executed by a [`ReceiptExecutor`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ReceiptOutcome {
+    /// Account which created the receipt, or signed the transaction for the first receipt.
+    pub predecessor_id: AccountId,
+    /// Account on which the receipt was executed.
+    pub receiver_id: AccountId,
+    /// Logs emitted by the function calls of the receipt.
+    pub logs: Vec<String>,
+    /// The panic message if the receipt failed, in which case none of its actions was applied.
+    pub failure: Option<String>,
+    /// Gas used and storage changed by the function calls of the receipt.
+    pub gas_profile: GasProfile,
+}
+
+/// Outcome of a transaction executed by a [`ReceiptExecutor`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ExecutionOutcome {
+    /// Value returned by the transaction, which is the value returned by the called method, or
+    /// by the receipt it returned a promise of. It is the panic message if that failed, or an
+    /// error if it is waiting for a yielded promise to be resumed.
+    pub result: Result<Vec<u8>, String>,
+    /// Outcomes of all the receipts executed, in order, starting with the transaction's one.
+    pub receipts: Vec<ReceiptOutcome>,
+}
+
+impl ExecutionOutcome {
+    /// Returns `true` if the value of the transaction was returned successfully. Receipts it did
+    /// not depend on may still have failed, see [`Self::failures`].
+    pub fn is_success(&self) -> bool {
+        self.result.is_ok()
+    }
+
+    /// Returns the outcomes of the receipts which failed.
+    pub fn failures(&self) -> impl Iterator<Item = &ReceiptOutcome> {
+        self.receipts.iter().filter(|receipt| receipt.failure.is_some())
+    }
+
+    /// Returns the logs of all the receipts, in execution order.
+    pub fn logs(&self) -> Vec<&str> {
+        self.receipts.iter().flat_map(|receipt| receipt.logs.iter().map(String::as_str)).collect()
+    }
+
+    /// Deserializes the value returned by the transaction from JSON.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the transaction failed or if the value can't be deserialized.
+    pub fn json<T: DeserializeOwned>(&self) -> T {
+        match &self.result {
+            Ok(value) => serde_json::from_slice(value)
+                .unwrap_or_else(|err| panic!("Failed to deserialize the result: {}", err)),
+            Err(message) => panic!("The transaction failed: {}", message),
+        }
+    }
+}
+
+/// Result of a receipt, which is either final or the result of another receipt.
+enum ReceiptResult {
+    Value(Result<Vec<u8>, String>),
+    Forwarded(usize),
+}
+
+struct PendingReceipt {
+    id: usize,
+    signer_id: AccountId,
+    predecessor_id: AccountId,
+    receiver_id: AccountId,
+    /// Receipts which results are passed to this one as promise results.
+    dependencies: Vec<usize>,
+    actions: Vec<MockAction>,
+}
+
+impl PendingReceipt {
+    fn deposit(&self) -> NearToken {
+        action_deposits(&self.actions)
+    }
+}
+
+/// Output of a function call executed successfully.
+struct FunctionCallOutput {
+    return_data: ReturnData,
+    receipts: Vec<PendingReceipt>,
+}
+
+/// Executes function call transactions on native contracts along with all the receipts they
+/// create, see the [module documentation](self).
+///
+/// The executor replaces the [`MockedBlockchain`] of the current thread while it executes a
+/// function call, so tests should not rely on the state set up with
+/// [`testing_env!`](crate::testing_env) across calls to the executor.
+#[derive(Default)]
+pub struct ReceiptExecutor {
+    accounts: HashMap<AccountId, Account>,
+    next_receipt_id: usize,
+    /// Receipts waiting for their dependencies, which may be yielded promises.
+    pending: VecDeque<PendingReceipt>,
+    results: HashMap<usize, ReceiptResult>,
+    /// All the promises yielded so far, to keep their data ids unique.
+    yields: Vec<YieldedReceipt>,
+    /// Data id of the promise yielded for each of the receipts delivering their payload.
+    yield_data: HashMap<usize, CryptoHash>,
+    /// Receipt id of the callback of each yielded promise.
+    yield_callbacks: HashMap<CryptoHash, usize>,
+}
+
+impl std::fmt::Debug for ReceiptExecutor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReceiptExecutor")
+            .field("accounts", &self.accounts)
+            .field("yields", &self.yields)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ReceiptExecutor {
+    /// Creates an executor without any account.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an account without contract holding `balance`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the account already exists.
+    pub fn create_account(&mut self, account_id: AccountId, balance: NearToken) {
+        if self.accounts.contains_key(&account_id) {
+            panic!("Account {} already exists", account_id);
+        }
+        self.accounts.insert(account_id, Account::new(balance));
+    }
+
+    /// Deploys `contract` on an existing account, replacing the one deployed before if any.
+    /// The state of the account is kept.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the account does not exist.
+    pub fn deploy(&mut self, account_id: &AccountId, contract: NativeContract) {
+        self.account_mut(account_id).contract = Some(contract);
+    }
+
+    /// Returns `true` if the account exists.
+    pub fn account_exists(&self, account_id: &AccountId) -> bool {
+        self.accounts.contains_key(account_id)
+    }
+
+    /// Returns the balance of an account, or [`None`] if it does not exist.
+    pub fn balance(&self, account_id: &AccountId) -> Option<NearToken> {
+        self.accounts.get(account_id).map(|account| account.balance)
+    }
+
+    /// Returns the storage of an account, or [`None`] if it does not exist.
+    pub fn storage(&self, account_id: &AccountId) -> Option<&HashMap<Vec<u8>, Vec<u8>>> {
+        self.accounts.get(account_id).map(|account| &account.storage)
+    }
+
+    /// Returns the promises yielded so far, along with whether they were resumed.
+    pub fn yielded_receipts(&self) -> &[YieldedReceipt] {
+        &self.yields
+    }
+
+    /// Times out the promise yielded with `data_id`, and executes its callback with a failed
+    /// promise result, then all the receipts depending on it. The result of the outcome is the
+    /// result of the callback.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no promise yielded with `data_id` is waiting to be resumed.
+    pub fn time_out(&mut self, data_id: &CryptoHash) -> ExecutionOutcome {
+        let yielded = self
+            .yields
+            .iter_mut()
+            .find(|yielded| yielded.data_id == *data_id && yielded.status == YieldStatus::Pending)
+            .expect("No yielded promise is waiting to be resumed with this data id");
+        yielded.status = YieldStatus::TimedOut;
+        let receipts = self.execute_pending();
+        let result = self.resolve(self.yield_callbacks[data_id]);
+        ExecutionOutcome {
+            result: result.unwrap_or_else(|| Err(WAITING_FOR_YIELD.into())),
+            receipts,
+        }
+    }
+
+    /// Executes a transaction from `signer_id` calling `method_name` on `receiver_id`, then all
+    /// the receipts it creates. `deposit` is withdrawn from the signer's balance.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the signer does not exist or can't pay for `deposit`.
+    pub fn call(
+        &mut self,
+        signer_id: AccountId,
+        receiver_id: AccountId,
+        method_name: &str,
+        args: Vec<u8>,
+        deposit: NearToken,
+        gas: Gas,
+    ) -> ExecutionOutcome {
+        let signer = self.account_mut(&signer_id);
+        signer.balance = signer.balance.checked_sub(deposit).unwrap_or_else(|| {
+            panic!("Account {} can't pay for a deposit of {}", signer_id, deposit)
+        });
+        let transaction = PendingReceipt {
+            id: self.next_receipt_id(),
+            predecessor_id: signer_id.clone(),
+            signer_id,
+            receiver_id,
+            dependencies: Vec::new(),
+            actions: vec![MockAction::FunctionCallWeight {
+                receipt_index: 0,
+                method_name: method_name.as_bytes().to_vec(),
+                args,
+                attached_deposit: deposit,
+                prepaid_gas: gas,
+                gas_weight: GasWeight(0),
+            }],
+        };
+        let transaction_id = transaction.id;
+        self.pending.push_back(transaction);
+        let receipts = self.execute_pending();
+        let result = self.resolve(transaction_id);
+        ExecutionOutcome {
+            result: result.unwrap_or_else(|| Err(WAITING_FOR_YIELD.into())),
+            receipts,
+        }
+    }
+
+    /// Executes the pending receipts which dependencies are resolved, until there are none left,
+    /// and returns their outcomes.
+    fn execute_pending(&mut self) -> Vec<ReceiptOutcome> {
+        let mut receipts = Vec::new();
+        while let Some(position) = self
+            .pending
+            .iter()
+            .position(|receipt| receipt.dependencies.iter().all(|id| self.resolve(*id).is_some()))
+        {
+            let receipt = self.pending.remove(position).unwrap();
+            let promise_results = receipt
+                .dependencies
+                .iter()
+                .map(|id| match self.resolve(*id).unwrap() {
+                    Ok(value) => PromiseResult::Successful(value),
+                    Err(_) => PromiseResult::Failed,
+                })
+                .collect();
+            let (outcome, result, created) = self.execute(&receipt, promise_results);
+            receipts.push(outcome);
+            self.results.insert(receipt.id, result);
+            self.pending.extend(created);
+        }
+        receipts
+    }
+
+    /// Follows the forwarded results of receipts, and returns the final result of receipt `id` if
+    /// it was executed, or the payload of a yielded promise if it was resumed or timed out.
+    fn resolve(&self, mut id: usize) -> Option<Result<Vec<u8>, String>> {
+        loop {
+            if let Some(data_id) = self.yield_data.get(&id) {
+                let yielded = self.yields.iter().find(|yielded| yielded.data_id == *data_id)?;
+                return match &yielded.status {
+                    YieldStatus::Pending => None,
+                    YieldStatus::Resumed(payload) => Some(Ok(payload.clone())),
+                    YieldStatus::TimedOut => Some(Err("The yielded promise timed out".into())),
+                };
+            }
+            match self.results.get(&id)? {
+                ReceiptResult::Value(value) => return Some(value.clone()),
+                ReceiptResult::Forwarded(next) => id = *next,
+            }
+        }
+    }
+
+    fn account_mut(&mut self, account_id: &AccountId) -> &mut Account {
+        self.accounts
+            .get_mut(account_id)
+            .unwrap_or_else(|| panic!("Account {} does not exist", account_id))
+    }
+
+    fn next_receipt_id(&mut self) -> usize {
+        self.next_receipt_id += 1;
+        self.next_receipt_id
+    }
+
+    /// Applies the actions of a receipt, and returns its outcome, its result and the receipts it
+    /// created. The accounts are restored and the deposit refunded if any action fails.
+    fn execute(
+        &mut self,
+        receipt: &PendingReceipt,
+        promise_results: Vec<PromiseResult>,
+    ) -> (ReceiptOutcome, ReceiptResult, Vec<PendingReceipt>) {
+        let snapshot = (self.accounts.clone(), self.yields.clone());
+        let mut outcome = ReceiptOutcome {
+            predecessor_id: receipt.predecessor_id.clone(),
+            receiver_id: receipt.receiver_id.clone(),
+            logs: Vec::new(),
+            failure: None,
+            gas_profile: GasProfile::default(),
+        };
+        let mut return_data = ReturnData::None;
+        let mut created = Vec::new();
+        let mut status = Ok(());
+        for action in &receipt.actions {
+            status =
+                self.apply_action(receipt, action, &promise_results, &mut outcome).map(|output| {
+                    if let Some(output) = output {
+                        return_data = output.return_data;
+                        created.extend(output.receipts);
+                    }
+                });
+            if status.is_err() {
+                break;
+            }
+        }
+
+        let result = match status {
+            Ok(()) => match return_data {
+                ReturnData::Value(value) => ReceiptResult::Value(Ok(value)),
+                ReturnData::ReceiptIndex(index) => ReceiptResult::Forwarded(index as usize),
+                ReturnData::None => ReceiptResult::Value(Ok(Vec::new())),
+            },
+            Err(ref message) => {
+                (self.accounts, self.yields) = snapshot;
+                created.clear();
+                if let Some(predecessor) = self.accounts.get_mut(&receipt.predecessor_id) {
+                    predecessor.balance = predecessor.balance.saturating_add(receipt.deposit());
+                }
+                ReceiptResult::Value(Err(message.clone()))
+            }
+        };
+        outcome.failure = status.err();
+        (outcome, result, created)
+    }
+
+    fn apply_action(
+        &mut self,
+        receipt: &PendingReceipt,
+        action: &MockAction,
+        promise_results: &[PromiseResult],
+        outcome: &mut ReceiptOutcome,
+    ) -> Result<Option<FunctionCallOutput>, String> {
+        let receiver_id = &receipt.receiver_id;
+        if let MockAction::CreateAccount { .. } = action {
+            if self.accounts.contains_key(receiver_id) {
+                return Err(format!("Account {} already exists", receiver_id));
+            }
+            self.accounts.insert(receiver_id.clone(), Account::new(NearToken::from_near(0)));
+            return Ok(None);
+        }
+        let account = self
+            .accounts
+            .get_mut(receiver_id)
+            .ok_or_else(|| format!("Account {} does not exist", receiver_id))?;
+        match action {
+            MockAction::Transfer { deposit, .. } => {
+                account.balance = account.balance.saturating_add(*deposit);
+            }
+            MockAction::FunctionCallWeight {
+                method_name,
+                args,
+                attached_deposit,
+                prepaid_gas,
+                ..
+            } => {
+                account.balance = account.balance.saturating_add(*attached_deposit);
+                let method_name = String::from_utf8_lossy(method_name);
+                let context = VMContextBuilder::new()
+                    .current_account_id(receiver_id.clone())
+                    .signer_account_id(receipt.signer_id.clone())
+                    .predecessor_account_id(receipt.predecessor_id.clone())
+                    .attached_deposit(*attached_deposit)
+                    .prepaid_gas(*prepaid_gas)
+                    .build();
+                return self
+                    .function_call(
+                        receipt,
+                        &method_name,
+                        args.clone(),
+                        context,
+                        promise_results,
+                        outcome,
+                    )
+                    .map(Some);
+            }
+            MockAction::DeleteAccount { beneficiary_id, .. } => {
+                let balance = self.accounts.remove(receiver_id).unwrap().balance;
+                if let Some(beneficiary) = self.accounts.get_mut(beneficiary_id) {
+                    beneficiary.balance = beneficiary.balance.saturating_add(balance);
+                }
+            }
+            // Wasm code can't be executed natively, and keys and stakes are not modelled.
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn function_call(
+        &mut self,
+        receipt: &PendingReceipt,
+        method_name: &str,
+        args: Vec<u8>,
+        mut context: crate::VMContext,
+        promise_results: &[PromiseResult],
+        outcome: &mut ReceiptOutcome,
+    ) -> Result<FunctionCallOutput, String> {
+        let account = self.accounts.get_mut(&receipt.receiver_id).unwrap();
+        let wrapper = account
+            .contract
+            .as_ref()
+            .ok_or_else(|| format!("Account {} has no contract deployed", receipt.receiver_id))?
+            .methods
+            .get(method_name)
+            .copied()
+            // The methods releasing the locks generated for `#[near(lock = "...")]` all share the
+            // same body, so they don't have to be listed.
+            .or_else(|| {
+                method_name
+                    .starts_with(crate::__private::RELEASE_LOCK_METHOD_PREFIX)
+                    .then_some(crate::__private::release_lock as fn())
+            })
+            .ok_or_else(|| format!("Method {} is not found in the contract", method_name))?;
+
+        context.input = args;
+        context.account_balance = account.balance;
+        context.storage_usage = account.storage_usage;
+        let prepaid_gas = context.prepaid_gas;
+        env::set_blockchain_interface(
+            MockedBlockchain::new(
+                context,
+                test_vm_config(),
+                RuntimeFeesConfig::test(),
+                promise_results.to_vec(),
+                std::mem::take(&mut account.storage),
+                Default::default(),
+                None,
+            )
+            .with_yielded_receipts(self.yields.clone())
+            .with_gas_profiles(Some(Vec::new())),
+        );
+
+        let status = panic::catch_unwind(AssertUnwindSafe(wrapper)).map_err(panic_message);
+        // Taken before reading the used gas below, which would be profiled otherwise.
+        let gas_profile = crate::mock::with_mocked_blockchain(|b| b.take_gas_profiles())
+            .and_then(|mut profiles| profiles.pop())
+            .unwrap_or_default();
+        outcome.gas_profile.merge(&gas_profile);
+        let (used_gas, storage_usage) = match status {
+            Ok(()) => (env::used_gas(), env::storage_usage()),
+            Err(_) => (prepaid_gas, account.storage_usage),
+        };
+        let (storage, call_logs, created, return_data, yields, yielded) =
+            crate::mock::with_mocked_blockchain(|b| {
+                (
+                    b.take_storage(),
+                    b.logs(),
+                    b.indexed_receipts(),
+                    b.return_data(),
+                    b.take_yielded_receipts(),
+                    b.indexed_yields(),
+                )
+            });
+        account.storage = storage;
+        account.storage_usage = storage_usage;
+        outcome.logs.extend(call_logs);
+        status?;
+        self.yields = yields;
+
+        let mut receipts: Vec<_> = created
+            .into_iter()
+            .map(|(index, created)| {
+                let receipt = PendingReceipt {
+                    id: 0,
+                    signer_id: receipt.signer_id.clone(),
+                    predecessor_id: receipt.receiver_id.clone(),
+                    receiver_id: created.receiver_id,
+                    dependencies: created.receipt_indices.iter().map(|i| *i as usize).collect(),
+                    actions: created.actions,
+                };
+                (index as usize, receipt)
+            })
+            .collect();
+        for (index, data_id) in &yielded {
+            let yielded = self.yields.iter().find(|yielded| yielded.data_id == *data_id).unwrap();
+            let callback = PendingReceipt {
+                id: 0,
+                signer_id: receipt.signer_id.clone(),
+                predecessor_id: receipt.receiver_id.clone(),
+                receiver_id: receipt.receiver_id.clone(),
+                dependencies: Vec::new(),
+                actions: vec![MockAction::FunctionCallWeight {
+                    receipt_index: *index,
+                    method_name: yielded.method_name.clone().into_bytes(),
+                    args: yielded.args.clone(),
+                    attached_deposit: NearToken::from_near(0),
+                    prepaid_gas: yielded.prepaid_gas,
+                    gas_weight: yielded.gas_weight.clone(),
+                }],
+            };
+            receipts.push((*index as usize, callback));
+        }
+        let ids: HashMap<usize, usize> =
+            receipts.iter().map(|(index, _)| (*index, self.next_receipt_id())).collect();
+        // The callback of a yielded promise depends on a receipt delivering its payload, which
+        // is resolved once the promise is resumed or timed out.
+        let data_receipt_ids: HashMap<usize, usize> = yielded
+            .into_iter()
+            .map(|(index, data_id)| {
+                let data_receipt_id = self.next_receipt_id();
+                self.yield_data.insert(data_receipt_id, data_id);
+                self.yield_callbacks.insert(data_id, ids[&(index as usize)]);
+                (index as usize, data_receipt_id)
+            })
+            .collect();
+        distribute_unused_gas(
+            receipts.iter_mut().map(|(_, receipt)| receipt),
+            prepaid_gas.saturating_sub(used_gas),
+        );
+
+        let account = self.accounts.get_mut(&receipt.receiver_id).unwrap();
+        let receipts = receipts
+            .into_iter()
+            .map(|(index, mut created)| {
+                created.id = ids[&index];
+                created.dependencies = match data_receipt_ids.get(&index) {
+                    Some(data_receipt_id) => vec![*data_receipt_id],
+                    None => created.dependencies.iter().map(|i| ids[i]).collect(),
+                };
+                account.balance = account.balance.saturating_sub(created.deposit());
+                created
+            })
+            .collect();
+        let return_data = match return_data {
+            ReturnData::ReceiptIndex(index) => {
+                ReturnData::ReceiptIndex(ids[&(index as usize)] as _)
+            }
+            return_data => return_data,
+        };
+        Ok(FunctionCallOutput { return_data, receipts })
+    }
+}
+
+fn action_deposits(actions: &[MockAction]) -> NearToken {
+    actions
+        .iter()
+        .map(|action| match action {
+            MockAction::Transfer { deposit, .. } => *deposit,
+            MockAction::FunctionCallWeight { attached_deposit, .. } => *attached_deposit,
+            _ => NearToken::from_near(0),
+        })
+        .fold(NearToken::from_near(0), NearToken::saturating_add)
+}
+
+/// Splits the gas left unused by a function call between the function calls it created,
+/// proportionally to their gas weight.
+fn distribute_unused_gas<'a>(receipts: impl Iterator<Item = &'a mut PendingReceipt>, unused: Gas) {
+    let mut calls: Vec<_> = receipts
+        .flat_map(|receipt| receipt.actions.iter_mut())
+        .filter_map(|action| match action {
+            MockAction::FunctionCallWeight { prepaid_gas, gas_weight, .. } if gas_weight.0 > 0 => {
+                Some((prepaid_gas, gas_weight.0))
+            }
+            _ => None,
+        })
+        .collect();
+    let total_weight: u128 = calls.iter().map(|(_, weight)| u128::from(*weight)).sum();
+    for (prepaid_gas, weight) in calls.iter_mut() {
+        let share = u128::from(unused.as_gas()) * u128::from(*weight) / total_weight;
+        **prepaid_gas = prepaid_gas.saturating_add(Gas::from_gas(share as u64));
+    }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => match payload.downcast::<&str>() {
+            Ok(message) => message.to_string(),
+            Err(_) => "Contract panicked".to_string(),
+        },
+    }
+}
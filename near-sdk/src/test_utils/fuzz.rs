@@ -0,0 +1,567 @@
+//! Property-based testing of contracts with random sequences of calls.
+//!
+//! A [`Fuzzer`] is given the methods of a contract which can be called, as closures which read
+//! their arguments from an [`Input`], and the invariants the state of the contract must uphold.
+//! It executes random sequences of calls with random predecessors and deposits on the
+//! [`MockedBlockchain`](crate::MockedBlockchain), checks the invariants after every call, and
+//! shrinks the first sequence violating one of them to a minimal one.
+//!
+//! As on chain, the contract is read from storage before each call and written back after it,
+//! and a call which panics is reverted along with the storage it changed. Contracts keeping their
+//! data in [`store`](crate::store) collections can so be fuzzed like any other.
+//!
+//! # Examples
+//!
+//! ```
+//! use near_sdk::test_utils::fuzz::Fuzzer;
+//! use near_sdk::{env, near, require, AccountId};
+//! use std::collections::HashMap;
+//!
+//! #[near(contract_state)]
+//! #[derive(Default)]
+//! pub struct Token {
+//!     balances: HashMap<AccountId, u64>,
+//!     total_supply: u64,
+//! }
+//!
+//! #[near]
+//! impl Token {
+//!     pub fn mint(&mut self, amount: u64) {
+//!         *self.balances.entry(env::predecessor_account_id()).or_default() += amount;
+//!         self.total_supply += amount;
+//!     }
+//!
+//!     pub fn transfer(&mut self, receiver_id: AccountId, amount: u64) {
+//!         let balance = self.balances.entry(env::predecessor_account_id()).or_default();
+//!         require!(*balance >= amount, "Not enough balance");
+//!         *balance -= amount;
+//!         *self.balances.entry(receiver_id).or_default() += amount;
+//!     }
+//! }
+//!
+//! Fuzzer::new(Token::default)
+//!     .method("mint", |token, input| {
+//!         token.mint(input.int_in_range(0..=1000)?);
+//!         Ok(())
+//!     })
+//!     .method("transfer", |token, input| {
+//!         token.transfer(input.account()?, input.int_in_range(0..=1000)?);
+//!         Ok(())
+//!     })
+//!     .invariant("total supply is the sum of the balances", |token| {
+//!         token.total_supply == token.balances.values().sum::<u64>()
+//!     })
+//!     .run();
+//! ```
+use crate::mock::{self, BlockchainSnapshot};
+use crate::test_utils::{accounts, VMContextBuilder};
+use crate::{env, AccountId, MockedBlockchain, NearToken};
+pub use arbitrary;
+use arbitrary::Unstructured;
+use borsh::{BorshDeserialize, BorshSerialize};
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::panic::{self, AssertUnwindSafe};
+
+/// Maximum number of bytes from which the arguments of a call are read.
+const MAX_INPUT_LEN: usize = 64;
+
+type MethodFn<C> = Box<dyn Fn(&mut C, &mut Input) -> arbitrary::Result<()>>;
+
+struct Method<C> {
+    name: String,
+    payable: bool,
+    call: MethodFn<C>,
+}
+
+struct Invariant<C> {
+    name: String,
+    check: Box<dyn Fn(&C) -> bool>,
+}
+
+/// Source of the arguments of a call, from the random bytes of a [`Step`].
+///
+/// It dereferences to [`Unstructured`], to read values with [`Unstructured::arbitrary`] or
+/// [`Unstructured::int_in_range`] for instance. Once all the bytes are read, values default to
+/// zero or empty ones.
+pub struct Input<'a> {
+    unstructured: Unstructured<'a>,
+    accounts: &'a [AccountId],
+}
+
+impl Input<'_> {
+    /// Returns one of the accounts calling the contract, as set with [`Fuzzer::accounts`].
+    pub fn account(&mut self) -> arbitrary::Result<AccountId> {
+        self.unstructured.choose(self.accounts).cloned()
+    }
+}
+
+impl<'a> Deref for Input<'a> {
+    type Target = Unstructured<'a>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.unstructured
+    }
+}
+
+impl DerefMut for Input<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.unstructured
+    }
+}
+
+/// A call of a sequence generated by the [`Fuzzer`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Step {
+    /// Name of the method called.
+    pub method: String,
+    pub predecessor_id: AccountId,
+    pub deposit: NearToken,
+    /// Bytes from which the arguments are read.
+    pub input: Vec<u8>,
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} calls `{}`", self.predecessor_id, self.method)?;
+        if !self.deposit.is_zero() {
+            write!(f, " with {}", self.deposit.exact_amount_display())?;
+        }
+        write!(f, " on input {:?}", self.input)
+    }
+}
+
+/// A sequence of calls after which an invariant doesn't hold, returned by [`Fuzzer::check`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Failure {
+    /// Name of the invariant violated.
+    pub invariant: String,
+    /// The calls, shrunk to a minimal sequence violating the invariant.
+    pub steps: Vec<Step>,
+    /// Panic message of each call, or `None` if it succeeded.
+    pub panics: Vec<Option<String>>,
+    /// Seed of the run which found the sequence, to reproduce it with [`Fuzzer::seed`].
+    pub seed: u64,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Invariant `{}` violated after {} call(s), seed {}:",
+            self.invariant,
+            self.steps.len(),
+            self.seed
+        )?;
+        for (i, (step, panic)) in self.steps.iter().zip(&self.panics).enumerate() {
+            write!(f, "\n  {}. {}", i + 1, step)?;
+            if let Some(message) = panic {
+                write!(f, ", which panicked: {}", message)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Generator of random sequences of calls to a contract, checking its invariants. See the
+/// [module documentation](self).
+pub struct Fuzzer<C> {
+    init: Box<dyn Fn() -> C>,
+    methods: Vec<Method<C>>,
+    invariants: Vec<Invariant<C>>,
+    accounts: Vec<AccountId>,
+    deposits: Vec<NearToken>,
+    runs: usize,
+    steps: usize,
+    seed: u64,
+}
+
+impl<C: BorshSerialize + BorshDeserialize> Fuzzer<C> {
+    /// Creates a fuzzer of the contract initialized by `init`, which is called once by the first
+    /// of the [accounts](Self::accounts). Each sequence of calls starts from the state it leaves.
+    pub fn new(init: impl Fn() -> C + 'static) -> Self {
+        Self {
+            init: Box::new(init),
+            methods: Vec::new(),
+            invariants: Vec::new(),
+            accounts: (0..4).map(accounts).collect(),
+            deposits: vec![
+                NearToken::from_yoctonear(1),
+                NearToken::from_millinear(1),
+                NearToken::from_near(1),
+                NearToken::from_near(100),
+            ],
+            runs: 100,
+            steps: 20,
+            seed: 0,
+        }
+    }
+
+    /// Adds a method which is called without a deposit. `call` reads the arguments from the
+    /// [`Input`] and calls the method, which may panic.
+    pub fn method(
+        mut self,
+        name: impl Into<String>,
+        call: impl Fn(&mut C, &mut Input) -> arbitrary::Result<()> + 'static,
+    ) -> Self {
+        self.methods.push(Method { name: name.into(), payable: false, call: Box::new(call) });
+        self
+    }
+
+    /// Adds a method which is called with a random deposit, either none or one of the
+    /// [deposits](Self::deposits).
+    pub fn payable_method(
+        mut self,
+        name: impl Into<String>,
+        call: impl Fn(&mut C, &mut Input) -> arbitrary::Result<()> + 'static,
+    ) -> Self {
+        self.methods.push(Method { name: name.into(), payable: true, call: Box::new(call) });
+        self
+    }
+
+    /// Adds an invariant, which must hold after every call.
+    pub fn invariant(
+        mut self,
+        name: impl Into<String>,
+        check: impl Fn(&C) -> bool + 'static,
+    ) -> Self {
+        self.invariants.push(Invariant { name: name.into(), check: Box::new(check) });
+        self
+    }
+
+    /// Sets the accounts calling the methods, which [`Input::account`] chooses from as well.
+    /// Defaults to the first four [`accounts`](crate::test_utils::accounts), the first one
+    /// being the contract account.
+    pub fn accounts(mut self, accounts: Vec<AccountId>) -> Self {
+        assert!(!accounts.is_empty(), "At least one account is needed");
+        self.accounts = accounts;
+        self
+    }
+
+    /// Sets the deposits attached to the calls of payable methods.
+    pub fn deposits(mut self, deposits: Vec<NearToken>) -> Self {
+        self.deposits = deposits;
+        self
+    }
+
+    /// Sets the number of sequences generated, 100 by default.
+    pub fn runs(mut self, runs: usize) -> Self {
+        self.runs = runs;
+        self
+    }
+
+    /// Sets the maximum number of calls of a sequence, 20 by default.
+    pub fn steps(mut self, steps: usize) -> Self {
+        self.steps = steps;
+        self
+    }
+
+    /// Sets the seed of the first sequence, the following ones using the next seeds.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Generates and executes the sequences of calls.
+    ///
+    /// # Panics
+    ///
+    /// Panics with the shrunk sequence if an invariant is violated.
+    pub fn run(&self) {
+        if let Err(failure) = self.check() {
+            panic!("{}", failure);
+        }
+    }
+
+    /// Generates and executes the sequences of calls, and returns the shrunk sequence violating
+    /// an invariant, if any.
+    pub fn check(&self) -> Result<(), Failure> {
+        assert!(!self.methods.is_empty(), "No method to call");
+        let fixture = self.init_state();
+        for run in 0..self.runs {
+            let seed = self.seed.wrapping_add(run as u64);
+            let steps = self.generate(seed);
+            if let Some((invariant, len)) = self.execute(&fixture, &steps).violation {
+                let steps = self.shrink(&fixture, steps[..len].to_vec(), &invariant);
+                let panics = self.execute(&fixture, &steps).panics;
+                return Err(Failure { invariant, steps, panics, seed });
+            }
+        }
+        Ok(())
+    }
+
+    /// Initializes the contract on an empty blockchain, and returns the state of the blockchain.
+    fn init_state(&self) -> BlockchainSnapshot {
+        let context = VMContextBuilder::new()
+            .predecessor_account_id(self.accounts[0].clone())
+            .signer_account_id(self.accounts[0].clone())
+            .build();
+        env::set_blockchain_interface(MockedBlockchain::new(
+            context,
+            crate::test_vm_config(),
+            near_parameters::RuntimeFeesConfig::test(),
+            vec![],
+            Default::default(),
+            Default::default(),
+            None,
+        ));
+        let contract = (self.init)();
+        env::state_write(&contract);
+        // Collections write their pending changes when dropped.
+        drop(contract);
+        mock::snapshot()
+    }
+
+    fn generate(&self, seed: u64) -> Vec<Step> {
+        let mut rng = SplitMix64(seed);
+        let len = 1 + rng.below(self.steps.max(1));
+        (0..len)
+            .map(|_| {
+                let method = &self.methods[rng.below(self.methods.len())];
+                let deposit = match rng.below(self.deposits.len() + 1) {
+                    i if method.payable && i < self.deposits.len() => self.deposits[i],
+                    _ => NearToken::from_yoctonear(0),
+                };
+                Step {
+                    method: method.name.clone(),
+                    predecessor_id: self.accounts[rng.below(self.accounts.len())].clone(),
+                    deposit,
+                    input: (0..rng.below(MAX_INPUT_LEN + 1)).map(|_| rng.next() as u8).collect(),
+                }
+            })
+            .collect()
+    }
+
+    /// Executes `steps` from the initial state, until an invariant is violated.
+    fn execute(&self, state: &BlockchainSnapshot, steps: &[Step]) -> Execution {
+        mock::restore(state.clone());
+        let mut panics = Vec::new();
+        for (i, step) in steps.iter().enumerate() {
+            let method = self.methods.iter().find(|method| method.name == step.method).unwrap();
+            let mut context = VMContextBuilder::new();
+            context
+                .predecessor_account_id(step.predecessor_id.clone())
+                .signer_account_id(step.predecessor_id.clone())
+                .attached_deposit(step.deposit)
+                .block_height(i as u64 + 1)
+                .block_timestamp((i as u64 + 1) * 1_000_000_000);
+            // Unlike `testing_env!`, the configurations are not built again for each call.
+            let storage = mock::with_mocked_blockchain(|b| b.take_storage());
+            env::set_blockchain_interface(MockedBlockchain::new(
+                context.build(),
+                state.config.clone(),
+                state.fees_config.clone(),
+                vec![],
+                storage,
+                Default::default(),
+                None,
+            ));
+
+            // A call which panics is reverted, as it would be on chain.
+            let before = mock::snapshot();
+            let mut input =
+                Input { unstructured: Unstructured::new(&step.input), accounts: &self.accounts };
+            let result = panic::catch_unwind(AssertUnwindSafe(|| -> arbitrary::Result<()> {
+                let mut contract = read_state::<C>();
+                (method.call)(&mut contract, &mut input)?;
+                env::state_write(&contract);
+                Ok(())
+            }));
+            let panic = match result {
+                Ok(Ok(())) => None,
+                Ok(Err(error)) => Some(error.to_string()),
+                Err(payload) => Some(panic_message(payload)),
+            };
+            if panic.is_some() {
+                mock::restore(before);
+            }
+            panics.push(panic);
+
+            let contract = read_state::<C>();
+            if let Some(invariant) =
+                self.invariants.iter().find(|invariant| !(invariant.check)(&contract))
+            {
+                return Execution { violation: Some((invariant.name.clone(), i + 1)), panics };
+            }
+        }
+        Execution { violation: None, panics }
+    }
+
+    /// Shrinks `steps` while they still violate `invariant`, by removing calls and then
+    /// simplifying their predecessor, deposit and input.
+    fn shrink(
+        &self,
+        fixture: &BlockchainSnapshot,
+        mut steps: Vec<Step>,
+        invariant: &str,
+    ) -> Vec<Step> {
+        let fails = |steps: &[Step]| matches!(self.execute(fixture, steps).violation, Some((name, _)) if name == invariant);
+        let mut i = steps.len();
+        while i > 0 {
+            i -= 1;
+            let mut candidate = steps.clone();
+            candidate.remove(i);
+            if fails(&candidate) {
+                steps = candidate;
+            }
+        }
+        for i in 0..steps.len() {
+            for candidate in simplifications(&steps[i], &self.accounts[0]) {
+                let mut candidates = steps.clone();
+                candidates[i] = candidate;
+                if fails(&candidates) {
+                    steps = candidates;
+                }
+            }
+        }
+        steps
+    }
+}
+
+fn read_state<C: BorshDeserialize>() -> C {
+    env::state_read().unwrap_or_else(|| env::panic_str("The contract state is missing"))
+}
+
+struct Execution {
+    /// Name of the invariant violated, and the number of calls after which it was.
+    violation: Option<(String, usize)>,
+    panics: Vec<Option<String>>,
+}
+
+/// Returns simpler variants of `step`, from the simplest.
+fn simplifications(step: &Step, first_account: &AccountId) -> Vec<Step> {
+    let mut candidates = Vec::new();
+    if step.predecessor_id != *first_account {
+        candidates.push(Step { predecessor_id: first_account.clone(), ..step.clone() });
+    }
+    if !step.deposit.is_zero() {
+        candidates.push(Step { deposit: NearToken::from_yoctonear(0), ..step.clone() });
+    }
+    let mut len = step.input.len() / 2;
+    while len < step.input.len() {
+        candidates.push(Step { input: step.input[..len].to_vec(), ..step.clone() });
+        len = (len + step.input.len()).div_ceil(2).max(len + 1);
+    }
+    candidates
+}
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => match payload.downcast::<&str>() {
+            Ok(message) => message.to_string(),
+            Err(_) => "Box<dyn Any>".to_string(),
+        },
+    }
+}
+
+/// Small generator of the sequences, so that they can be reproduced from their seed.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::require;
+    use crate::store::IterableMap;
+
+    #[derive(BorshSerialize, BorshDeserialize)]
+    struct Vault {
+        deposits: IterableMap<AccountId, NearToken>,
+        total: NearToken,
+    }
+
+    impl Vault {
+        fn new() -> Self {
+            Self { deposits: IterableMap::new(b"d"), total: NearToken::from_yoctonear(0) }
+        }
+
+        fn deposit(&mut self) {
+            let amount = env::attached_deposit();
+            let deposit = self.deposits.entry(env::predecessor_account_id()).or_default();
+            *deposit = deposit.saturating_add(amount);
+            self.total = self.total.saturating_add(amount);
+            env::storage_write(b"total", &self.total.as_yoctonear().to_le_bytes());
+        }
+
+        fn withdraw(&mut self, amount: NearToken) {
+            let deposit = self.deposits.entry(env::predecessor_account_id()).or_default();
+            require!(*deposit >= amount, "Not enough deposited");
+            *deposit = deposit.saturating_sub(amount);
+            // Bug: the total is only updated for small amounts.
+            if amount < NearToken::from_near(50) {
+                self.total = self.total.saturating_sub(amount);
+            }
+        }
+
+        fn is_consistent(&self) -> bool {
+            self.deposits.values().map(|deposit| deposit.as_yoctonear()).sum::<u128>()
+                == self.total.as_yoctonear()
+        }
+    }
+
+    fn fuzzer() -> Fuzzer<Vault> {
+        Fuzzer::new(Vault::new)
+            .payable_method("deposit", |vault, _| {
+                vault.deposit();
+                Ok(())
+            })
+            .method("withdraw", |vault, input| {
+                let amount = *input.choose(&[1, 1_000, 60_000])?;
+                vault.withdraw(NearToken::from_millinear(amount));
+                Ok(())
+            })
+            .invariant("total is the sum of the deposits", Vault::is_consistent)
+    }
+
+    #[test]
+    fn finds_and_shrinks_violation() {
+        let failure = fuzzer().runs(500).check().unwrap_err();
+        assert_eq!(failure.invariant, "total is the sum of the deposits");
+        // Depositing 100 NEAR and withdrawing 60 of them.
+        assert_eq!(failure.steps.len(), 2, "{}", failure);
+        assert_eq!(failure.steps[0].method, "deposit");
+        assert_eq!(failure.steps[0].deposit, NearToken::from_near(100));
+        assert_eq!(failure.steps[1].method, "withdraw");
+        assert_eq!(failure.steps[0].predecessor_id, failure.steps[1].predecessor_id);
+        assert_eq!(failure.panics, vec![None, None]);
+
+        // The failure is reproduced from its seed.
+        let reproduced = fuzzer().seed(failure.seed).runs(1).check().unwrap_err();
+        assert_eq!(reproduced, failure);
+    }
+
+    #[test]
+    fn panicking_calls_are_reverted() {
+        Fuzzer::new(Vault::new)
+            .payable_method("deposit", |vault, _| {
+                vault.deposit();
+                env::panic_str("Deposits are disabled")
+            })
+            .invariant("nothing is deposited", |vault| vault.total.is_zero())
+            .invariant("nothing is stored", |_| env::storage_read(b"total").is_none())
+            .run();
+    }
+
+    #[test]
+    fn generation_is_deterministic() {
+        let fuzzer = fuzzer();
+        assert_eq!(fuzzer.generate(7), fuzzer.generate(7));
+        assert_ne!(fuzzer.generate(7), fuzzer.generate(8));
+    }
+}
@@ -1,11 +1,18 @@
 //! Testing blockchain utilities. These can only be used inside tests and are not available for
 //! a wasm32 target.
+pub mod executor;
+pub mod fuzz;
+pub mod profile;
 pub mod test_env;
 
 pub(crate) mod context;
-use crate::mock::Receipt;
+use crate::mock::{Receipt, YieldedReceipt};
 #[allow(deprecated)]
 pub use context::{accounts, testing_env_with_promise_results, VMContextBuilder};
+pub use executor::{ExecutionOutcome, NativeContract, ReceiptExecutor, ReceiptOutcome};
+pub use near_sdk_macros::native_contract;
+use near_vm_runner::logic::ReturnData;
+pub use profile::{get_gas_profiles, profile, GasCategory, GasProfile};
 
 /// Initializes a testing environment to mock interactions which would otherwise go through a
 /// validator node. This macro will initialize or overwrite the [`MockedBlockchain`]
@@ -26,6 +33,11 @@ pub use context::{accounts, testing_env_with_promise_results, VMContextBuilder};
 ///
 /// Any argument not included will use the default implementation of each.
 ///
+/// Prefixing the parameters with `profile:` enables the profiling mode, in which the gas used
+/// and the storage changed in the context of each `testing_env!` are recorded, until the end
+/// of the test. As a context is usually set for each contract method called, this records a
+/// [`GasProfile`] for each method invocation, which can be read with [`get_gas_profiles`].
+///
 /// # Example use
 ///
 /// ```
@@ -43,12 +55,18 @@ pub use context::{accounts, testing_env_with_promise_results, VMContextBuilder};
 ///
 /// // Or include arguments up to the five optional
 /// testing_env!(
-///     context,
+///     context.clone(),
 ///     test_vm_config(),
 ///     RuntimeFeesConfig::test(),
 ///     HashMap::default(),
 ///     Vec::default(),
 /// );
+///
+/// // Record the gas used in this context and in the next ones
+/// testing_env!(profile: context.clone());
+/// near_sdk::env::storage_write(b"key", b"value");
+/// testing_env!(context);
+/// println!("{}", near_sdk::test_utils::get_gas_profiles()[0]);
 /// # }
 /// ```
 ///
@@ -62,17 +80,29 @@ pub use context::{accounts, testing_env_with_promise_results, VMContextBuilder};
 /// [`HashMap`]: std::collections::HashMap
 #[macro_export]
 macro_rules! testing_env {
-    ($context:expr, $config:expr, $fee_config:expr, $validators:expr, $promise_results:expr $(,)?) => {
-        $crate::env::set_blockchain_interface($crate::MockedBlockchain::new(
-            $context,
-            $config,
-            $fee_config,
-            $promise_results,
-            $crate::mock::with_mocked_blockchain(|b| b.take_storage()),
-            $validators,
-            None,
-        ))
-    };
+    (profile: $($args:expr),+ $(,)?) => {{
+        $crate::testing_env!($($args),+);
+        $crate::mock::with_mocked_blockchain(|b| b.enable_gas_profiling());
+    }};
+    ($context:expr, $config:expr, $fee_config:expr, $validators:expr, $promise_results:expr $(,)?) => {{
+        // Taken before the storage, which the profile of the previous context accounts for.
+        let gas_profiles = $crate::mock::with_mocked_blockchain(|b| b.take_gas_profiles());
+        $crate::env::set_blockchain_interface(
+            $crate::MockedBlockchain::new(
+                $context,
+                $config,
+                $fee_config,
+                $promise_results,
+                $crate::mock::with_mocked_blockchain(|b| b.take_storage()),
+                $validators,
+                None,
+            )
+            .with_yielded_receipts($crate::mock::with_mocked_blockchain(|b| {
+                b.take_yielded_receipts()
+            }))
+            .with_gas_profiles(gas_profiles),
+        )
+    }};
     ($context:expr, $config:expr, $fee_config:expr, $validators:expr $(,)?) => {
         $crate::testing_env!($context, $config, $fee_config, $validators, Default::default())
     };
@@ -93,11 +123,28 @@ pub fn get_logs() -> Vec<String> {
     crate::mock::with_mocked_blockchain(|b| b.logs())
 }
 
+/// Returns the value set as the return value of the contract with [`env::value_return`], or
+/// `None` if no value was returned. Only available in unit tests.
+///
+/// [`env::value_return`]: crate::env::value_return
+pub fn get_return_value() -> Option<Vec<u8>> {
+    match crate::mock::with_mocked_blockchain(|b| b.return_data()) {
+        ReturnData::Value(value) => Some(value),
+        _ => None,
+    }
+}
+
 /// Accessing receipts created by the contract. Only available in unit tests.
 pub fn get_created_receipts() -> Vec<Receipt> {
     crate::mock::with_mocked_blockchain(|b| b.created_receipts())
 }
 
+/// Accessing the promises yielded by the contract, including before the last `testing_env!`,
+/// along with whether they were resumed. Only available in unit tests.
+pub fn get_yielded_receipts() -> Vec<YieldedReceipt> {
+    crate::mock::with_mocked_blockchain(|b| b.yielded_receipts())
+}
+
 /// Objects stored on the trie directly should have identifiers. If identifier is not provided
 /// explicitly than `Default` trait would use this index to generate an id.
 #[cfg(test)]
@@ -0,0 +1,306 @@
+//! Profiling of the gas used and the storage changed by contract code in unit tests.
+use crate::Gas;
+use near_parameters::{ExtCosts, ExtCostsConfig};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// Categories of the gas reported by a [`GasProfile`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum GasCategory {
+    /// Base cost of every host function call.
+    Base,
+    /// Loading the contract code.
+    ContractLoading,
+    /// Copying data between the contract memory and the registers, and decoding strings.
+    Memory,
+    /// Reading values and checking keys from storage.
+    StorageRead,
+    /// Writing values to storage.
+    StorageWrite,
+    /// Removing values from storage.
+    StorageRemove,
+    /// Iterating over storage.
+    StorageIter,
+    /// Accessing the nodes of the trie, on any storage operation.
+    Trie,
+    /// Hash functions, such as `sha256` or `keccak256`.
+    Hashing,
+    /// Signature verification and elliptic curve operations.
+    Crypto,
+    /// Emitting logs.
+    Logs,
+    /// Combining, returning and yielding promises.
+    Promises,
+    /// Querying validators.
+    Validators,
+    /// Fees of the actions of the promises created, and the gas attached to their function
+    /// calls.
+    Actions,
+}
+
+impl GasCategory {
+    /// Returns the category of the gas charged for `cost`.
+    pub fn of(cost: ExtCosts) -> Self {
+        use ExtCosts::*;
+        match cost {
+            base => Self::Base,
+            contract_loading_base | contract_loading_bytes => Self::ContractLoading,
+            read_memory_base | read_memory_byte | write_memory_base | write_memory_byte
+            | read_register_base | read_register_byte | write_register_base
+            | write_register_byte | utf8_decoding_base | utf8_decoding_byte
+            | utf16_decoding_base | utf16_decoding_byte => Self::Memory,
+            storage_read_base
+            | storage_read_key_byte
+            | storage_read_value_byte
+            | storage_has_key_base
+            | storage_has_key_byte
+            | storage_large_read_overhead_base
+            | storage_large_read_overhead_byte => Self::StorageRead,
+            storage_write_base
+            | storage_write_key_byte
+            | storage_write_value_byte
+            | storage_write_evicted_byte => Self::StorageWrite,
+            storage_remove_base | storage_remove_key_byte | storage_remove_ret_value_byte => {
+                Self::StorageRemove
+            }
+            storage_iter_create_prefix_base
+            | storage_iter_create_prefix_byte
+            | storage_iter_create_range_base
+            | storage_iter_create_from_byte
+            | storage_iter_create_to_byte
+            | storage_iter_next_base
+            | storage_iter_next_key_byte
+            | storage_iter_next_value_byte => Self::StorageIter,
+            touching_trie_node | read_cached_trie_node => Self::Trie,
+            sha256_base | sha256_byte | keccak256_base | keccak256_byte | keccak512_base
+            | keccak512_byte | ripemd160_base | ripemd160_block => Self::Hashing,
+            log_base | log_byte => Self::Logs,
+            promise_and_base
+            | promise_and_per_promise
+            | promise_return
+            | yield_create_base
+            | yield_create_byte
+            | yield_resume_base
+            | yield_resume_byte => Self::Promises,
+            validator_stake_base | validator_total_stake_base => Self::Validators,
+            _ => Self::Crypto,
+        }
+    }
+}
+
+/// Gas used and storage changed by some contract code, as measured by [`profile`] or recorded
+/// by `testing_env!` in profiling mode.
+///
+/// The [`Display`](fmt::Display) implementation formats it as a report, one line per category.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct GasProfile {
+    /// Total gas used, including the gas attached to the function calls created.
+    pub used_gas: Gas,
+    /// Gas used per category. Categories for which no gas was used are omitted.
+    pub categories: BTreeMap<GasCategory, Gas>,
+    /// Gas used per cost of the host functions, such as `storage_write_value_byte`, for a finer
+    /// breakdown than the categories.
+    pub costs: BTreeMap<ExtCosts, Gas>,
+    /// Change of the storage usage of the account, in bytes.
+    pub storage_delta: i64,
+}
+
+impl GasProfile {
+    /// Returns the gas used for `category`.
+    pub fn gas(&self, category: GasCategory) -> Gas {
+        self.categories.get(&category).copied().unwrap_or(Gas::from_gas(0))
+    }
+
+    /// Adds the gas and storage of `other` to this profile.
+    pub(crate) fn merge(&mut self, other: &GasProfile) {
+        self.used_gas = self.used_gas.saturating_add(other.used_gas);
+        for (category, gas) in &other.categories {
+            let entry = self.categories.entry(*category).or_insert(Gas::from_gas(0));
+            *entry = entry.saturating_add(*gas);
+        }
+        for (cost, gas) in &other.costs {
+            let entry = self.costs.entry(*cost).or_insert(Gas::from_gas(0));
+            *entry = entry.saturating_add(*gas);
+        }
+        self.storage_delta += other.storage_delta;
+    }
+}
+
+impl fmt::Display for GasProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:<16} {:>20}", "Total gas", self.used_gas.as_gas())?;
+        for (category, gas) in &self.categories {
+            let percent = gas.as_gas() as f64 * 100.0 / self.used_gas.as_gas().max(1) as f64;
+            writeln!(
+                f,
+                "{:<16} {:>20} {:>6.2}%",
+                format!("{:?}", category),
+                gas.as_gas(),
+                percent
+            )?;
+        }
+        write!(f, "{:<16} {:>+20}", "Storage bytes", self.storage_delta)
+    }
+}
+
+/// State of the gas counters at some point of the execution, from which a [`GasProfile`] is
+/// computed.
+#[derive(Clone, Debug)]
+pub(crate) struct ProfileCheckpoint {
+    pub(crate) used_gas: u64,
+    pub(crate) storage_bytes: u64,
+    pub(crate) cost_counts: HashMap<ExtCosts, u64>,
+}
+
+impl ProfileCheckpoint {
+    /// Returns the profile of the execution from `start` to this checkpoint. `measurement_gas`
+    /// is the gas charged to read the used gas at this checkpoint, which is not included.
+    pub(crate) fn profile_since(
+        &self,
+        start: &ProfileCheckpoint,
+        measurement_gas: u64,
+        ext_costs: &ExtCostsConfig,
+    ) -> GasProfile {
+        let mut profile = GasProfile {
+            used_gas: Gas::from_gas(
+                self.used_gas.saturating_sub(start.used_gas).saturating_sub(measurement_gas),
+            ),
+            storage_delta: self.storage_bytes as i64 - start.storage_bytes as i64,
+            ..Default::default()
+        };
+        let mut host_gas = 0u64;
+        for (cost, count) in &self.cost_counts {
+            let count = count.saturating_sub(start.cost_counts.get(cost).copied().unwrap_or(0));
+            if count == 0 {
+                continue;
+            }
+            let gas = ext_costs.gas_cost(*cost).saturating_mul(count);
+            host_gas = host_gas.saturating_add(gas);
+            profile.costs.insert(*cost, Gas::from_gas(gas));
+            let entry =
+                profile.categories.entry(GasCategory::of(*cost)).or_insert(Gas::from_gas(0));
+            *entry = entry.saturating_add(Gas::from_gas(gas));
+        }
+        let actions_gas = profile.used_gas.as_gas().saturating_sub(host_gas);
+        if actions_gas > 0 {
+            profile.categories.insert(GasCategory::Actions, Gas::from_gas(actions_gas));
+        }
+        profile
+    }
+}
+
+/// Gas profiles of the previous executions and the start of the current one, kept by the
+/// [`MockedBlockchain`](crate::mock::MockedBlockchain) in profiling mode.
+#[derive(Clone, Debug)]
+pub(crate) struct GasProfiler {
+    pub(crate) profiles: Vec<GasProfile>,
+    pub(crate) start: ProfileCheckpoint,
+}
+
+/// Runs `f` and returns its result along with the profile of the gas it used and the storage it
+/// changed.
+///
+/// Reading the used gas is charged as a host function call, so profiling adds the base cost of
+/// a host function call to the gas used by the test, which isn't included in the profile.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::env;
+/// use near_sdk::test_utils::{profile, GasCategory};
+///
+/// let (_, profile) = profile(|| env::storage_write(b"key", b"value"));
+/// assert!(profile.gas(GasCategory::StorageWrite).as_gas() > 0);
+/// // The key and value, along with the fixed overhead of a storage record.
+/// assert_eq!(profile.storage_delta, 3 + 5 + 40);
+/// println!("{}", profile);
+/// ```
+pub fn profile<R>(f: impl FnOnce() -> R) -> (R, GasProfile) {
+    let start = crate::mock::with_mocked_blockchain(|b| b.profile_checkpoint());
+    let result = f();
+    let profile = crate::mock::with_mocked_blockchain(|b| b.profile_since(&start));
+    (result, profile)
+}
+
+/// Returns the gas profiles recorded by `testing_env!` in profiling mode: one for each previous
+/// `testing_env!`, covering the code executed in its context, followed by the one of the current
+/// context so far. Only available in unit tests.
+///
+/// # Panics
+///
+/// Panics if profiling is not enabled with `testing_env!(profile: context, ...)`.
+pub fn get_gas_profiles() -> Vec<GasProfile> {
+    crate::mock::with_mocked_blockchain(|b| b.gas_profiles()).unwrap_or_else(|| {
+        panic!("Gas profiling is not enabled, use `testing_env!(profile: context)`")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::VMContextBuilder;
+    use crate::{env, testing_env};
+
+    #[test]
+    fn profile_storage() {
+        env::storage_write(b"a", b"1");
+        let ((), report) = profile(|| {
+            env::storage_write(b"a", b"22");
+            env::storage_write(b"bb", b"3");
+            env::storage_read(b"a");
+        });
+        assert_eq!(report.storage_delta, 1 + 2 + 1 + 40);
+        assert!(report.gas(GasCategory::StorageWrite).as_gas() > 0);
+        assert!(report.gas(GasCategory::StorageRead).as_gas() > 0);
+        assert_eq!(report.gas(GasCategory::Actions), Gas::from_gas(0));
+        assert_eq!(report.gas(GasCategory::StorageRemove), Gas::from_gas(0));
+        let total = report.categories.values().map(|gas| gas.as_gas()).sum::<u64>();
+        assert_eq!(report.used_gas.as_gas(), total);
+
+        let ((), report) = profile(|| {
+            env::storage_remove(b"bb");
+        });
+        assert_eq!(report.storage_delta, -(2 + 1 + 40));
+    }
+
+    #[test]
+    fn profile_actions() {
+        let ((), report) = profile(|| {
+            let promise = env::promise_batch_create(&"bob.near".parse().unwrap());
+            env::promise_batch_action_function_call(
+                promise,
+                "method",
+                &[],
+                crate::NearToken::from_near(0),
+                Gas::from_tgas(5),
+            );
+        });
+        assert!(report.gas(GasCategory::Actions) > Gas::from_tgas(5));
+    }
+
+    #[test]
+    fn testing_env_profiles() {
+        testing_env!(profile: VMContextBuilder::new().build());
+        env::storage_write(b"key", b"value");
+        testing_env!(VMContextBuilder::new().build());
+        env::log_str("log");
+        env::storage_remove(b"key");
+
+        let profiles = get_gas_profiles();
+        assert_eq!(profiles.len(), 2);
+        assert_eq!(profiles[0].storage_delta, 3 + 5 + 40);
+        assert!(profiles[0].gas(GasCategory::StorageWrite).as_gas() > 0);
+        assert_eq!(profiles[0].gas(GasCategory::Logs), Gas::from_gas(0));
+        assert_eq!(profiles[1].storage_delta, -(3 + 5 + 40));
+        assert!(profiles[1].gas(GasCategory::Logs).as_gas() > 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Gas profiling is not enabled")]
+    fn profiles_disabled() {
+        testing_env!(VMContextBuilder::new().build());
+        get_gas_profiles();
+    }
+}
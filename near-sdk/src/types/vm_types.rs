@@ -14,7 +14,7 @@ pub type IteratorIndex = u64;
 
 /// When there is a callback attached to one or more contract calls the execution results of these
 /// calls are available to the contract invoked through the callback.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PromiseResult {
     Successful(Vec<u8>),
     Failed,
@@ -32,8 +32,32 @@ impl From<PromiseResult> for VmPromiseResult {
 
 /// All error variants which can occur with promise results.
 #[non_exhaustive]
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PromiseError {
-    /// Promise result failed.
+    /// Promise result failed. The runtime does not expose the reason, which can be that the
+    /// receiver panicked, ran out of gas or does not exist.
     Failed,
+    /// Promise result is not available yet, which only happens when a method is called directly
+    /// instead of as a callback of the promise.
+    NotReady,
+    /// Promise result is longer than the maximum length accepted. Holds the length of the
+    /// result.
+    TooLong(usize),
+    /// Promise result could not be deserialized. Holds the error of the deserializer.
+    DeserializationFailed(String),
 }
+
+impl std::fmt::Display for PromiseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Failed => f.write_str("Promise failed"),
+            Self::NotReady => f.write_str("Promise result is not ready"),
+            Self::TooLong(len) => write!(f, "Promise result of {} bytes is too long", len),
+            Self::DeserializationFailed(err) => {
+                write!(f, "Failed to deserialize promise result: {}", err)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PromiseError {}
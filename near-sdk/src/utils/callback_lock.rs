@@ -0,0 +1,173 @@
+use borsh::BorshSerialize;
+
+use crate::{env, Gas, NearToken, Promise, PromiseOrValue};
+
+/// Prefix of the storage keys of the locks.
+const LOCK_PREFIX: &[u8] = b"__near_lock";
+
+/// Prefix of the names of the methods generated by `#[near(lock = "...")]`, which release the
+/// lock once the promise chain launched by the method is executed.
+#[doc(hidden)]
+pub const RELEASE_LOCK_METHOD_PREFIX: &str = "__near_release_lock_";
+
+/// Gas attached to the receipt releasing a lock.
+const GAS_FOR_RELEASE_LOCK: Gas = Gas::from_tgas(5);
+
+/// Per-key locks held in storage while a promise chain is pending, to protect the state from
+/// being mutated before the callback of the chain is executed.
+///
+/// A method launching a promise chain acquires the lock of the key it works on, such as an
+/// account, and the callback of the chain releases it. In between, the methods touching the same
+/// key reject the calls with [`CallbackLock::require_unlocked`].
+///
+/// Keys are identified by their Borsh serialization, so keys of different types with the same
+/// serialization share the same lock.
+///
+/// Rather than calling these functions directly, methods can be annotated with
+/// `#[near(lock = "arg")]` and `#[near(reject_locked = "arg")]`, where `arg` is the argument
+/// holding the key. The lock acquired with `lock` is then released once the promise chain returned
+/// by the method is executed, even if its callback panics.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::{near, AccountId, CallbackLock, Gas, NearToken, Promise};
+///
+/// #[near(contract_state)]
+/// #[derive(Default)]
+/// pub struct Contract {}
+///
+/// #[near]
+/// impl Contract {
+///     pub fn withdraw(&mut self, account_id: AccountId) -> Promise {
+///         CallbackLock::acquire(&account_id);
+///         Promise::new(account_id.clone()).transfer(NearToken::from_near(1)).then(
+///             Self::ext(near_sdk::env::current_account_id())
+///                 .with_static_gas(Gas::from_tgas(5))
+///                 .on_withdraw(account_id),
+///         )
+///     }
+///
+///     #[private]
+///     pub fn on_withdraw(&mut self, account_id: AccountId) {
+///         CallbackLock::release(&account_id);
+///     }
+/// }
+/// ```
+pub struct CallbackLock;
+
+impl CallbackLock {
+    /// Locks `key`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` is already locked.
+    pub fn acquire<K: BorshSerialize + ?Sized>(key: &K) {
+        let storage_key = storage_key(key);
+        if env::storage_has_key(&storage_key) {
+            env::panic_str("The key is locked by a pending callback");
+        }
+        env::storage_write(&storage_key, &[]);
+    }
+
+    /// Unlocks `key`. Returns whether it was locked.
+    pub fn release<K: BorshSerialize + ?Sized>(key: &K) -> bool {
+        env::storage_remove(&storage_key(key))
+    }
+
+    /// Returns whether `key` is locked.
+    pub fn is_locked<K: BorshSerialize + ?Sized>(key: &K) -> bool {
+        env::storage_has_key(&storage_key(key))
+    }
+
+    /// Panics if `key` is locked.
+    pub fn require_unlocked<K: BorshSerialize + ?Sized>(key: &K) {
+        if Self::is_locked(key) {
+            env::panic_str("The key is locked by a pending callback");
+        }
+    }
+}
+
+fn storage_key<K: BorshSerialize + ?Sized>(key: &K) -> Vec<u8> {
+    let mut storage_key = LOCK_PREFIX.to_vec();
+    key.serialize(&mut storage_key)
+        .unwrap_or_else(|_| env::panic_str("Failed to serialize the lock key"));
+    storage_key
+}
+
+/// Return values of the methods which can be annotated with `#[near(lock = "...")]`, which
+/// release the lock once the promise chain they launched is executed.
+#[doc(hidden)]
+pub trait ReleaseLockAfter {
+    /// Releases the lock of the key serialized as `key` once `self` is executed, by calling the
+    /// method `release_method` of the contract, or right away if `self` isn't a promise.
+    fn release_lock_after(&self, key: Vec<u8>, release_method: &str);
+}
+
+impl ReleaseLockAfter for Promise {
+    fn release_lock_after(&self, key: Vec<u8>, release_method: &str) {
+        // The release depends on the promise without being returned, so that the result of the
+        // method is still the one of the promise, and it's executed whether it fails or not.
+        env::promise_then(
+            self.construct_recursively(),
+            env::current_account_id(),
+            release_method,
+            &key,
+            NearToken::from_yoctonear(0),
+            GAS_FOR_RELEASE_LOCK,
+        );
+    }
+}
+
+impl<T> ReleaseLockAfter for PromiseOrValue<T> {
+    fn release_lock_after(&self, key: Vec<u8>, release_method: &str) {
+        match self {
+            PromiseOrValue::Promise(promise) => promise.release_lock_after(key, release_method),
+            PromiseOrValue::Value(_) => {
+                env::storage_remove(&[LOCK_PREFIX, &key].concat());
+            }
+        }
+    }
+}
+
+/// Serializes `key` and locks it, for the methods annotated with `#[near(lock = "...")]`.
+#[doc(hidden)]
+pub fn acquire_lock<K: BorshSerialize + ?Sized>(key: &K) -> Vec<u8> {
+    CallbackLock::acquire(key);
+    borsh::to_vec(key).unwrap_or_else(|_| env::panic_str("Failed to serialize the lock key"))
+}
+
+/// Body of the methods releasing the locks, which take the serialized key as input.
+#[doc(hidden)]
+pub fn release_lock() {
+    if env::current_account_id() != env::predecessor_account_id() {
+        env::panic_str("Locks can only be released by the contract");
+    }
+    let key = env::input().unwrap_or_default();
+    env::storage_remove(&[LOCK_PREFIX, &key].concat());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::test_env::{alice, bob};
+
+    #[test]
+    fn acquire_and_release() {
+        assert!(!CallbackLock::is_locked(&alice()));
+        CallbackLock::acquire(&alice());
+        assert!(CallbackLock::is_locked(&alice()));
+        CallbackLock::require_unlocked(&bob());
+
+        assert!(CallbackLock::release(&alice()));
+        assert!(!CallbackLock::release(&alice()));
+        CallbackLock::require_unlocked(&alice());
+    }
+
+    #[test]
+    #[should_panic(expected = "The key is locked by a pending callback")]
+    fn acquire_locked() {
+        CallbackLock::acquire("key");
+        CallbackLock::acquire("key");
+    }
+}
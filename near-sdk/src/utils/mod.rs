@@ -6,6 +6,8 @@ mod stable_map;
 pub(crate) use self::stable_map::StableMap;
 mod cache_entry;
 pub(crate) use cache_entry::{CacheEntry, EntryState};
+pub(crate) mod callback_lock;
+pub use callback_lock::CallbackLock;
 
 use crate::{env, NearToken, PromiseResult};
 
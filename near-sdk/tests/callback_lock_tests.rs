@@ -0,0 +1,209 @@
+use near_sdk::serde_json::{json, Value};
+use near_sdk::test_utils::{native_contract, ExecutionOutcome, ReceiptExecutor};
+use near_sdk::{AccountId, Gas, NearToken};
+
+mod vault {
+    use near_sdk::{env, near, require, AccountId, Gas, Promise, PromiseError};
+    use std::collections::HashMap;
+
+    #[near(contract_state)]
+    #[derive(Default)]
+    pub struct Vault {
+        balances: HashMap<AccountId, u64>,
+    }
+
+    #[near(native_dispatch)]
+    impl Vault {
+        #[near(reject_locked = "account_id")]
+        pub fn deposit(&mut self, account_id: AccountId, amount: u64) {
+            *self.balances.entry(account_id).or_default() += amount;
+        }
+
+        /// Sends the whole balance of `account_id` to `receiver_id`.
+        #[near(lock = "account_id")]
+        pub fn withdraw(&mut self, account_id: AccountId, receiver_id: AccountId) -> Promise {
+            let amount = self.balances.remove(&account_id).unwrap_or_default();
+            require!(amount > 0, "Nothing to withdraw");
+            super::receiver::Receiver::ext(receiver_id)
+                .with_static_gas(Gas::from_tgas(20))
+                .on_withdraw(account_id.clone(), amount)
+                .then(
+                    Self::ext(env::current_account_id())
+                        .with_static_gas(Gas::from_tgas(10))
+                        .on_withdrawn(account_id, amount),
+                )
+        }
+
+        #[private]
+        pub fn on_withdrawn(
+            &mut self,
+            account_id: AccountId,
+            amount: u64,
+            #[callback_result] result: Result<(), PromiseError>,
+        ) -> bool {
+            if result.is_err() {
+                *self.balances.entry(account_id).or_default() += amount;
+            }
+            result.is_ok()
+        }
+
+        /// Like `withdraw`, with a callback which panics.
+        #[near(lock = "account_id")]
+        pub fn withdraw_and_panic(&mut self, account_id: AccountId) -> Promise {
+            Self::ext(env::current_account_id())
+                .with_static_gas(Gas::from_tgas(5))
+                .balance_of(account_id.clone())
+                .then(
+                    Self::ext(env::current_account_id())
+                        .with_static_gas(Gas::from_tgas(5))
+                        .on_withdrawn_panic(account_id),
+                )
+        }
+
+        #[private]
+        pub fn on_withdrawn_panic(&mut self, account_id: AccountId) {
+            self.balances.remove(&account_id);
+            env::panic_str("Callback failed");
+        }
+
+        pub fn balance_of(&self, account_id: AccountId) -> u64 {
+            self.balances.get(&account_id).copied().unwrap_or_default()
+        }
+    }
+}
+
+mod receiver {
+    use near_sdk::{env, near, AccountId, Gas, Promise};
+
+    #[near(contract_state)]
+    #[derive(Default)]
+    pub struct Receiver {}
+
+    #[near(native_dispatch)]
+    impl Receiver {
+        /// Tries to deposit back part of the amount while the withdrawal is pending.
+        pub fn on_withdraw(&mut self, account_id: AccountId, amount: u64) -> Promise {
+            super::vault::Vault::ext(env::predecessor_account_id())
+                .with_static_gas(Gas::from_tgas(5))
+                .deposit(account_id, amount / 2)
+        }
+    }
+}
+
+use receiver::Receiver;
+use vault::Vault;
+
+fn alice() -> AccountId {
+    "alice.near".parse().unwrap()
+}
+
+fn vault_id() -> AccountId {
+    "vault.near".parse().unwrap()
+}
+
+fn receiver_id() -> AccountId {
+    "receiver.near".parse().unwrap()
+}
+
+fn setup() -> ReceiptExecutor {
+    let mut executor = ReceiptExecutor::new();
+    for account_id in [alice(), vault_id(), receiver_id()] {
+        executor.create_account(account_id, NearToken::from_near(10));
+    }
+    executor.deploy(
+        &vault_id(),
+        native_contract!(Vault {
+            deposit,
+            withdraw,
+            on_withdrawn,
+            withdraw_and_panic,
+            on_withdrawn_panic,
+            balance_of
+        }),
+    );
+    executor.deploy(&receiver_id(), native_contract!(Receiver { on_withdraw }));
+    call(&mut executor, "deposit", json!({ "account_id": alice(), "amount": 10 }));
+    executor
+}
+
+fn call(executor: &mut ReceiptExecutor, method_name: &str, args: Value) -> ExecutionOutcome {
+    executor.call(
+        alice(),
+        vault_id(),
+        method_name,
+        args.to_string().into_bytes(),
+        NearToken::from_near(0),
+        Gas::from_tgas(100),
+    )
+}
+
+#[test]
+fn locked_key_rejects_reentrant_calls() {
+    let mut executor = setup();
+
+    let outcome = call(
+        &mut executor,
+        "withdraw",
+        json!({ "account_id": alice(), "receiver_id": receiver_id() }),
+    );
+    // The deposit made by the receiver before the callback is rejected, so the withdrawal fails
+    // and the callback restores the balance.
+    let failures = outcome.failures().collect::<Vec<_>>();
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].receiver_id, vault_id());
+    assert!(failures[0].failure.as_ref().unwrap().contains("locked by a pending callback"));
+    assert!(!outcome.json::<bool>());
+    let balance = call(&mut executor, "balance_of", json!({ "account_id": alice() }));
+    assert_eq!(balance.json::<u64>(), 10);
+
+    // The lock was released after the callback.
+    let outcome = call(&mut executor, "deposit", json!({ "account_id": alice(), "amount": 5 }));
+    assert!(outcome.is_success());
+    let balance = call(&mut executor, "balance_of", json!({ "account_id": alice() }));
+    assert_eq!(balance.json::<u64>(), 15);
+}
+
+#[test]
+fn lock_released_on_failure() {
+    let mut executor = setup();
+    let missing: AccountId = "missing.near".parse().unwrap();
+
+    let outcome =
+        call(&mut executor, "withdraw", json!({ "account_id": alice(), "receiver_id": missing }));
+    assert!(!outcome.json::<bool>());
+
+    let outcome = call(&mut executor, "deposit", json!({ "account_id": alice(), "amount": 5 }));
+    assert!(outcome.is_success());
+}
+
+#[test]
+fn lock_released_when_callback_panics() {
+    let mut executor = setup();
+
+    let outcome = call(&mut executor, "withdraw_and_panic", json!({ "account_id": alice() }));
+    let failures = outcome.failures().collect::<Vec<_>>();
+    assert_eq!(failures.len(), 1);
+    assert!(failures[0].failure.as_ref().unwrap().contains("Callback failed"));
+    // The result is still the one of the callback.
+    assert!(outcome.result.unwrap_err().contains("Callback failed"));
+    let balance = call(&mut executor, "balance_of", json!({ "account_id": alice() }));
+    assert_eq!(balance.json::<u64>(), 10);
+
+    let outcome = call(&mut executor, "deposit", json!({ "account_id": alice(), "amount": 5 }));
+    assert!(outcome.is_success());
+}
+
+#[test]
+fn lock_release_is_private() {
+    let mut executor = setup();
+
+    let outcome = call(&mut executor, "__near_release_lock_withdraw", json!({}));
+    assert!(outcome
+        .failures()
+        .next()
+        .unwrap()
+        .failure
+        .as_ref()
+        .unwrap()
+        .contains("Locks can only"));
+}
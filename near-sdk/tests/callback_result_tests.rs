@@ -0,0 +1,88 @@
+use near_sdk::test_utils::{native_contract, ReceiptExecutor};
+use near_sdk::{env, near, AccountId, Gas, NearToken, Promise, PromiseError};
+
+#[near(contract_state)]
+#[derive(Default)]
+pub struct Caller {}
+
+#[near(native_dispatch)]
+impl Caller {
+    pub fn call(&self, method_name: String) -> Promise {
+        Promise::new(callee())
+            .function_call(method_name, vec![], NearToken::from_near(0), Gas::from_tgas(10))
+            .then(Self::ext(env::current_account_id()).on_result())
+    }
+
+    #[private]
+    pub fn on_result(
+        &self,
+        #[callback_result(max_len = 16)] result: Result<u64, PromiseError>,
+    ) -> String {
+        match result {
+            Ok(value) => value.to_string(),
+            Err(PromiseError::DeserializationFailed(_)) => "deserialization failed".to_string(),
+            Err(err) => format!("{:?}", err),
+        }
+    }
+}
+
+mod callee {
+    use super::*;
+
+    #[near(contract_state)]
+    #[derive(Default)]
+    pub struct Callee {}
+
+    #[near(native_dispatch)]
+    impl Callee {
+        pub fn number(&self) -> u64 {
+            42
+        }
+
+        pub fn text(&self) -> String {
+            "forty-two".to_string()
+        }
+
+        pub fn long_text(&self) -> String {
+            "forty-two".repeat(10)
+        }
+
+        pub fn fail(&self) {
+            env::panic_str("Callee failed");
+        }
+    }
+}
+
+fn caller() -> AccountId {
+    "caller.near".parse().unwrap()
+}
+
+fn callee() -> AccountId {
+    "callee.near".parse().unwrap()
+}
+
+fn callback_result(method_name: &str) -> String {
+    let mut executor = ReceiptExecutor::new();
+    executor.create_account(caller(), NearToken::from_near(10));
+    executor.create_account(callee(), NearToken::from_near(10));
+    executor.deploy(&caller(), native_contract!(Caller { call, on_result }));
+    executor.deploy(&callee(), native_contract!(callee::Callee { number, text, long_text, fail }));
+    let outcome = executor.call(
+        caller(),
+        caller(),
+        "call",
+        near_sdk::serde_json::json!({ "method_name": method_name }).to_string().into_bytes(),
+        NearToken::from_near(0),
+        Gas::from_tgas(100),
+    );
+    outcome.json()
+}
+
+#[test]
+fn callback_result_errors() {
+    assert_eq!(callback_result("number"), "42");
+    assert_eq!(callback_result("text"), "deserialization failed");
+    // The result is the 90 characters of the string along with its quotes.
+    assert_eq!(callback_result("long_text"), "TooLong(92)");
+    assert_eq!(callback_result("fail"), "Failed");
+}
@@ -0,0 +1,74 @@
+use near_sdk::mock::MockAction;
+use near_sdk::serde_json::{self, json};
+use near_sdk::test_utils::{get_created_receipts, VMContextBuilder};
+use near_sdk::{ext_contract_from_abi, testing_env, AccountId, NearToken};
+
+ext_contract_from_abi!(ext_token, "tests/abi/token_abi.json");
+ext_contract_from_abi!(ext_keywords, "compilation_tests/abi/keywords_abi.json");
+
+fn token() -> AccountId {
+    "token.near".parse().unwrap()
+}
+
+/// Returns the method name and arguments of the function calls created.
+fn function_calls() -> Vec<(String, Vec<u8>)> {
+    get_created_receipts()
+        .into_iter()
+        .flat_map(|receipt| receipt.actions)
+        .filter_map(|action| match action {
+            MockAction::FunctionCallWeight { method_name, args, .. } => {
+                Some((String::from_utf8(method_name).unwrap(), args))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn json_functions() {
+    testing_env!(VMContextBuilder::new().build());
+
+    ext_token::ext(token()).ft_balance_of("alice.near".parse().unwrap());
+    ext_token::ext(token()).ft_total_supply();
+    ext_token::ext(token()).with_attached_deposit(NearToken::from_yoctonear(1)).ft_transfer(
+        "bob.near".parse().unwrap(),
+        "100".to_string(),
+        None,
+    );
+    ext_token::ext(token()).set_metadata(json!({ "name": "Token", "symbol": "TKN" }), Some(24));
+
+    let calls = function_calls();
+    let args = |index: usize| serde_json::from_slice::<serde_json::Value>(&calls[index].1).unwrap();
+    assert_eq!(calls[0].0, "ft_balance_of");
+    assert_eq!(args(0), json!({ "account_id": "alice.near" }));
+    assert_eq!(calls[1], ("ft_total_supply".to_string(), vec![]));
+    assert_eq!(args(2), json!({ "receiver_id": "bob.near", "amount": "100", "memo": null }));
+    assert_eq!(
+        args(3),
+        json!({ "metadata": { "name": "Token", "symbol": "TKN" }, "decimals": 24 })
+    );
+}
+
+#[test]
+fn borsh_functions() {
+    testing_env!(VMContextBuilder::new().build());
+
+    ext_token::ext(token()).set_owners(vec!["alice.near".to_string()], 1);
+
+    let calls = function_calls();
+    assert_eq!(calls[0].0, "set_owners");
+    let expected = near_sdk::borsh::to_vec(&(vec!["alice.near".to_string()], 1u32)).unwrap();
+    assert_eq!(calls[0].1, expected);
+}
+
+#[test]
+fn keyword_names() {
+    testing_env!(VMContextBuilder::new().build());
+
+    ext_keywords::ext(token()).r#type("value".to_string(), 1);
+
+    let calls = function_calls();
+    assert_eq!(calls[0].0, "type");
+    let args: serde_json::Value = serde_json::from_slice(&calls[0].1).unwrap();
+    assert_eq!(args, json!({ "fn": "value", "amount": 1 }));
+}
@@ -0,0 +1,230 @@
+use near_sdk::serde_json::json;
+use near_sdk::test_utils::{native_contract, ExecutionOutcome, ReceiptExecutor};
+use near_sdk::{env, near, require, AccountId, Gas, NearToken};
+
+#[near(contract_state)]
+#[derive(Default)]
+pub struct Registry {
+    owner_id: Option<AccountId>,
+    minters: Vec<AccountId>,
+    paused: bool,
+    entries: u32,
+}
+
+#[near(native_dispatch)]
+impl Registry {
+    #[init]
+    #[deposit(exact = "1 NEAR")]
+    pub fn new(owner_id: AccountId) -> Self {
+        Self { owner_id: Some(owner_id), ..Default::default() }
+    }
+
+    #[near(owner_only)]
+    pub fn add_minter(&mut self, account_id: AccountId) {
+        self.minters.push(account_id);
+    }
+
+    #[near(owner_only)]
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    /// Registers an entry.
+    #[near(role = "minter", guard = "Self::assert_not_paused")]
+    #[deposit(min = "0.01 NEAR")]
+    #[gas(min = "20 Tgas")]
+    pub fn register(&mut self) -> u32 {
+        self.entries += 1;
+        self.entries
+    }
+
+    fn assert_owner(&self) {
+        require!(self.owner_id == Some(env::predecessor_account_id()), "Not the owner");
+    }
+
+    fn assert_role(&self, role: &str) {
+        require!(role == "minter", "Unknown role");
+        require!(self.minters.contains(&env::predecessor_account_id()), "Not a minter");
+    }
+
+    fn assert_not_paused(&self) {
+        require!(!self.paused, "Paused");
+    }
+}
+
+#[near]
+impl Registry {
+    pub fn entries(&self) -> u32 {
+        self.entries
+    }
+}
+
+fn alice() -> AccountId {
+    "alice.near".parse().unwrap()
+}
+
+fn bob() -> AccountId {
+    "bob.near".parse().unwrap()
+}
+
+fn registry() -> AccountId {
+    "registry.near".parse().unwrap()
+}
+
+fn call(
+    executor: &mut ReceiptExecutor,
+    signer_id: AccountId,
+    method_name: &str,
+    args: near_sdk::serde_json::Value,
+    deposit: NearToken,
+    gas: Gas,
+) -> ExecutionOutcome {
+    executor.call(signer_id, registry(), method_name, args.to_string().into_bytes(), deposit, gas)
+}
+
+fn setup() -> ReceiptExecutor {
+    let mut executor = ReceiptExecutor::new();
+    executor.create_account(alice(), NearToken::from_near(10));
+    executor.create_account(bob(), NearToken::from_near(10));
+    executor.create_account(registry(), NearToken::from_near(10));
+    executor
+        .deploy(&registry(), native_contract!(Registry { new, add_minter, set_paused, register }));
+    executor
+}
+
+fn error(outcome: ExecutionOutcome) -> String {
+    outcome.result.unwrap_err()
+}
+
+#[test]
+fn init_deposit() {
+    let mut executor = setup();
+    let args = json!({ "owner_id": alice() });
+    let outcome = call(
+        &mut executor,
+        alice(),
+        "new",
+        args.clone(),
+        NearToken::from_millinear(999),
+        Gas::from_tgas(30),
+    );
+    assert!(error(outcome).contains("Method new requires a deposit of exactly 1 NEAR"));
+
+    let outcome =
+        call(&mut executor, alice(), "new", args, NearToken::from_near(1), Gas::from_tgas(30));
+    assert!(outcome.is_success());
+}
+
+#[test]
+fn owner_only() {
+    let mut executor = setup();
+    let args = json!({ "owner_id": alice() });
+    call(&mut executor, alice(), "new", args, NearToken::from_near(1), Gas::from_tgas(30));
+
+    // The guard is checked before the arguments are deserialized.
+    let outcome = call(
+        &mut executor,
+        bob(),
+        "add_minter",
+        json!({}),
+        NearToken::from_near(0),
+        Gas::from_tgas(30),
+    );
+    assert!(error(outcome).contains("Not the owner"));
+
+    let args = json!({ "account_id": bob() });
+    let outcome = call(
+        &mut executor,
+        alice(),
+        "add_minter",
+        args,
+        NearToken::from_near(0),
+        Gas::from_tgas(30),
+    );
+    assert!(outcome.is_success());
+}
+
+#[test]
+fn role_deposit_gas_and_guard() {
+    let mut executor = setup();
+    let args = json!({ "owner_id": alice() });
+    call(&mut executor, alice(), "new", args, NearToken::from_near(1), Gas::from_tgas(30));
+    let deposit = NearToken::from_millinear(10);
+
+    let outcome = call(&mut executor, bob(), "register", json!({}), deposit, Gas::from_tgas(30));
+    assert!(error(outcome).contains("Not a minter"));
+
+    let args = json!({ "account_id": bob() });
+    call(&mut executor, alice(), "add_minter", args, NearToken::from_near(0), Gas::from_tgas(30));
+
+    let outcome = call(
+        &mut executor,
+        bob(),
+        "register",
+        json!({}),
+        NearToken::from_millinear(9),
+        Gas::from_tgas(30),
+    );
+    assert!(error(outcome).contains("Method register requires a deposit of at least 0.01 NEAR"));
+
+    let outcome = call(&mut executor, bob(), "register", json!({}), deposit, Gas::from_tgas(10));
+    assert!(error(outcome).contains("Method register requires at least 20 Tgas of prepaid gas"));
+
+    let outcome = call(&mut executor, bob(), "register", json!({}), deposit, Gas::from_tgas(30));
+    assert_eq!(outcome.json::<u32>(), 1);
+
+    let args = json!({ "paused": true });
+    call(&mut executor, alice(), "set_paused", args, NearToken::from_near(0), Gas::from_tgas(30));
+    let outcome = call(&mut executor, bob(), "register", json!({}), deposit, Gas::from_tgas(30));
+    assert!(error(outcome).contains("Paused"));
+}
+
+#[cfg(feature = "__abi-generate")]
+#[test]
+fn abi_requirements() {
+    use near_sdk::__private::{AbiFunctionRequirements, ChunkedAbiEntry};
+
+    // The ABI chunk of an impl block is exported under the name of its first method.
+    #[allow(improper_ctypes)]
+    extern "C" {
+        fn __near_abi_new() -> (*const u8, usize);
+        fn __near_abi_entries() -> (*const u8, usize);
+    }
+
+    // Chunks are read and combined the way `cargo near` builds the ABI of the contract.
+    let chunks =
+        [unsafe { __near_abi_new() }, unsafe { __near_abi_entries() }].map(|(ptr, len)| {
+            let chunk = unsafe { std::slice::from_raw_parts(ptr, len) };
+            near_sdk::serde_json::from_slice::<ChunkedAbiEntry>(chunk).unwrap()
+        });
+    let abi = ChunkedAbiEntry::combine(chunks).unwrap();
+    let requirements = |name: &str| {
+        let function = abi.body.functions.iter().find(|f| f.name == name).unwrap();
+        let requirements = function.doc.as_deref().and_then(AbiFunctionRequirements::from_doc);
+        requirements.map(|requirements| near_sdk::serde_json::to_value(requirements).unwrap())
+    };
+
+    assert_eq!(
+        requirements("new"),
+        Some(json!({ "deposit": { "exact": "1000000000000000000000000" } }))
+    );
+    assert_eq!(requirements("add_minter"), Some(json!({ "guards": ["owner"] })));
+    assert_eq!(requirements("set_paused"), Some(json!({ "guards": ["owner"] })));
+    assert_eq!(
+        requirements("register"),
+        Some(json!({
+            "deposit": { "min": "10000000000000000000000" },
+            "min_gas": "20000000000000",
+            "guards": [{ "role": "minter" }, { "function": "Self::assert_not_paused" }],
+        }))
+    );
+    assert_eq!(requirements("entries"), None);
+
+    // The requirements follow the human readable doc.
+    let register = abi.body.functions.iter().find(|f| f.name == "register").unwrap();
+    assert!(register
+        .doc
+        .as_ref()
+        .unwrap()
+        .starts_with(" Registers an entry.\n\n```near-requirements\n"));
+}
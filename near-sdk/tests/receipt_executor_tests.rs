@@ -0,0 +1,284 @@
+use near_sdk::json_types::U128;
+use near_sdk::serde_json::json;
+use near_sdk::store::LookupMap;
+use near_sdk::test_utils::{native_contract, GasCategory, ReceiptExecutor};
+use near_sdk::{
+    env, ext_contract, near, require, AccountId, Gas, NearToken, PanicOnDefault, Promise,
+    PromiseOrValue, PromiseResult,
+};
+
+#[ext_contract(ext_receiver)]
+pub trait Receiver {
+    fn on_transfer(&mut self, sender_id: AccountId, amount: U128, msg: String) -> U128;
+}
+
+#[near(contract_state)]
+#[derive(PanicOnDefault)]
+pub struct Token {
+    balances: LookupMap<AccountId, u128>,
+}
+
+#[near(native_dispatch)]
+impl Token {
+    #[init]
+    pub fn new(owner_id: AccountId, total_supply: U128) -> Self {
+        let mut balances = LookupMap::new(b"b");
+        balances.insert(owner_id, total_supply.0);
+        Self { balances }
+    }
+
+    pub fn balance_of(&self, account_id: AccountId) -> U128 {
+        U128(self.balances.get(&account_id).copied().unwrap_or_default())
+    }
+
+    pub fn transfer(&mut self, receiver_id: AccountId, amount: U128) {
+        self.internal_transfer(&env::predecessor_account_id(), &receiver_id, amount.0);
+    }
+
+    pub fn transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        amount: U128,
+        msg: String,
+    ) -> PromiseOrValue<U128> {
+        let sender_id = env::predecessor_account_id();
+        self.internal_transfer(&sender_id, &receiver_id, amount.0);
+        ext_receiver::ext(receiver_id.clone())
+            .with_static_gas(Gas::from_tgas(10))
+            .on_transfer(sender_id.clone(), amount, msg)
+            .then(Self::ext(env::current_account_id()).resolve_transfer(
+                sender_id,
+                receiver_id,
+                amount,
+            ))
+            .into()
+    }
+
+    #[private]
+    pub fn resolve_transfer(
+        &mut self,
+        sender_id: AccountId,
+        receiver_id: AccountId,
+        amount: U128,
+    ) -> U128 {
+        let unused = match env::promise_result(0) {
+            PromiseResult::Successful(value) => {
+                near_sdk::serde_json::from_slice::<U128>(&value).unwrap().0.min(amount.0)
+            }
+            PromiseResult::Failed => amount.0,
+        };
+        if unused > 0 {
+            self.internal_transfer(&receiver_id, &sender_id, unused);
+        }
+        U128(amount.0 - unused)
+    }
+
+    #[payable]
+    pub fn create_holder(&mut self, account_id: AccountId) -> Promise {
+        self.balances.insert(account_id.clone(), 0);
+        Promise::new(account_id).create_account().transfer(env::attached_deposit())
+    }
+}
+
+impl Token {
+    fn internal_transfer(&mut self, sender_id: &AccountId, receiver_id: &AccountId, amount: u128) {
+        let sender_balance = self.balances.get(sender_id).copied().unwrap_or_default();
+        require!(sender_balance >= amount, "Not enough balance");
+        self.balances.insert(sender_id.clone(), sender_balance - amount);
+        *self.balances.entry(receiver_id.clone()).or_default() += amount;
+    }
+}
+
+// Each contract lives in its own module, as they would in their own crates.
+mod vault {
+    use super::*;
+
+    #[near(contract_state)]
+    #[derive(Default)]
+    pub struct Vault {
+        deposits: u128,
+    }
+
+    #[near(native_dispatch)]
+    impl Receiver for Vault {
+        fn on_transfer(&mut self, sender_id: AccountId, amount: U128, msg: String) -> U128 {
+            require!(msg != "panic", "Vault rejected the transfer");
+            env::log_str(&format!("{} deposited {}", sender_id, amount.0));
+            let refund = if msg == "refund half" { amount.0 / 2 } else { 0 };
+            self.deposits += amount.0 - refund;
+            U128(refund)
+        }
+    }
+
+    #[near(native_dispatch)]
+    impl Vault {
+        pub fn deposits(&self) -> U128 {
+            U128(self.deposits)
+        }
+    }
+}
+
+fn alice() -> AccountId {
+    "alice.near".parse().unwrap()
+}
+
+fn token() -> AccountId {
+    "token.near".parse().unwrap()
+}
+
+fn vault() -> AccountId {
+    "vault.near".parse().unwrap()
+}
+
+fn setup() -> ReceiptExecutor {
+    let mut executor = ReceiptExecutor::new();
+    for account_id in [alice(), token(), vault()] {
+        executor.create_account(account_id, NearToken::from_near(10));
+    }
+    executor.deploy(
+        &token(),
+        native_contract!(Token {
+            new,
+            balance_of,
+            transfer,
+            transfer_call,
+            resolve_transfer,
+            create_holder
+        }),
+    );
+    executor.deploy(&vault(), native_contract!(vault::Vault { on_transfer, deposits }));
+    let outcome =
+        call(&mut executor, token(), "new", json!({"owner_id": alice(), "total_supply": "1000"}));
+    assert!(outcome.is_success(), "{:?}", outcome);
+    executor
+}
+
+fn call(
+    executor: &mut ReceiptExecutor,
+    receiver_id: AccountId,
+    method_name: &str,
+    args: near_sdk::serde_json::Value,
+) -> near_sdk::test_utils::ExecutionOutcome {
+    executor.call(
+        alice(),
+        receiver_id,
+        method_name,
+        args.to_string().into_bytes(),
+        NearToken::from_near(0),
+        Gas::from_tgas(300),
+    )
+}
+
+fn balance_of(executor: &mut ReceiptExecutor, account_id: AccountId) -> u128 {
+    call(executor, token(), "balance_of", json!({ "account_id": account_id })).json::<U128>().0
+}
+
+#[test]
+fn transfer_call_round_trip() {
+    let mut executor = setup();
+    let outcome = call(
+        &mut executor,
+        token(),
+        "transfer_call",
+        json!({"receiver_id": vault(), "amount": "100", "msg": "refund half"}),
+    );
+    // The transaction returns the value of the callback.
+    assert_eq!(outcome.json::<U128>(), U128(50));
+    assert_eq!(outcome.receipts.len(), 3);
+    assert_eq!(outcome.logs(), vec!["alice.near deposited 100"]);
+    assert_eq!(outcome.failures().count(), 0);
+
+    assert_eq!(balance_of(&mut executor, alice()), 950);
+    assert_eq!(balance_of(&mut executor, vault()), 50);
+    let deposits = call(&mut executor, vault(), "deposits", json!({}));
+    assert_eq!(deposits.json::<U128>(), U128(50));
+}
+
+#[test]
+fn receipt_gas_profiles() {
+    let mut executor = setup();
+    let outcome = call(
+        &mut executor,
+        token(),
+        "transfer_call",
+        json!({"receiver_id": vault(), "amount": "100", "msg": "refund half"}),
+    );
+    // `transfer_call` writes the balances and creates the function calls to the vault and to the
+    // callback, which gas is attributed to the actions.
+    let profile = &outcome.receipts[0].gas_profile;
+    assert!(profile.gas(GasCategory::StorageWrite).as_gas() > 0);
+    assert!(profile.gas(GasCategory::Actions) > Gas::from_tgas(10));
+    assert!(profile.used_gas >= profile.gas(GasCategory::Actions));
+    // The vault balance is a new entry of the map.
+    assert!(profile.storage_delta > 0);
+
+    let outcome = call(&mut executor, token(), "balance_of", json!({ "account_id": alice() }));
+    let profile = &outcome.receipts[0].gas_profile;
+    assert_eq!(profile.storage_delta, 0);
+    assert_eq!(profile.gas(GasCategory::StorageWrite), Gas::from_gas(0));
+    assert!(profile.gas(GasCategory::StorageRead).as_gas() > 0);
+}
+
+#[test]
+fn failed_receipt_is_reverted() {
+    let mut executor = setup();
+    let outcome = call(
+        &mut executor,
+        token(),
+        "transfer_call",
+        json!({"receiver_id": vault(), "amount": "100", "msg": "panic"}),
+    );
+    // The callback sees the failure of `on_transfer` and refunds the whole amount.
+    assert_eq!(outcome.json::<U128>(), U128(0));
+    let failures: Vec<_> = outcome.failures().collect();
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].receiver_id, vault());
+    assert!(failures[0].failure.as_ref().unwrap().contains("Vault rejected the transfer"));
+    assert_eq!(balance_of(&mut executor, alice()), 1000);
+
+    let outcome =
+        call(&mut executor, token(), "transfer", json!({"receiver_id": vault(), "amount": "2000"}));
+    assert!(outcome.result.unwrap_err().contains("Not enough balance"));
+    assert_eq!(balance_of(&mut executor, vault()), 0);
+
+    let outcome = call(&mut executor, vault(), "withdraw", json!({}));
+    assert!(outcome.result.unwrap_err().contains("withdraw is not found"));
+}
+
+#[test]
+fn create_account_and_transfer() {
+    let mut executor = setup();
+    let holder: AccountId = "holder.token.near".parse().unwrap();
+    let outcome = executor.call(
+        alice(),
+        token(),
+        "create_holder",
+        json!({ "account_id": holder }).to_string().into_bytes(),
+        NearToken::from_near(2),
+        Gas::from_tgas(300),
+    );
+    assert!(outcome.is_success(), "{:?}", outcome);
+    assert_eq!(executor.balance(&holder), Some(NearToken::from_near(2)));
+    assert_eq!(executor.balance(&alice()), Some(NearToken::from_near(8)));
+    assert_eq!(executor.balance(&token()), Some(NearToken::from_near(10)));
+
+    // Creating the account again fails, and the deposit is refunded to the token contract.
+    let outcome = executor.call(
+        alice(),
+        token(),
+        "create_holder",
+        json!({ "account_id": holder }).to_string().into_bytes(),
+        NearToken::from_near(1),
+        Gas::from_tgas(300),
+    );
+    assert!(outcome
+        .failures()
+        .next()
+        .unwrap()
+        .failure
+        .as_ref()
+        .unwrap()
+        .contains("already exists"));
+    assert_eq!(executor.balance(&holder), Some(NearToken::from_near(2)));
+    assert_eq!(executor.balance(&token()), Some(NearToken::from_near(11)));
+}
@@ -0,0 +1,97 @@
+use near_sdk::store::{
+    check_storage_prefixes, storage_prefixes, IterableMap, LookupMap, LookupSet, Vector,
+};
+use near_sdk::{near, AccountId, IntoStorageKey};
+
+#[near]
+pub struct Ledger {
+    accounts: LookupMap<AccountId, u128>,
+    total_supply: u128,
+}
+
+impl Ledger {
+    fn new(prefix: impl IntoStorageKey) -> Self {
+        Self { accounts: LookupMap::new(prefix), total_supply: 0 }
+    }
+}
+
+#[near(contract_state, auto_prefix)]
+pub struct Contract {
+    owner_id: AccountId,
+    tokens: IterableMap<u32, String>,
+    r#type: LookupSet<String>,
+    #[near(auto_prefix)]
+    ledger: Ledger,
+}
+
+impl Default for Contract {
+    fn default() -> Self {
+        Self {
+            owner_id: "owner.near".parse().unwrap(),
+            tokens: IterableMap::new(ContractStorageKey::Tokens),
+            r#type: LookupSet::new(ContractStorageKey::Type),
+            ledger: Ledger::new(ContractStorageKey::Ledger),
+        }
+    }
+}
+
+#[near]
+impl Contract {}
+
+#[near(serializers = [borsh], auto_prefix = "index")]
+pub struct Indexed {
+    first: Vector<u8>,
+    count: u32,
+    second: near_sdk::collections::LookupSet<u8>,
+}
+
+#[near]
+pub struct Pair(Vector<u8>, Ledger);
+
+#[test]
+fn name_prefixes() {
+    assert_eq!(ContractStorageKey::Tokens.into_storage_key(), b"tokens:");
+    assert_eq!(ContractStorageKey::Type.into_storage_key(), b"type:");
+
+    let contract = Contract::default();
+    let prefixes: Vec<_> = storage_prefixes(&contract)
+        .into_iter()
+        .map(|p| (p.path, String::from_utf8(p.prefix).unwrap()))
+        .collect();
+    assert_eq!(
+        prefixes,
+        [
+            ("ledger.accounts".to_string(), "ledger:".to_string()),
+            ("tokens.values".to_string(), "tokens:m".to_string()),
+            ("tokens.keys".to_string(), "tokens:v".to_string()),
+            ("type".to_string(), "type:".to_string()),
+        ]
+    );
+    assert!(check_storage_prefixes(&contract).is_ok());
+}
+
+#[test]
+fn index_prefixes() {
+    assert_eq!(IndexedStorageKey::First.into_storage_key(), [0]);
+    assert_eq!(IndexedStorageKey::Second.into_storage_key(), [1]);
+
+    let indexed = Indexed {
+        first: Vector::new(IndexedStorageKey::First),
+        count: 0,
+        second: near_sdk::collections::LookupSet::new(IndexedStorageKey::Second),
+    };
+    assert!(check_storage_prefixes(&indexed).is_ok());
+}
+
+#[test]
+fn nested_overlap() {
+    let pair = Pair(Vector::new(b"l"), Ledger::new(b"ledger".as_slice()));
+    let overlap = check_storage_prefixes(&pair).unwrap_err();
+    assert_eq!(overlap.prefix.path, "0");
+    assert_eq!(overlap.overlapping.path, "1.accounts");
+
+    let pair = Pair(Vector::new(b"S"), Ledger::new(b"ledger".as_slice()));
+    let overlap = check_storage_prefixes(&pair).unwrap_err();
+    assert_eq!(overlap.prefix.path, "0");
+    assert_eq!(overlap.overlapping.path, "STATE");
+}
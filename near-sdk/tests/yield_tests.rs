@@ -0,0 +1,105 @@
+use near_sdk::mock::YieldStatus;
+use near_sdk::serde_json::json;
+use near_sdk::test_utils::{native_contract, ReceiptExecutor};
+use near_sdk::{
+    env, near, AccountId, CryptoHash, Gas, NearToken, Promise, PromiseError, PromiseOrValue,
+};
+
+#[near(contract_state)]
+#[derive(Default)]
+pub struct Oracle {
+    requests: Vec<CryptoHash>,
+}
+
+#[near(native_dispatch)]
+impl Oracle {
+    pub fn request(&mut self, cached: Option<u64>) -> PromiseOrValue<String> {
+        if let Some(price) = cached {
+            return PromiseOrValue::Value(price.to_string());
+        }
+        let yielded = Promise::new_yield("on_response", vec![], Gas::from_tgas(10));
+        self.requests.push(yielded.data_id());
+        yielded.into()
+    }
+
+    pub fn respond(&mut self, price: u64) -> bool {
+        let data_id = self.requests.pop().unwrap_or_else(|| env::panic_str("No pending request"));
+        Promise::resume(&data_id, &price)
+    }
+
+    #[private]
+    pub fn on_response(&self, #[callback_result] price: Result<u64, PromiseError>) -> String {
+        match price {
+            Ok(price) => price.to_string(),
+            Err(_) => "timed out".to_string(),
+        }
+    }
+}
+
+fn alice() -> AccountId {
+    "alice.near".parse().unwrap()
+}
+
+fn oracle() -> AccountId {
+    "oracle.near".parse().unwrap()
+}
+
+fn setup() -> ReceiptExecutor {
+    let mut executor = ReceiptExecutor::new();
+    executor.create_account(alice(), NearToken::from_near(10));
+    executor.create_account(oracle(), NearToken::from_near(10));
+    executor.deploy(&oracle(), native_contract!(Oracle { request, respond, on_response }));
+    executor
+}
+
+fn call(
+    executor: &mut ReceiptExecutor,
+    method_name: &str,
+    args: near_sdk::serde_json::Value,
+) -> near_sdk::test_utils::ExecutionOutcome {
+    executor.call(
+        alice(),
+        oracle(),
+        method_name,
+        args.to_string().into_bytes(),
+        NearToken::from_near(0),
+        Gas::from_tgas(100),
+    )
+}
+
+#[test]
+fn resume_yielded_promise() {
+    let mut executor = setup();
+    assert_eq!(call(&mut executor, "request", json!({ "cached": 7 })).json::<String>(), "7");
+
+    let outcome = call(&mut executor, "request", json!({}));
+    assert_eq!(outcome.receipts.len(), 1);
+    assert!(outcome.result.unwrap_err().contains("waiting for a yielded promise"));
+    let yielded = &executor.yielded_receipts()[0];
+    assert_eq!(yielded.receiver_id, oracle());
+    assert_eq!(yielded.status, YieldStatus::Pending);
+
+    // The callback is executed as part of the call resuming the promise.
+    let outcome = call(&mut executor, "respond", json!({ "price": 42 }));
+    assert!(outcome.json::<bool>());
+    assert_eq!(outcome.receipts.len(), 2);
+    assert_eq!(outcome.receipts[1].receiver_id, oracle());
+    assert_eq!(outcome.receipts[1].predecessor_id, oracle());
+    assert_eq!(executor.yielded_receipts()[0].status, YieldStatus::Resumed(b"42".to_vec()));
+}
+
+#[test]
+fn time_out_yielded_promise() {
+    let mut executor = setup();
+    call(&mut executor, "request", json!({}));
+    let data_id = executor.yielded_receipts()[0].data_id;
+
+    let outcome = executor.time_out(&data_id);
+    assert_eq!(outcome.json::<String>(), "timed out");
+    assert_eq!(executor.yielded_receipts()[0].status, YieldStatus::TimedOut);
+
+    // The promise can't be resumed anymore.
+    let outcome = call(&mut executor, "respond", json!({ "price": 42 }));
+    assert!(!outcome.json::<bool>());
+    assert_eq!(outcome.receipts.len(), 1);
+}